@@ -0,0 +1,19 @@
+//!Minimal axum server with tracing layer, printing spans to stdout
+//!
+//!Run with `cargo run --example axum_server` and query it:
+//!
+//!```text
+//!curl -H 'X-Forwarded-For: 203.0.113.7' http://127.0.0.1:8080/hello
+//!```
+
+#[allow(dead_code)]
+mod common;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    common::init_tracing();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await.expect("to bind 127.0.0.1:8080");
+    tracing::info!("Listening on {}", listener.local_addr().expect("to have local address"));
+    axum::serve(listener, common::router()).await.expect("server to run");
+}
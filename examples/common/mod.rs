@@ -0,0 +1,136 @@
+//!Router shared by examples and smoke test
+//!
+//!Hooks of the middleware are invoked in following order:
+//!
+//!1. [LayerContext::extract_client_ip] - while span fields are recorded, before request is passed further;
+//!2. [LayerContext::on_request] - once span is fully recorded, right before calling inner service;
+//!3. Inner service (i.e. handler) within request's span;
+//!4. [LayerContext::on_response_ok] or [LayerContext::on_response_error] - once inner service completes;
+//!5. [HttpRequestLayer::with_on_complete] callback - last, after response status is recorded.
+
+use std::net::IpAddr;
+
+use tower_http_tracing::{http, HttpRequestLayer, HttpRequestService, LayerContext, RequestInfo, RequestSummary, X_FORWARDED_FOR};
+use tower_http_tracing::body::{ResponseBodyLayer, ResponseBodyService};
+use tower_layer::Layer;
+
+tower_http_tracing::make_request_spanner!(request_span("request", tracing::Level::INFO));
+
+///gRPC health check method
+pub const GRPC_HEALTH_CHECK: &str = "/grpc.health.v1.Health/Check";
+//Length prefixed `HealthCheckResponse { status: SERVING }`
+const GRPC_HEALTH_SERVING: &[u8] = &[0, 0, 0, 0, 2, 0x08, 0x01];
+
+#[derive(Clone)]
+pub struct ExampleContext;
+
+impl LayerContext for ExampleContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[&http::header::CONTENT_TYPE, &http::header::ACCEPT];
+
+    //Only trust `X-Forwarded-For` when you're behind proxy which overwrites it
+    fn extract_client_ip(&self, _: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
+        let forwarded_for = parts.headers.get(X_FORWARDED_FOR)?.to_str().ok()?;
+        forwarded_for.split(',').next()?.trim().parse().ok()
+    }
+
+    fn on_request<T>(&self, _: &tracing::Span, _: &http::Request<T>) {
+        tracing::info!("hook: on_request");
+    }
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, response: &mut http::Response<T>) {
+        tracing::info!(status = response.status().as_u16(), "hook: on_response_ok");
+    }
+
+    fn on_response_error(&self, _: &tracing::Span, error: &impl std::error::Error) {
+        tracing::info!(%error, "hook: on_response_error");
+    }
+}
+
+fn on_complete(span: &tracing::Span, summary: &RequestSummary) {
+    tracing::info!(parent: span, %summary, "hook: on_complete");
+}
+
+async fn hello(request: axum::extract::Request) -> String {
+    tracing::info!("handler: hello");
+    match request.extensions().get::<RequestInfo>() {
+        Some(info) => format!("Hello, request_id={}", info.request_id),
+        None => "Hello".to_owned(),
+    }
+}
+
+//Replies with trailers-only response, as status is known upfront
+async fn grpc_health_check() -> http::Response<axum::body::Body> {
+    tracing::info!("handler: grpc_health_check");
+    http::Response::builder().header(http::header::CONTENT_TYPE, "application/grpc")
+                             .header(tower_http_tracing::grpc::GRPC_STATUS, "0")
+                             .body(axum::body::Body::from(GRPC_HEALTH_SERVING))
+                             .expect("valid response")
+}
+
+///Installs subscriber, which prints events to stdout
+///
+///With `opentelemetry` feature spans are exported to stdout via opentelemetry's stdout exporter, otherwise they are printed once closed
+#[cfg(feature = "opentelemetry")]
+pub fn init_tracing() {
+    use tower_http_tracing::opentelemetry::trace::TracerProvider;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    //Spans are exported as soon as they end, while tracer keeps provider alive
+    let provider = tower_http_tracing::opentelemetry::sdk::trace::SdkTracerProvider::builder().with_simple_exporter(opentelemetry_stdout::SpanExporter::default())
+                                                                                             .build();
+    let tracer = provider.tracer("tower-http-tracing-example");
+    tracing_subscriber::registry().with(tracing_subscriber::fmt::layer())
+                                  .with(tower_http_tracing::opentelemetry::tracing_opentelemetry::layer().with_tracer(tracer))
+                                  .init();
+}
+
+///Installs subscriber, which prints events to stdout
+///
+///With `opentelemetry` feature spans are exported to stdout via opentelemetry's stdout exporter, otherwise they are printed once closed
+#[cfg(not(feature = "opentelemetry"))]
+pub fn init_tracing() {
+    tracing_subscriber::fmt().with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE).init();
+}
+
+///Creates tracing layer shared by all examples
+pub fn layer() -> HttpRequestLayer<ExampleContext> {
+    HttpRequestLayer::new(request_span, ExampleContext).with_on_complete(on_complete)
+}
+
+///Creates router with tracing layer installed
+pub fn router() -> axum::Router {
+    axum::Router::new().route("/hello", axum::routing::get(hello))
+                       .route(GRPC_HEALTH_CHECK, axum::routing::post(grpc_health_check))
+                       .layer(layer())
+}
+
+///Creates tracing layer for tonic, which places status of successful call into trailers, requiring [ResponseBodyLayer] to record it
+pub fn tonic_layer() -> tower_layer::Stack<ResponseBodyLayer, HttpRequestLayer<ExampleContext>> {
+    tower_layer::Stack::new(ResponseBodyLayer::new(), layer())
+}
+
+///Creates tonic's gRPC health service with tracing layer installed
+pub fn tonic_health_service() -> HttpRequestService<ResponseBodyService<tonic::service::Routes>, ExampleContext> {
+    let (_, health) = tonic_health::server::health_reporter();
+    tonic_layer().layer(tonic::service::Routes::new(health).prepare())
+}
+
+///Creates gRPC health check request of the `service`, which name must be shorter than 126 bytes
+pub fn grpc_health_check_request(service: &str) -> http::Request<axum::body::Body> {
+    //Length prefixed `HealthCheckRequest { service }`
+    let mut message = vec![0, 0, 0, 0];
+    match service.len() {
+        0 => message.push(0),
+        len => {
+            message.push(len as u8 + 2);
+            message.extend_from_slice(&[0x0a, len as u8]);
+            message.extend_from_slice(service.as_bytes());
+        }
+    }
+    http::Request::post(GRPC_HEALTH_CHECK).version(http::Version::HTTP_2)
+                                          .header(http::header::CONTENT_TYPE, "application/grpc")
+                                          .header("grpc-timeout", "1S")
+                                          .body(axum::body::Body::from(message))
+                                          .expect("valid request")
+}
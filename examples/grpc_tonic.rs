@@ -0,0 +1,24 @@
+//!gRPC server built with tonic, exporting spans to stdout via opentelemetry's stdout exporter
+//!
+//!Run with `cargo run --example grpc_tonic --features opentelemetry` and query it:
+//!
+//!```text
+//!grpcurl -plaintext 127.0.0.1:50051 grpc.health.v1.Health/Check
+//!```
+
+#[allow(dead_code)]
+mod common;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    common::init_tracing();
+
+    let (_, health) = tonic_health::server::health_reporter();
+    let addr = "127.0.0.1:50051".parse().expect("valid address");
+    tracing::info!("Listening on {addr}");
+    tonic::transport::Server::builder().layer(common::tonic_layer())
+                                       .add_service(health)
+                                       .serve(addr)
+                                       .await
+                                       .expect("server to run");
+}
@@ -0,0 +1,33 @@
+//!Fires few requests, including gRPC health check, at the example router in-process and prints resulting spans
+//!
+//!Run with `cargo run --example load_client`
+
+mod common;
+
+use tower::ServiceExt;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    common::init_tracing();
+
+    let router = common::router();
+    for client_ip in ["203.0.113.7", "2001:db8::1"] {
+        let request = http::Request::get("/hello").header(tower_http_tracing::X_FORWARDED_FOR, client_ip)
+                                                  .body(axum::body::Body::empty())
+                                                  .expect("valid request");
+        let response = router.clone().oneshot(request).await.expect("infallible");
+        println!("GET /hello => {}", response.status());
+    }
+
+    let response = router.oneshot(common::grpc_health_check_request("")).await.expect("infallible");
+    println!("POST {} => grpc-status {:?}", common::GRPC_HEALTH_CHECK, response.headers().get(tower_http_tracing::grpc::GRPC_STATUS));
+
+    //Same check served by tonic, which places status of successful call into trailers
+    for service in ["", "unknown"] {
+        let response = common::tonic_health_service().oneshot(common::grpc_health_check_request(service)).await.expect("infallible");
+        let (parts, body) = response.into_parts();
+        let trailers = http_body_util::BodyExt::collect(body).await.expect("to read body").trailers().cloned();
+        let status = parts.headers.get(tower_http_tracing::grpc::GRPC_STATUS).or_else(|| trailers.as_ref()?.get(tower_http_tracing::grpc::GRPC_STATUS));
+        println!("tonic POST {} service={service:?} => grpc-status {status:?}", common::GRPC_HEALTH_CHECK);
+    }
+}
@@ -0,0 +1,76 @@
+//! axum integration
+//!
+//! Provides extractors for request's information, populated by [HttpRequestLayer](../struct.HttpRequestLayer.html).
+//!
+//! Extraction fails with `500 Internal Server Error` if layer is not installed.
+//!
+//! ## Usage
+//!
+//!```rust
+//!use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestInfo};
+//!use tower_http_tracing::axum::RequestSpanHandle;
+//!
+//!make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+//!
+//!async fn handler(info: RequestInfo, span: RequestSpanHandle) -> String {
+//!    span.span().record("url.path", "/overridden");
+//!    info.request_id.to_string()
+//!}
+//!
+//!let router: axum::Router = axum::Router::new().route("/", axum::routing::get(handler))
+//!                                              .layer(HttpRequestLayer::new_simple(make_my_request_span));
+//!```
+
+use core::ops;
+
+pub use axum_core;
+use axum_core::extract::FromRequestParts;
+use axum_core::response::{IntoResponse, Response};
+
+use crate::{RequestInfo, SpanHandle};
+
+#[derive(Debug)]
+///Rejection of extractors, when [HttpRequestLayer](../struct.HttpRequestLayer.html) is not installed
+pub struct MissingLayer(&'static str);
+
+impl IntoResponse for MissingLayer {
+    #[inline]
+    fn into_response(self) -> Response {
+        (http::StatusCode::INTERNAL_SERVER_ERROR, self.0).into_response()
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestInfo {
+    type Rejection = MissingLayer;
+
+    async fn from_request_parts(parts: &mut http::request::Parts, _: &S) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<RequestInfo>() {
+            Some(info) => Ok(info.clone()),
+            None => Err(MissingLayer("RequestInfo is missing: HttpRequestLayer is not installed")),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+///Extractor of request's [SpanHandle]
+pub struct RequestSpanHandle(pub SpanHandle);
+
+impl ops::Deref for RequestSpanHandle {
+    type Target = SpanHandle;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestSpanHandle {
+    type Rejection = MissingLayer;
+
+    async fn from_request_parts(parts: &mut http::request::Parts, _: &S) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<SpanHandle>() {
+            Some(handle) => Ok(Self(handle.clone())),
+            None => Err(MissingLayer("SpanHandle is missing: HttpRequestLayer is not installed")),
+        }
+    }
+}
@@ -0,0 +1,777 @@
+//! Body instrumentation
+//!
+//! Body of the request can only be observed by wrapping it, which changes type of request passed to the inner service.
+//! Hence it is provided as separate [BodyLayer], which must be placed after (i.e. inside of) [HttpRequestLayer](../struct.HttpRequestLayer.html)
+//! in order to record into request's span, which it finds via [SpanHandle](../struct.SpanHandle.html) extension.
+//!
+//! ## Usage
+//!
+//!```rust
+//!use tower_http_tracing::{HttpRequestLayer, make_request_spanner};
+//!use tower_http_tracing::body::BodyLayer;
+//!
+//!make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+//!
+//!let layers = (HttpRequestLayer::new_simple(make_my_request_span), BodyLayer::new());
+//!```
+//!
+//! Similarly, response's body is instrumented by separate [ResponseBodyLayer], so that responses are left intact unless it is used.
+//!
+//!```rust
+//!use tower_http_tracing::{http, HttpRequestLayer, make_request_spanner};
+//!use tower_http_tracing::body::ResponseBodyLayer;
+//!
+//!make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+//!
+//!let layers = (HttpRequestLayer::new_simple(make_my_request_span), ResponseBodyLayer::new().with_inspect_trailers(&[http::HeaderName::from_static("server-timing")]));
+//!```
+
+use std::sync::Arc;
+use core::{future::Future, pin::Pin, task};
+use std::io::IoSlice;
+
+use bytes::Buf;
+use http_body::Body;
+
+use crate::{DeferredCompletion, SpanHandle};
+use crate::clock::Clock;
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+//Number of digest bytes recorded
+const HASH_RECORD_LEN: usize = 8;
+//Number of slices of single data frame to inspect without allocation
+pub(crate) const MAX_DATA_SLICES: usize = 16;
+
+///Incremental hasher of body's content
+pub trait BodyHasher: Send {
+    ///Feeds `chunk` of body into hasher
+    fn update(&mut self, chunk: &[u8]);
+    ///Finishes hashing, returning digest
+    fn finish(self: Box<Self>) -> [u8; 32];
+}
+
+///Alias to function signature required to create body hasher
+pub type MakeBodyHasher = fn() -> Box<dyn BodyHasher>;
+
+#[derive(Copy, Clone, Debug, Default)]
+///Marker extension to opt request out of body instrumentation
+///
+///Request's body is opted out if marker is inserted into request's extensions before it reaches [BodyLayer] (e.g. by routing layer).
+///
+///Response's body is opted out if marker is inserted into request's extensions before it reaches [ResponseBodyLayer],
+///or if handler inserts marker into response's extensions, which is checked by [ResponseBodyLayer] before body is wrapped.
+///In this case request is completed once response is returned, and `http.response.body.size` is only populated from `Content-Length`.
+///
+///Opted out body is passed through as it is, without any inspection.
+pub struct DisableBodyInstrumentation;
+
+#[derive(Clone, Default)]
+struct Options {
+    request_body_hash: Option<(usize, MakeBodyHasher)>,
+    no_instrumentation_paths: &'static [&'static str],
+    first_byte_delay: bool,
+    track_body: bool,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Options {
+    #[inline(always)]
+    fn instant(&self) -> std::time::Instant {
+        match &self.clock {
+            Some(clock) => clock.instant(),
+            None => std::time::Instant::now(),
+        }
+    }
+
+    #[inline]
+    fn is_disabled(&self, parts: &http::request::Parts) -> bool {
+        parts.extensions.get::<DisableBodyInstrumentation>().is_some()
+        || self.no_instrumentation_paths.iter().any(|prefix| parts.uri.path().starts_with(prefix))
+    }
+}
+
+#[derive(Clone, Default)]
+///Tower layer to instrument request's body
+pub struct BodyLayer {
+    options: Arc<Options>,
+}
+
+impl BodyLayer {
+    #[inline]
+    ///Creates new layer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    ///Enables hashing of request's body, which is recorded as `http.request.body.sha256` (first 16 hex characters of digest) once body is fully consumed by the inner service
+    ///
+    ///Body is never buffered, instead content is hashed as inner service pulls it.
+    ///If body exceeds `max_bytes`, then hashing is aborted and `http.request.body.hash_skipped` is set to `true`.
+    ///Nothing is recorded if inner service doesn't consume body till the end.
+    pub fn with_request_body_hash(mut self, max_bytes: usize, make_hasher: MakeBodyHasher) -> Self {
+        Arc::make_mut(&mut self.options).request_body_hash = Some((max_bytes, make_hasher));
+        self
+    }
+
+    #[inline]
+    ///Specifies path prefixes of requests, which body is not instrumented
+    ///
+    ///Intended for streaming routes (e.g. proxies) that cannot afford body inspection.
+    ///Per request opt-out is possible via [DisableBodyInstrumentation] extension.
+    pub fn with_no_body_instrumentation_paths(mut self, prefixes: &'static [&'static str]) -> Self {
+        Arc::make_mut(&mut self.options).no_instrumentation_paths = prefixes;
+        self
+    }
+
+    #[inline]
+    ///Enables recording of time between request being passed to inner service and arrival of the first chunk of its body (or its end, if body is empty) as `http.request.body.first_byte_delay_ms`
+    ///
+    ///This is particularly useful for requests with `Expect: 100-continue`, as client waits for interim response before sending body.
+    pub fn with_request_first_byte_delay(mut self) -> Self {
+        Arc::make_mut(&mut self.options).first_byte_delay = true;
+        self
+    }
+
+    #[inline]
+    ///Specifies whether to count bytes of request's body as inner service reads it
+    ///
+    ///Once body is fully read, number of received bytes is recorded as `http.request.body.size` (replacing value of `Content-Length`, if any).
+    ///If body is dropped before its end, while `Content-Length` promised more bytes than received, warning is emitted instead.
+    ///
+    ///Disabled by default
+    pub fn with_track_request_body(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).track_body = enabled;
+        self
+    }
+
+    #[inline]
+    ///Specifies clock used to measure time, by default system clock is used
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        Arc::make_mut(&mut self.options).clock = Some(Arc::new(clock));
+        self
+    }
+}
+
+impl<S> tower_layer::Layer<S> for BodyLayer {
+    type Service = BodyService<S>;
+    #[inline(always)]
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyService {
+            options: self.options.clone(),
+            inner,
+        }
+    }
+}
+
+#[derive(Clone)]
+///Tower service to instrument request's body
+pub struct BodyService<S> {
+    options: Arc<Options>,
+    inner: S,
+}
+
+impl<B: Body, S: tower_service::Service<http::Request<RequestBody<B>>>> tower_service::Service<http::Request<B>> for BodyService<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, ctx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(ctx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let (parts, body) = req.into_parts();
+        if self.options.is_disabled(&parts) {
+            return self.inner.call(http::Request::from_parts(parts, RequestBody {
+                inner: body,
+                state: None,
+            }));
+        }
+
+        let span = parts.extensions.get::<SpanHandle>().map_or_else(tracing::Span::none, |handle| handle.span().clone());
+        let (hash, hash_skipped) = match self.options.request_body_hash {
+            Some((max_bytes, make_hasher)) => match body.size_hint().lower() {
+                //Recorded once body is polled to keep order of fields
+                len if len > max_bytes as u64 => (None, true),
+                _ => (Some(BodyHash {
+                    hasher: make_hasher(),
+                    max_bytes,
+                    consumed: 0,
+                    spare_slices: Vec::new(),
+                }), false)
+            },
+            None => (None, false),
+        };
+
+        let first_byte_since = match self.options.first_byte_delay {
+            true => Some(self.options.instant()),
+            false => None,
+        };
+        let received = match self.options.track_body {
+            true => Some(BodyReceived {
+                span: span.clone(),
+                expected: crate::headers::content_length(&parts.headers),
+                bytes: 0,
+                //Empty body might be never polled
+                is_complete: body.is_end_stream(),
+            }),
+            false => None,
+        };
+
+        self.inner.call(http::Request::from_parts(parts, RequestBody {
+            inner: body,
+            state: Some(RequestBodyState {
+                span,
+                hash,
+                hash_skipped,
+                first_byte_since,
+                received,
+                options: self.options.clone(),
+            }),
+        }))
+    }
+}
+
+struct BodyHash {
+    hasher: Box<dyn BodyHasher>,
+    max_bytes: usize,
+    consumed: usize,
+    //Storage for data exposing more than `MAX_DATA_SLICES` slices, kept empty between frames to reuse its allocation
+    spare_slices: Vec<IoSlice<'static>>,
+}
+
+//Reuses allocation of empty vector for slices of another lifetime
+fn reuse_slices<'a>(slices: Vec<IoSlice<'_>>) -> Vec<IoSlice<'a>> {
+    slices.into_iter().map(|_| IoSlice::new(&[])).collect()
+}
+
+impl BodyHash {
+    //Returns `false` if body cannot be hashed
+    fn update(&mut self, data: &impl Buf) -> bool {
+        let remaining = data.remaining();
+        self.consumed = self.consumed.saturating_add(remaining);
+        if self.consumed > self.max_bytes {
+            return false;
+        }
+
+        let mut slices = [IoSlice::new(&[]); MAX_DATA_SLICES];
+        let len = data.chunks_vectored(&mut slices);
+        if len < slices.len() {
+            return self.update_slices(&slices[..len], remaining);
+        }
+
+        //Data cannot be advanced by reference, so all of its slices must be exposed at once.
+        //Number of non-empty slices is bounded by size of data, which is within `max_bytes`, and storage grows only once per body
+        let mut slices = reuse_slices(core::mem::take(&mut self.spare_slices));
+        let mut capacity = slices.capacity().max(MAX_DATA_SLICES.saturating_mul(2)).min(remaining);
+        let result = loop {
+            slices.resize(capacity, IoSlice::new(&[]));
+            let len = data.chunks_vectored(&mut slices);
+            if len < capacity || capacity == remaining {
+                break self.update_slices(&slices[..len], remaining);
+            }
+            capacity = capacity.saturating_mul(2).min(remaining);
+        };
+        slices.clear();
+        self.spare_slices = reuse_slices(slices);
+        result
+    }
+
+    //Returns `false` if slices do not cover whole data (i.e. `Buf` exposes only part of it)
+    fn update_slices(&mut self, slices: &[IoSlice<'_>], remaining: usize) -> bool {
+        let mut hashed = 0;
+        for slice in slices {
+            self.hasher.update(slice);
+            hashed += slice.len();
+        }
+        hashed == remaining
+    }
+
+    fn record(self, span: &tracing::Span) {
+        let digest = self.hasher.finish();
+        let mut out = [0u8; HASH_RECORD_LEN * 2];
+        for (idx, byt) in digest[..HASH_RECORD_LEN].iter().enumerate() {
+            out[idx * 2] = HEX[(byt >> 4) as usize];
+            out[idx * 2 + 1] = HEX[(byt & 0xf) as usize];
+        }
+        //Hex characters are always valid utf-8
+        if let Ok(out) = core::str::from_utf8(&out) {
+            span.record("http.request.body.sha256", out);
+        }
+    }
+}
+
+//Bytes of request's body received so far, reported once body ends or is dropped
+struct BodyReceived {
+    span: tracing::Span,
+    //Value of `Content-Length`
+    expected: Option<u64>,
+    bytes: u64,
+    is_complete: bool,
+}
+
+impl Drop for BodyReceived {
+    fn drop(&mut self) {
+        if self.is_complete {
+            self.span.record("http.request.body.size", self.bytes);
+        } else if let Some(expected) = self.expected.filter(|expected| *expected > self.bytes) {
+            tracing::warn!(target: crate::targets::SPAN, parent: &self.span, received = self.bytes, expected, "Request body is dropped before its end");
+        }
+    }
+}
+
+struct RequestBodyState {
+    span: tracing::Span,
+    hash: Option<BodyHash>,
+    hash_skipped: bool,
+    first_byte_since: Option<std::time::Instant>,
+    received: Option<BodyReceived>,
+    options: Arc<Options>,
+}
+
+///Request's body wrapper, instrumenting it according to [BodyLayer] configuration
+pub struct RequestBody<B> {
+    inner: B,
+    //Absent if body is opted out of instrumentation
+    state: Option<RequestBodyState>,
+}
+
+impl<B> RequestBody<B> {
+    #[inline(always)]
+    ///Access underlying body
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    #[inline(always)]
+    ///Returns underlying body
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: Body> Body for RequestBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let (mut inner, state) = unsafe {
+            let this = self.get_unchecked_mut();
+            (Pin::new_unchecked(&mut this.inner), &mut this.state)
+        };
+        let RequestBodyState { span, hash, hash_skipped, first_byte_since, received, options } = match state {
+            Some(state) => state,
+            None => return inner.poll_frame(ctx),
+        };
+
+        let result = inner.as_mut().poll_frame(ctx);
+        if result.is_ready() {
+            if let Some(since) = first_byte_since.take() {
+                let delay = options.instant().saturating_duration_since(since);
+                span.record("http.request.body.first_byte_delay_ms", delay.as_millis() as u64);
+            }
+            if core::mem::take(hash_skipped) {
+                span.record("http.request.body.hash_skipped", true);
+            }
+        }
+        match &result {
+            task::Poll::Ready(Some(Ok(frame))) => if let Some(state) = received.as_mut() {
+                if let Some(data) = frame.data_ref() {
+                    state.bytes = state.bytes.saturating_add(data.remaining() as u64);
+                }
+                //Consumer may stop polling once body signals its end
+                if frame.is_trailers() || inner.is_end_stream() {
+                    state.is_complete = true;
+                    *received = None;
+                }
+            },
+            task::Poll::Ready(None) => if let Some(mut state) = received.take() {
+                state.is_complete = true;
+            },
+            task::Poll::Ready(Some(Err(_))) | task::Poll::Pending => (),
+        }
+        match &result {
+            task::Poll::Ready(Some(Ok(frame))) => if let Some(data) = frame.data_ref() {
+                if let Some(state) = hash {
+                    if !state.update(data) {
+                        *hash = None;
+                        span.record("http.request.body.hash_skipped", true);
+                    } else if inner.is_end_stream() {
+                        //Consumer may stop polling once body signals its end
+                        if let Some(state) = hash.take() {
+                            state.record(span);
+                        }
+                    }
+                }
+            },
+            task::Poll::Ready(None) => if let Some(state) = hash.take() {
+                state.record(span);
+            },
+            task::Poll::Ready(Some(Err(_))) => {
+                *hash = None;
+            },
+            task::Poll::Pending => (),
+        }
+        result
+    }
+
+    #[inline(always)]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[derive(Clone, Default)]
+struct ResponseOptions {
+    inspect_trailers: Option<Arc<[http::HeaderName]>>,
+    no_instrumentation_paths: &'static [&'static str],
+    track_body: bool,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl ResponseOptions {
+    #[inline(always)]
+    fn instant(&self) -> std::time::Instant {
+        match &self.clock {
+            Some(clock) => clock.instant(),
+            None => std::time::Instant::now(),
+        }
+    }
+
+    #[inline]
+    fn is_disabled<B>(&self, req: &http::Request<B>) -> bool {
+        req.extensions().get::<DisableBodyInstrumentation>().is_some()
+        || self.no_instrumentation_paths.iter().any(|prefix| req.uri().path().starts_with(prefix))
+    }
+}
+
+#[derive(Clone, Default)]
+///Tower layer to instrument response's body
+///
+///Must be placed after (i.e. inside of) [HttpRequestLayer](../struct.HttpRequestLayer.html), same as [BodyLayer]
+///
+///For gRPC responses, which carry `grpc-status` only in trailers, status is recorded and request is completed once body yields them,
+///regardless of whether body is tracked. Trailers of base64 encoded gRPC-Web body (`application/grpc-web-text`) are not decoded.
+pub struct ResponseBodyLayer {
+    options: Arc<ResponseOptions>,
+}
+
+impl ResponseBodyLayer {
+    #[inline]
+    ///Creates new layer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    ///Specifies response's trailers to inspect via `http.response.trailers` attribute
+    ///
+    ///Trailers are recorded once body yields them, as long as any of specified trailers is present.
+    ///Nothing is recorded if body is not consumed till trailers (e.g. client disconnected)
+    pub fn with_inspect_trailers(mut self, trailers: &[http::HeaderName]) -> Self {
+        Arc::make_mut(&mut self.options).inspect_trailers = Some(trailers.into());
+        self
+    }
+
+    #[inline]
+    ///Specifies path prefixes of requests, which response's body is not instrumented
+    ///
+    ///Intended for streaming routes (e.g. proxies) that cannot afford body inspection.
+    ///Per request opt-out is possible via [DisableBodyInstrumentation] extension.
+    pub fn with_no_body_instrumentation_paths(mut self, prefixes: &'static [&'static str]) -> Self {
+        Arc::make_mut(&mut self.options).no_instrumentation_paths = prefixes;
+        self
+    }
+
+    #[inline]
+    ///Specifies whether to count bytes of response's body as it is streamed
+    ///
+    ///Once body ends or is dropped, number of bytes streamed so far is recorded as `http.response.body.size` (replacing value of `Content-Length`, if any),
+    ///followed by event with target [MILESTONE](../targets/constant.MILESTONE.html), which contains `bytes`, `elapsed_ms` since request was passed to inner service (i.e. time to last byte)
+    ///and `completed`, which is `false` if body is dropped before its end or fails.
+    ///
+    ///[Completion callback](../struct.HttpRequestLayer.html#method.with_on_complete) is deferred until then as well.
+    ///
+    ///Disabled by default
+    pub fn with_track_response_body(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).track_body = enabled;
+        self
+    }
+
+    #[inline]
+    ///Specifies clock used to measure time, by default system clock is used
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        Arc::make_mut(&mut self.options).clock = Some(Arc::new(clock));
+        self
+    }
+}
+
+impl<S> tower_layer::Layer<S> for ResponseBodyLayer {
+    type Service = ResponseBodyService<S>;
+    #[inline(always)]
+    fn layer(&self, inner: S) -> Self::Service {
+        ResponseBodyService {
+            options: self.options.clone(),
+            inner,
+        }
+    }
+}
+
+#[derive(Clone)]
+///Tower service to instrument response's body
+pub struct ResponseBodyService<S> {
+    options: Arc<ResponseOptions>,
+    inner: S,
+}
+
+impl<ReqBody, ResBody: Body, S: tower_service::Service<http::Request<ReqBody>, Response = http::Response<ResBody>>> tower_service::Service<http::Request<ReqBody>> for ResponseBodyService<S> {
+    type Response = http::Response<ResponseBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseBodyFut<S::Future>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, ctx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(ctx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        if self.options.is_disabled(&req) {
+            return ResponseBodyFut {
+                inner: self.inner.call(req),
+                span: tracing::Span::none(),
+                options: self.options.clone(),
+                protocol: crate::Protocol::Http,
+                since: None,
+                disabled: true,
+            };
+        }
+
+        let span = match self.options.inspect_trailers.is_some() || self.options.track_body {
+            true => req.extensions().get::<SpanHandle>().map_or_else(tracing::Span::none, |handle| handle.span().clone()),
+            false => tracing::Span::none(),
+        };
+        let since = match self.options.track_body {
+            true => Some(self.options.instant()),
+            false => None,
+        };
+        let protocol = req.extensions().get::<crate::RequestInfo>().map_or(crate::Protocol::Http, |info| info.protocol);
+        ResponseBodyFut {
+            inner: self.inner.call(req),
+            span,
+            options: self.options.clone(),
+            protocol,
+            since,
+            disabled: false,
+        }
+    }
+}
+
+///Response future of [ResponseBodyService]
+pub struct ResponseBodyFut<F> {
+    inner: F,
+    span: tracing::Span,
+    options: Arc<ResponseOptions>,
+    protocol: crate::Protocol,
+    //Time when request is passed to inner service, if body is tracked
+    since: Option<std::time::Instant>,
+    //Whether body is opted out of instrumentation by request
+    disabled: bool,
+}
+
+impl<B: Body, E, F: Future<Output = Result<http::Response<B>, E>>> Future for ResponseBodyFut<F> {
+    type Output = Result<http::Response<ResponseBody<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let (inner, span, options, protocol, since, disabled) = unsafe {
+            let this = self.get_unchecked_mut();
+            (Pin::new_unchecked(&mut this.inner), &mut this.span, &this.options, this.protocol, &mut this.since, this.disabled)
+        };
+
+        match inner.poll(ctx) {
+            task::Poll::Ready(result) => task::Poll::Ready(result.map(|resp| {
+                let (mut parts, inner) = resp.into_parts();
+                let span = core::mem::replace(span, tracing::Span::none());
+                if disabled || parts.extensions.get::<DisableBodyInstrumentation>().is_some() {
+                    return http::Response::from_parts(parts, ResponseBody {
+                        inner,
+                        state: None,
+                    });
+                }
+                //gRPC status, which is not sent in headers, is expected in trailers
+                let awaits_status = match protocol {
+                    crate::Protocol::Http => false,
+                    crate::Protocol::Grpc | crate::Protocol::GrpcWeb => !parts.headers.contains_key(crate::grpc::GRPC_STATUS),
+                };
+                let since = since.take();
+                let progress = match since.is_some() || awaits_status {
+                    true => {
+                        //Request is completed by the middleware once body ends
+                        let completion = DeferredCompletion::default();
+                        parts.extensions.insert(completion.clone());
+                        //gRPC-Web sends trailers as the last message of body, unless it is base64 encoded
+                        let grpc_web_trailers = match protocol {
+                            crate::Protocol::GrpcWeb if awaits_status => match parts.headers.get(http::header::CONTENT_TYPE) {
+                                Some(content_type) if content_type.as_bytes().starts_with(b"application/grpc-web-text") => None,
+                                _ => Some(crate::grpc::GrpcWebTrailers::default()),
+                            },
+                            _ => None,
+                        };
+                        Some(BodyProgress {
+                            span: span.clone(),
+                            since,
+                            options: options.clone(),
+                            bytes: 0,
+                            //Empty body might be never polled
+                            is_complete: inner.is_end_stream(),
+                            grpc_web_trailers,
+                            completion,
+                        })
+                    },
+                    false => None,
+                };
+                http::Response::from_parts(parts, ResponseBody {
+                    inner,
+                    state: Some(ResponseBodyState {
+                        span,
+                        inspect_trailers: options.inspect_trailers.clone(),
+                        progress,
+                    }),
+                })
+            })),
+            task::Poll::Pending => task::Poll::Pending,
+        }
+    }
+}
+
+//Bytes of response's body streamed so far, reported once body ends or is dropped
+struct BodyProgress {
+    span: tracing::Span,
+    //Absent if body is not tracked, but only awaits gRPC status
+    since: Option<std::time::Instant>,
+    options: Arc<ResponseOptions>,
+    bytes: u64,
+    is_complete: bool,
+    grpc_web_trailers: Option<crate::grpc::GrpcWebTrailers>,
+    completion: DeferredCompletion,
+}
+
+impl Drop for BodyProgress {
+    fn drop(&mut self) {
+        let since = match self.since {
+            Some(since) => since,
+            None => return self.completion.complete(None),
+        };
+        if !self.span.is_none() {
+            let elapsed_ms = self.options.instant().saturating_duration_since(since).as_millis() as u64;
+            self.span.record("http.response.body.size", self.bytes);
+            match self.is_complete {
+                true => tracing::info!(target: crate::targets::MILESTONE, parent: &self.span, bytes = self.bytes, elapsed_ms, completed = true, "Response body is complete"),
+                false => tracing::info!(target: crate::targets::MILESTONE, parent: &self.span, bytes = self.bytes, elapsed_ms, completed = false, "Response body is dropped before its end"),
+            }
+        }
+        self.completion.complete(Some(self.bytes));
+    }
+}
+
+struct ResponseBodyState {
+    span: tracing::Span,
+    inspect_trailers: Option<Arc<[http::HeaderName]>>,
+    progress: Option<BodyProgress>,
+}
+
+///Response's body wrapper, instrumenting it according to [ResponseBodyLayer] configuration
+pub struct ResponseBody<B> {
+    inner: B,
+    //Absent if body is opted out of instrumentation
+    state: Option<ResponseBodyState>,
+}
+
+impl<B> ResponseBody<B> {
+    #[inline(always)]
+    ///Access underlying body
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    #[inline(always)]
+    ///Returns underlying body
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: Body> Body for ResponseBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let (mut inner, state) = unsafe {
+            let this = self.get_unchecked_mut();
+            (Pin::new_unchecked(&mut this.inner), &mut this.state)
+        };
+        let ResponseBodyState { span, inspect_trailers, progress } = match state {
+            Some(state) => state,
+            None => return inner.poll_frame(ctx),
+        };
+
+        let result = inner.as_mut().poll_frame(ctx);
+        match &result {
+            task::Poll::Ready(Some(Ok(frame))) => {
+                if let (Some(trailers), Some(extra_header_list)) = (frame.trailers_ref(), inspect_trailers.as_deref()) {
+                    let inspect = crate::headers::InspectHeaders {
+                        header_list: &[],
+                        extra_header_list,
+                        prefix_list: &[],
+                        headers: trailers,
+                        format: Default::default(),
+                    };
+                    if inspect.is_present() {
+                        span.record("http.response.trailers", tracing::field::debug(inspect));
+                    }
+                    //Trailers are always the last frame
+                    *inspect_trailers = None;
+                }
+                if let Some(state) = progress.as_mut() {
+                    if let Some(data) = frame.data_ref() {
+                        state.bytes = state.bytes.saturating_add(data.remaining() as u64);
+                        if let Some(trailers) = state.grpc_web_trailers.as_mut().and_then(|decoder| decoder.decode(data)) {
+                            state.completion.on_trailers(&trailers);
+                            state.grpc_web_trailers = None;
+                        }
+                    } else if let Some(trailers) = frame.trailers_ref() {
+                        state.completion.on_trailers(trailers);
+                    }
+                    //Consumer may stop polling once body signals its end
+                    if frame.is_trailers() || inner.is_end_stream() {
+                        state.is_complete = true;
+                        *progress = None;
+                    }
+                }
+            },
+            task::Poll::Ready(None) => if let Some(mut state) = progress.take() {
+                state.is_complete = true;
+            },
+            //Body cannot be consumed further, report what is streamed so far
+            task::Poll::Ready(Some(Err(_))) => *progress = None,
+            task::Poll::Pending => (),
+        }
+        result
+    }
+
+    #[inline(always)]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
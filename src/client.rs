@@ -0,0 +1,92 @@
+//! Client side utilities
+//!
+//! [RequestIdPropagationLayer] is meant to wrap HTTP client used by handler, so that downstream calls carry the same `X-Request-Id` as incoming request.
+//!
+//! ## Usage
+//!
+//!```rust
+//!use tower_http_tracing::{http, RequestInfo};
+//!use tower_http_tracing::client::RequestIdPropagationLayer;
+//!use tower_layer::Layer;
+//!
+//!fn make_client<C>(request: &http::Request<()>, client: C) -> tower_http_tracing::client::RequestIdPropagationService<C> {
+//!    match request.extensions().get::<RequestInfo>() {
+//!        Some(info) => RequestIdPropagationLayer::with_request_id(info.request_id.clone()).layer(client),
+//!        None => RequestIdPropagationLayer::new().layer(client),
+//!    }
+//!}
+//!```
+
+use core::task;
+
+use crate::{RequestId, RequestInfo, REQUEST_ID};
+
+#[derive(Clone, Default)]
+///Tower layer to set `X-Request-Id` of outgoing requests
+///
+///Request's id is taken from layer, if specified via [with_request_id](#method.with_request_id), otherwise from [RequestInfo] within outgoing request's extensions.
+///
+///Header is never overwritten if it is already present in outgoing request
+pub struct RequestIdPropagationLayer {
+    request_id: Option<RequestId>,
+}
+
+impl RequestIdPropagationLayer {
+    #[inline(always)]
+    ///Creates new layer, which takes request's id from [RequestInfo] within extensions of outgoing request
+    pub const fn new() -> Self {
+        Self {
+            request_id: None,
+        }
+    }
+
+    #[inline(always)]
+    ///Creates new layer, which sets `request_id` to all outgoing requests
+    pub const fn with_request_id(request_id: RequestId) -> Self {
+        Self {
+            request_id: Some(request_id),
+        }
+    }
+}
+
+impl<S> tower_layer::Layer<S> for RequestIdPropagationLayer {
+    type Service = RequestIdPropagationService<S>;
+    #[inline(always)]
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdPropagationService {
+            request_id: self.request_id.clone(),
+            inner,
+        }
+    }
+}
+
+#[derive(Clone)]
+///Tower service to set `X-Request-Id` of outgoing requests, created by [RequestIdPropagationLayer]
+pub struct RequestIdPropagationService<S> {
+    request_id: Option<RequestId>,
+    inner: S,
+}
+
+impl<B, S: tower_service::Service<http::Request<B>>> tower_service::Service<http::Request<B>> for RequestIdPropagationService<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, ctx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(ctx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        if !req.headers().contains_key(REQUEST_ID) {
+            let request_id = match self.request_id.as_ref() {
+                Some(request_id) => Some(request_id),
+                None => req.extensions().get::<RequestInfo>().map(|info| &info.request_id),
+            };
+            if let Some(request_id) = request_id.and_then(RequestId::to_header_value) {
+                req.headers_mut().insert(REQUEST_ID, request_id);
+            }
+        }
+        self.inner.call(req)
+    }
+}
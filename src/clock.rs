@@ -0,0 +1,85 @@
+//! Clock abstraction
+//!
+//! Middleware uses clock whenever it needs to know current time, which allows to substitute it in tests.
+
+use std::time::{Duration, Instant, SystemTime};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+///Clock interface
+pub trait Clock: Send + Sync {
+    ///Returns current wall clock time
+    fn now(&self) -> SystemTime;
+    ///Returns current monotonic time
+    fn instant(&self) -> Instant;
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+///System clock, used by default
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline(always)]
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    #[inline(always)]
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+#[derive(Debug)]
+///Manually advanced clock, intended for tests
+pub struct ManualClock {
+    now: SystemTime,
+    instant: Instant,
+    offset_nanos: AtomicU64,
+}
+
+impl ManualClock {
+    #[inline]
+    ///Creates new clock starting at `now`
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            now,
+            instant: Instant::now(),
+            offset_nanos: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    ///Advances clock by `duration`
+    pub fn advance(&self, duration: Duration) {
+        self.offset_nanos.fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn offset(&self) -> Duration {
+        Duration::from_nanos(self.offset_nanos.load(Ordering::Relaxed))
+    }
+}
+
+impl Clock for ManualClock {
+    #[inline(always)]
+    fn now(&self) -> SystemTime {
+        self.now + self.offset()
+    }
+
+    #[inline(always)]
+    fn instant(&self) -> Instant {
+        self.instant + self.offset()
+    }
+}
+
+impl<C: Clock> Clock for std::sync::Arc<C> {
+    #[inline(always)]
+    fn now(&self) -> SystemTime {
+        C::now(self)
+    }
+
+    #[inline(always)]
+    fn instant(&self) -> Instant {
+        C::instant(self)
+    }
+}
@@ -0,0 +1,65 @@
+use core::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+///Default number of connections tracked
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+struct Entry {
+    requests_seen: u64,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<u64, Entry>,
+    //Recency order: tick of last use -> connection id
+    order: BTreeMap<u64, u64>,
+    tick: u64,
+}
+
+///Tracks number of requests seen per connection, evicting least recently used connection when capacity is exceeded
+pub struct ConnectionTracker {
+    capacity: usize,
+    lru: Mutex<Lru>,
+}
+
+impl ConnectionTracker {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: cmp::max(capacity, 1),
+            lru: Mutex::new(Lru::default()),
+        }
+    }
+
+    ///Registers request on connection `id`, returning number of requests seen on it, including this one
+    pub fn track(&self, id: u64) -> u64 {
+        let mut lru = match self.lru.lock() {
+            Ok(lru) => lru,
+            Err(error) => error.into_inner(),
+        };
+        let lru = &mut *lru;
+
+        lru.tick += 1;
+        let tick = lru.tick;
+        if let Some(entry) = lru.entries.get_mut(&id) {
+            lru.order.remove(&entry.last_used);
+            lru.order.insert(tick, id);
+            entry.last_used = tick;
+            entry.requests_seen = entry.requests_seen.saturating_add(1);
+            return entry.requests_seen;
+        }
+
+        if lru.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = lru.order.pop_first() {
+                lru.entries.remove(&oldest);
+            }
+        }
+        lru.order.insert(tick, id);
+        lru.entries.insert(id, Entry {
+            requests_seen: 1,
+            last_used: tick,
+        });
+        1
+    }
+}
@@ -0,0 +1,130 @@
+//! Error categorization
+//!
+//! `error.type` is derived from error's type, which is not helpful when service uses type erased errors (e.g. `Box<dyn Error>`).
+//! [ErrorCategorizer] allows to determine category of error, which is recorded as `error.category`.
+//!
+//! ## Usage
+//!
+//!```rust
+//!use tower_http_tracing::HttpRequestLayer;
+//!use tower_http_tracing::error::{self, ErrorCategorizer};
+//!
+//!tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+//!
+//!fn my_probe(error: &(dyn std::error::Error + 'static)) -> Option<&'static str> {
+//!    error.downcast_ref::<core::fmt::Error>().map(|_| "format")
+//!}
+//!
+//!let categorizer = ErrorCategorizer::new().with_probe(my_probe).with_probe(error::io_error);
+//!let layer = HttpRequestLayer::new_simple(make_my_request_span).with_error_categorizer(categorizer);
+//!```
+
+type Error = dyn std::error::Error + 'static;
+
+///Alias to function signature required to determine error's category, typically by attempting to downcast it
+pub type ErrorProbe = fn(&Error) -> Option<&'static str>;
+
+//Looks for error of type `T` in the `error`'s source chain
+fn find<T: std::error::Error + 'static>(error: &Error) -> Option<&T> {
+    let mut error = Some(error);
+    while let Some(cause) = error {
+        if let Some(error) = cause.downcast_ref::<T>() {
+            return Some(error);
+        }
+        error = cause.source();
+    }
+    None
+}
+
+#[derive(Clone, Debug, Default)]
+///Ordered list of probes to determine error's category
+///
+///Probes are tried in order of registration and the first category found is used.
+pub struct ErrorCategorizer {
+    probes: Vec<ErrorProbe>,
+}
+
+impl ErrorCategorizer {
+    #[inline]
+    ///Creates new categorizer without probes
+    pub const fn new() -> Self {
+        Self {
+            probes: Vec::new(),
+        }
+    }
+
+    #[inline]
+    ///Adds `probe` to the end of list
+    pub fn with_probe(mut self, probe: ErrorProbe) -> Self {
+        self.probes.push(probe);
+        self
+    }
+
+    ///Determines category of the `error`, if any probe recognizes it
+    pub fn categorize(&self, error: &Error) -> Option<&'static str> {
+        self.probes.iter().find_map(|probe| probe(error))
+    }
+}
+
+///Probe for `std::io::Error`, found anywhere in the `error`'s source chain
+///
+///Category is `io.<kind>` (e.g. `io.not_found`), with unknown kinds categorized as `io.other`
+pub fn io_error(error: &Error) -> Option<&'static str> {
+    use std::io::ErrorKind;
+
+    let category = match find::<std::io::Error>(error)?.kind() {
+        ErrorKind::NotFound => "io.not_found",
+        ErrorKind::PermissionDenied => "io.permission_denied",
+        ErrorKind::ConnectionRefused => "io.connection_refused",
+        ErrorKind::ConnectionReset => "io.connection_reset",
+        ErrorKind::ConnectionAborted => "io.connection_aborted",
+        ErrorKind::NotConnected => "io.not_connected",
+        ErrorKind::AddrInUse => "io.addr_in_use",
+        ErrorKind::AddrNotAvailable => "io.addr_not_available",
+        ErrorKind::BrokenPipe => "io.broken_pipe",
+        ErrorKind::AlreadyExists => "io.already_exists",
+        ErrorKind::WouldBlock => "io.would_block",
+        ErrorKind::InvalidInput => "io.invalid_input",
+        ErrorKind::InvalidData => "io.invalid_data",
+        ErrorKind::TimedOut => "io.timed_out",
+        ErrorKind::WriteZero => "io.write_zero",
+        ErrorKind::Interrupted => "io.interrupted",
+        ErrorKind::Unsupported => "io.unsupported",
+        ErrorKind::UnexpectedEof => "io.unexpected_eof",
+        ErrorKind::OutOfMemory => "io.out_of_memory",
+        _ => "io.other",
+    };
+    Some(category)
+}
+
+#[cfg(feature = "tower")]
+///Probe for `tower::timeout::error::Elapsed`, found anywhere in the `error`'s source chain
+///
+///Category is `timeout`
+pub fn tower_timeout(error: &Error) -> Option<&'static str> {
+    find::<tower::timeout::error::Elapsed>(error).map(|_| "timeout")
+}
+
+#[cfg(feature = "hyper")]
+///Probe for `hyper::Error`, found anywhere in the `error`'s source chain
+///
+///Category is `hyper.<kind>` (e.g. `hyper.timeout`), with unknown kinds categorized as `hyper.other`
+pub fn hyper_error(error: &Error) -> Option<&'static str> {
+    let error = find::<hyper::Error>(error)?;
+    let category = if error.is_timeout() {
+        "hyper.timeout"
+    } else if error.is_canceled() {
+        "hyper.canceled"
+    } else if error.is_closed() {
+        "hyper.closed"
+    } else if error.is_incomplete_message() {
+        "hyper.incomplete_message"
+    } else if error.is_parse() {
+        "hyper.parse"
+    } else if error.is_user() {
+        "hyper.user"
+    } else {
+        "hyper.other"
+    };
+    Some(category)
+}
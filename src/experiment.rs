@@ -0,0 +1,55 @@
+use crate::headers::truncate_str;
+
+///Max length of experiment's bucket value
+pub const VALUE_MAX_LEN: usize = 32;
+///Max number of experiments recorded per request
+pub const MAX_COUNT: usize = 8;
+
+#[derive(Clone)]
+pub struct ExperimentHeader {
+    pub header: http::HeaderName,
+    names: &'static [&'static str],
+    //Span field names in the same order as `names`
+    fields: Box<[Box<str>]>,
+}
+
+impl ExperimentHeader {
+    pub fn new(header: http::HeaderName, names: &'static [&'static str]) -> Self {
+        let fields = names.iter().map(|name| format!("experiment.{name}").into_boxed_str()).collect();
+        Self {
+            header,
+            names,
+            fields,
+        }
+    }
+
+    ///Records allow-listed experiments from `name=value;name=value` header value
+    pub fn record(&self, span: &tracing::Span, headers: &http::HeaderMap) {
+        let mut recorded = 0;
+        for value in headers.get_all(&self.header) {
+            let value = match value.to_str() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+
+            for pair in value.split(';') {
+                if recorded >= MAX_COUNT {
+                    return;
+                }
+
+                let (name, value) = match pair.split_once('=') {
+                    Some((name, value)) => (name.trim(), value.trim()),
+                    None => continue,
+                };
+                if name.is_empty() || value.is_empty() {
+                    continue;
+                }
+
+                if let Some(idx) = self.names.iter().position(|allowed| *allowed == name) {
+                    span.record(&*self.fields[idx], truncate_str(value, VALUE_MAX_LEN));
+                    recorded += 1;
+                }
+            }
+        }
+    }
+}
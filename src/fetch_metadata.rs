@@ -0,0 +1,39 @@
+//Fetch metadata request headers <https://www.w3.org/TR/fetch-metadata/>
+
+const SEC_FETCH_SITE: http::HeaderName = http::HeaderName::from_static("sec-fetch-site");
+const SEC_FETCH_MODE: http::HeaderName = http::HeaderName::from_static("sec-fetch-mode");
+const SEC_FETCH_DEST: http::HeaderName = http::HeaderName::from_static("sec-fetch-dest");
+const SEC_FETCH_USER: http::HeaderName = http::HeaderName::from_static("sec-fetch-user");
+
+//Recorded in place of value outside of known vocabulary to bound cardinality
+const OTHER: &str = "other";
+
+const SITES: &[&str] = &["cross-site", "same-origin", "same-site", "none"];
+const MODES: &[&str] = &["cors", "navigate", "no-cors", "same-origin", "websocket"];
+const DESTS: &[&str] = &[
+    "audio", "audioworklet", "document", "embed", "empty", "fencedframe", "font", "frame", "iframe", "image", "json", "manifest",
+    "object", "paintworklet", "report", "script", "serviceworker", "sharedworker", "style", "track", "video", "webidentity", "worker", "xslt",
+];
+const USERS: &[&str] = &["?1"];
+
+const FIELDS: [(http::HeaderName, &str, &[&str]); 4] = [
+    (SEC_FETCH_SITE, "http.request.header.sec-fetch-site", SITES),
+    (SEC_FETCH_MODE, "http.request.header.sec-fetch-mode", MODES),
+    (SEC_FETCH_DEST, "http.request.header.sec-fetch-dest", DESTS),
+    (SEC_FETCH_USER, "http.request.header.sec-fetch-user", USERS),
+];
+
+#[inline]
+fn normalize<'a>(value: &http::HeaderValue, known: &[&'a str]) -> &'a str {
+    let value = value.as_bytes().trim_ascii();
+    known.iter().find(|known| known.as_bytes().eq_ignore_ascii_case(value)).copied().unwrap_or(OTHER)
+}
+
+///Records `Sec-Fetch-*` headers, skipping absent ones
+pub fn record(span: &tracing::Span, headers: &http::HeaderMap) {
+    for (header, field, known) in FIELDS {
+        if let Some(value) = headers.get(header) {
+            span.record(field, normalize(value, known));
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Request fingerprint
+//!
+//! Fingerprint is stable hash of request's shape, intended to group similar requests during abuse investigation.
+//!
+//! ## Algorithm (version 1)
+//!
+//! Fingerprint is 64bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hash, rendered as 16 lowercase hex characters, computed over:
+//! - [VERSION] byte;
+//! - For each enabled component, in the order listed below, its tag byte followed by its content and `0xff` separator:
+//!   - [Components::METHOD] - tag `M`, request's method;
+//!   - [Components::PATH] - tag `P`, request's path without query;
+//!   - [Components::HEADER_NAMES] - tag `H`, lowercase names of headers present, sorted and joined with `,`. Values are never included;
+//!   - [Components::USER_AGENT] - tag `U`, product name of `User-Agent` (i.e. part before first `/` or whitespace) in lowercase.
+//!
+//! Any change to the algorithm must increment [VERSION], so that values stay comparable across deployments using the same version.
+
+use core::{fmt, ops};
+
+///Version of fingerprint algorithm
+pub const VERSION: u8 = 1;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+const SEPARATOR: u8 = 0xff;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Set of request's components included into fingerprint
+pub struct Components(u8);
+
+impl Components {
+    ///No components
+    pub const NONE: Self = Self(0);
+    ///Request's method
+    pub const METHOD: Self = Self(1);
+    ///Request's path
+    pub const PATH: Self = Self(1 << 1);
+    ///Names of headers present in request
+    pub const HEADER_NAMES: Self = Self(1 << 2);
+    ///Product name of `User-Agent`
+    pub const USER_AGENT: Self = Self(1 << 3);
+    ///All components
+    pub const ALL: Self = Self(Self::METHOD.0 | Self::PATH.0 | Self::HEADER_NAMES.0 | Self::USER_AGENT.0);
+
+    #[inline(always)]
+    ///Returns whether all of `other` components are included
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl ops::BitOr for Components {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitor(self, other: Self) -> Self::Output {
+        Self(self.0 | other.0)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Request's fingerprint, displayed as 16 hex characters
+pub struct Fingerprint(pub u64);
+
+impl fmt::Display for Fingerprint {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_fmt(format_args!("{:016x}", self.0))
+    }
+}
+
+struct Fnv(u64);
+
+impl Fnv {
+    #[inline(always)]
+    fn write(&mut self, bytes: &[u8]) {
+        for byt in bytes {
+            self.0 ^= *byt as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    #[inline(always)]
+    fn component(&mut self, tag: u8, content: &[u8]) {
+        self.write(&[tag]);
+        self.write(content);
+        self.write(&[SEPARATOR]);
+    }
+}
+
+fn user_agent_name(user_agent: &[u8]) -> &[u8] {
+    let user_agent = user_agent.trim_ascii_start();
+    match user_agent.iter().position(|byt| *byt == b'/' || byt.is_ascii_whitespace()) {
+        Some(idx) => &user_agent[..idx],
+        None => user_agent,
+    }
+}
+
+///Computes fingerprint of request using specified `components`
+pub fn compute(components: Components, parts: &http::request::Parts) -> Fingerprint {
+    let mut hash = Fnv(FNV_OFFSET);
+    hash.write(&[VERSION]);
+
+    if components.contains(Components::METHOD) {
+        hash.component(b'M', parts.method.as_str().as_bytes());
+    }
+    if components.contains(Components::PATH) {
+        hash.component(b'P', parts.uri.path().as_bytes());
+    }
+    if components.contains(Components::HEADER_NAMES) {
+        let mut names: Vec<&str> = parts.headers.keys().map(http::HeaderName::as_str).collect();
+        names.sort_unstable();
+        hash.write(b"H");
+        for (idx, name) in names.iter().enumerate() {
+            if idx > 0 {
+                hash.write(b",");
+            }
+            hash.write(name.as_bytes());
+        }
+        hash.write(&[SEPARATOR]);
+    }
+    if components.contains(Components::USER_AGENT) {
+        let name = parts.headers.get(http::header::USER_AGENT).map_or(&[][..], |user_agent| user_agent_name(user_agent.as_bytes()));
+        hash.write(b"U");
+        for byt in name {
+            hash.write(&[byt.to_ascii_lowercase()]);
+        }
+        hash.write(&[SEPARATOR]);
+    }
+
+    Fingerprint(hash.0)
+}
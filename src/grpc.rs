@@ -1,3 +1,163 @@
+//! gRPC utilities
+
+use crate::Protocol;
+
+///`grpc-status` header name
+pub const GRPC_STATUS: http::HeaderName = http::HeaderName::from_static("grpc-status");
+///`grpc-timeout` header name
+pub const GRPC_TIMEOUT: http::HeaderName = http::HeaderName::from_static("grpc-timeout");
+///`CANCELLED` status code
+pub const CANCELLED: u16 = 1;
+///`UNKNOWN` status code
+pub const UNKNOWN: u16 = 2;
+///`INTERNAL` status code
+pub const INTERNAL: u16 = 13;
+
+#[derive(Copy, Clone, Debug)]
+enum StatusSource {
+    Headers,
+    Trailers,
+}
+
+const fn status_sources(protocol: Protocol) -> &'static [StatusSource] {
+    match protocol {
+        Protocol::Http => &[],
+        //Trailers-only responses place status in headers, otherwise it is in HTTP/2 trailers
+        Protocol::Grpc => &[StatusSource::Headers, StatusSource::Trailers],
+        //Some servers (e.g. Envoy's grpc-web filter) inline status into headers, otherwise it is in body's trailers frame
+        Protocol::GrpcWeb => &[StatusSource::Headers, StatusSource::Trailers],
+    }
+}
+
+///Resolves gRPC status of the response for specified `protocol`
+///
+///Status is looked up in `headers` first, then in `trailers` (HTTP/2 trailers for gRPC or trailers frame of body for gRPC-Web), if available.
+///
+///Returns `UNKNOWN` if status cannot be found
+pub fn resolve_grpc_status(protocol: Protocol, headers: &http::HeaderMap, trailers: Option<&http::HeaderMap>) -> u16 {
+    find_grpc_status(protocol, headers, trailers).unwrap_or(UNKNOWN)
+}
+
+//Same as `resolve_grpc_status`, but returns `None` if status cannot be found
+pub(crate) fn find_grpc_status(protocol: Protocol, headers: &http::HeaderMap, trailers: Option<&http::HeaderMap>) -> Option<u16> {
+    for source in status_sources(protocol) {
+        let status = match source {
+            StatusSource::Headers => headers.get(GRPC_STATUS),
+            StatusSource::Trailers => trailers.and_then(|trailers| trailers.get(GRPC_STATUS)),
+        };
+        if let Some(status) = status {
+            return Some(parse_grpc_status(status.as_bytes()));
+        }
+    }
+
+    None
+}
+
+//Flag of gRPC-Web frame, which contains trailers instead of message
+const GRPC_WEB_TRAILERS_FLAG: u8 = 0x80;
+const GRPC_WEB_FRAME_HEADER_LEN: usize = 5;
+//Limit of trailers frame, which has to be buffered to be decoded
+const GRPC_WEB_TRAILERS_MAX_LEN: usize = 8 * 1024;
+
+#[derive(Default)]
+//Decodes trailers frame, which follows messages in gRPC-Web response's body
+pub(crate) struct GrpcWebTrailers {
+    header: [u8; GRPC_WEB_FRAME_HEADER_LEN],
+    header_len: usize,
+    //Bytes of current message yet to be skipped
+    skip: usize,
+    //Content of trailers frame received so far, along with its length
+    trailers: Option<(Vec<u8>, usize)>,
+    //Set once trailers are decoded or body cannot be decoded
+    is_done: bool,
+}
+
+impl GrpcWebTrailers {
+    //Decodes data frame of body, returning trailers once their frame is complete
+    pub(crate) fn decode(&mut self, data: &impl bytes::Buf) -> Option<http::HeaderMap> {
+        if self.is_done {
+            return None;
+        }
+
+        let mut slices = [std::io::IoSlice::new(&[]); crate::body::MAX_DATA_SLICES];
+        let len = data.chunks_vectored(&mut slices);
+        //Data cannot be advanced by reference, so it cannot be decoded if it doesn't fit
+        if slices[..len].iter().map(|slice| slice.len()).sum::<usize>() != data.remaining() {
+            self.is_done = true;
+            return None;
+        }
+        for slice in &slices[..len] {
+            let trailers = self.decode_slice(slice);
+            if trailers.is_some() {
+                self.is_done = true;
+                return trailers;
+            } else if self.is_done {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn decode_slice(&mut self, mut bytes: &[u8]) -> Option<http::HeaderMap> {
+        while !bytes.is_empty() {
+            if self.skip > 0 {
+                let len = self.skip.min(bytes.len());
+                self.skip -= len;
+                bytes = &bytes[len..];
+            } else if let Some((content, expected)) = self.trailers.as_mut() {
+                let len = (*expected - content.len()).min(bytes.len());
+                content.extend_from_slice(&bytes[..len]);
+                bytes = &bytes[len..];
+                if content.len() == *expected {
+                    return Some(parse_grpc_web_trailers(content));
+                }
+            } else {
+                let len = (GRPC_WEB_FRAME_HEADER_LEN - self.header_len).min(bytes.len());
+                self.header[self.header_len..self.header_len + len].copy_from_slice(&bytes[..len]);
+                self.header_len += len;
+                bytes = &bytes[len..];
+                if self.header_len == GRPC_WEB_FRAME_HEADER_LEN {
+                    self.header_len = 0;
+                    let [flag, len @ ..] = self.header;
+                    let len = u32::from_be_bytes(len) as usize;
+                    if flag & GRPC_WEB_TRAILERS_FLAG == 0 {
+                        self.skip = len;
+                    } else if len == 0 {
+                        return Some(http::HeaderMap::new());
+                    } else if len > GRPC_WEB_TRAILERS_MAX_LEN {
+                        self.is_done = true;
+                        return None;
+                    } else {
+                        self.trailers = Some((Vec::with_capacity(len), len));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+//Parses trailers frame's content, which is formatted as HTTP/1 headers, skipping invalid lines
+fn parse_grpc_web_trailers(content: &[u8]) -> http::HeaderMap {
+    content.split(|byt| *byt == b'\n').filter_map(|line| {
+        let (name, value) = line.split_at(line.iter().position(|byt| *byt == b':')?);
+        let name = http::HeaderName::from_bytes(name.trim_ascii()).ok()?;
+        let value = http::HeaderValue::from_bytes(value[1..].trim_ascii()).ok()?;
+        Some((name, value))
+    }).collect()
+}
+
+///Parses gRPC request's `path` in format of `/<service>/<method>`, returning `None` if path is not valid gRPC path
+pub fn parse_grpc_path(path: &str) -> Option<(&str, &str)> {
+    let (service, method) = path.strip_prefix('/')?.split_once('/')?;
+    if service.is_empty() || method.is_empty() || method.contains('/') {
+        None
+    } else {
+        Some((service, method))
+    }
+}
+
+///Parses value of `grpc-status`, returning `UNKNOWN` if value is not valid status code
 pub fn parse_grpc_status(bytes: &[u8]) -> u16 {
     match bytes.len() {
         1 => match bytes[0] {
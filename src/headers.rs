@@ -1,25 +1,353 @@
 use core::fmt;
 
-struct DisplayHeaderValues<'a>(http::header::GetAll<'a, http::header::HeaderValue>);
+///`fmt::Write` implementation that writes into bytes buffer, allowing to construct `HeaderValue` without extra copy
+pub struct BytesWriter(pub bytes::BytesMut);
+
+impl BytesWriter {
+    #[inline(always)]
+    pub fn finish(self) -> bytes::Bytes {
+        self.0.freeze()
+    }
+}
+
+impl fmt::Write for BytesWriter {
+    #[inline(always)]
+    fn write_str(&mut self, input: &str) -> fmt::Result {
+        self.0.extend_from_slice(input.as_bytes());
+        Ok(())
+    }
+}
+
+///Formats `args` as `HeaderValue`
+pub fn to_header_value(args: fmt::Arguments<'_>) -> http::HeaderValue {
+    use fmt::Write;
+
+    let mut out = BytesWriter(bytes::BytesMut::new());
+    //Cannot fail, will panic on OOM
+    let _ = out.write_fmt(args);
+    unsafe {
+        //BytesWriter is guaranteed to only write via `fmt::Write` so all content is valid utf-8
+        http::HeaderValue::from_maybe_shared_unchecked(out.finish())
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+///Formatting of inspected header's values
+pub struct ValueFormat {
+    pub redactor: Option<crate::HeaderRedactor>,
+    ///Maximum length of each value in bytes
+    pub max_len: Option<usize>,
+    ///Renders headers as JSON object instead of debug map
+    pub json: bool,
+    ///Recording of headers with multiple values
+    pub strategy: crate::HeaderValuesStrategy,
+}
+
+///Prefix of span fields, declared per header via [make_request_spanner](../macro.make_request_spanner.html)
+pub const HEADER_FIELD_PREFIX: &str = "http.request.header.";
+
+///Prefix of header's value, which is not valid UTF-8 and therefore rendered as base64
+pub const BINARY_VALUE_PREFIX: &str = "b64:";
+
+#[derive(Copy, Clone)]
+//Content of header's value, displaying binary data as base64, prefixed with `BINARY_VALUE_PREFIX`
+enum ValueContent<'a> {
+    Text(&'a str),
+    Binary(&'a [u8]),
+}
+
+impl<'a> ValueContent<'a> {
+    #[inline]
+    fn new(value: &'a [u8]) -> Self {
+        match core::str::from_utf8(value) {
+            Ok(value) => Self::Text(value),
+            Err(_) => Self::Binary(value),
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Self::Text(value) => value.len(),
+            Self::Binary(value) => value.len(),
+        }
+    }
+
+    #[inline]
+    //Binary data is truncated in bytes, before encoding
+    fn truncate(&self, max_len: usize) -> Self {
+        match self {
+            Self::Text(value) => Self::Text(truncate_str(value, max_len)),
+            Self::Binary(value) => Self::Binary(&value[..value.len().min(max_len)]),
+        }
+    }
+}
+
+impl fmt::Display for ValueContent<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(value) => fmt.write_str(value),
+            Self::Binary(value) => {
+                fmt.write_str(BINARY_VALUE_PREFIX)?;
+                fmt::Display::fmt(&Base64(value), fmt)
+            },
+        }
+    }
+}
+
+///Displays bytes as standard base64 with padding, as gRPC encodes binary metadata
+pub struct Base64<'a>(pub &'a [u8]);
+
+impl fmt::Display for Base64<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        for chunk in self.0.chunks(3) {
+            let bits = chunk.iter().enumerate().fold(0u32, |bits, (idx, byt)| bits | (*byt as u32) << (16 - 8 * idx));
+            let mut out = [b'='; 4];
+            for (idx, byt) in out.iter_mut().take(chunk.len() + 1).enumerate() {
+                *byt = ALPHABET[(bits >> (18 - 6 * idx) & 0x3f) as usize];
+            }
+            fmt.write_str(unsafe {
+                //Alphabet and padding are ASCII
+                core::str::from_utf8_unchecked(&out)
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+struct DisplayHeaderValues<'a> {
+    name: &'a http::HeaderName,
+    values: http::header::GetAll<'a, http::header::HeaderValue>,
+    format: ValueFormat,
+    separator: &'static str,
+}
+
+impl<'a> DisplayHeaderValues<'a> {
+    #[inline(always)]
+    fn new(headers: &'a http::HeaderMap, name: &'a http::HeaderName, format: ValueFormat) -> Self {
+        Self {
+            name,
+            values: headers.get_all(name),
+            format,
+            separator: ", ",
+        }
+    }
+
+    #[inline(always)]
+    //Values joined by comma as OTEL requires for single string attribute
+    fn joined(headers: &'a http::HeaderMap, name: &'a http::HeaderName, format: ValueFormat) -> Self {
+        Self {
+            separator: ",",
+            ..Self::new(headers, name, format)
+        }
+    }
+
+    #[inline(always)]
+    fn redaction(&self, value: &http::HeaderValue) -> crate::HeaderRedaction {
+        self.format.redactor.map_or(crate::HeaderRedaction::Keep, |redactor| redactor(self.name, value))
+    }
+
+    //Returns value with redaction and length limit applied, followed by marker of cut off content, unless value is dropped
+    fn render<'b>(&self, value: &'b http::HeaderValue) -> Option<(ValueContent<'b>, &'static str)> {
+        const REDACTED_STR: &str = "[REDACTED]";
+        const TRUNCATED_STR: &str = "...";
+
+        let content = ValueContent::new(value.as_bytes());
+        let (content, is_redacted) = match self.redaction(value) {
+            crate::HeaderRedaction::Keep => (content, false),
+            crate::HeaderRedaction::Drop => return None,
+            crate::HeaderRedaction::Replace => (ValueContent::Text(""), true),
+            crate::HeaderRedaction::Prefix(len) => {
+                let prefix = content.truncate(len);
+                (prefix, prefix.len() < content.len())
+            },
+        };
+        let (content, is_truncated) = match self.format.max_len {
+            Some(max_len) => {
+                let truncated = content.truncate(max_len);
+                (truncated, truncated.len() < content.len())
+            },
+            None => (content, false),
+        };
+
+        let marker = if is_redacted {
+            REDACTED_STR
+        } else if is_truncated {
+            TRUNCATED_STR
+        } else {
+            ""
+        };
+        Some((content, marker))
+    }
+
+    #[inline]
+    fn rendered(&self) -> impl Iterator<Item = (ValueContent<'a>, &'static str)> + '_ {
+        self.values.iter().filter_map(|value| self.render(value))
+    }
+
+    //Returns values to record according to strategy, except `Count` which records no value
+    fn selected(&self) -> impl Iterator<Item = (ValueContent<'a>, &'static str)> + '_ {
+        let (all, single) = match self.format.strategy {
+            crate::HeaderValuesStrategy::Join => (Some(self.rendered()), None),
+            crate::HeaderValuesStrategy::First => (None, self.rendered().next()),
+            crate::HeaderValuesStrategy::Last => (None, self.rendered().last()),
+            crate::HeaderValuesStrategy::Count => (None, None),
+        };
+        all.into_iter().flatten().chain(single)
+    }
+
+    //Writes values as JSON string, or as array of strings if header has multiple values
+    fn fmt_json(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format.strategy == crate::HeaderValuesStrategy::Count {
+            return fmt::Display::fmt(&self.rendered().count(), fmt);
+        }
+        let is_array = self.selected().nth(1).is_some();
+
+        if is_array {
+            fmt.write_str("[")?;
+        }
+        for (idx, (content, marker)) in self.selected().enumerate() {
+            if idx > 0 {
+                fmt.write_str(",")?;
+            }
+            fmt.write_str("\"")?;
+            match content {
+                ValueContent::Text(value) => write_json_escaped(fmt, value)?,
+                //Base64 alphabet needs no escaping
+                ValueContent::Binary(_) => fmt::Display::fmt(&content, fmt)?,
+            }
+            fmt.write_str(marker)?;
+            fmt.write_str("\"")?;
+        }
+        if is_array {
+            fmt.write_str("]")?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    //Returns whether at least one value remains after redaction
+    fn is_visible(&self) -> bool {
+        self.values.iter().any(|value| self.redaction(value) != crate::HeaderRedaction::Drop)
+    }
+}
 
 impl fmt::Debug for DisplayHeaderValues<'_> {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+        if self.format.strategy == crate::HeaderValuesStrategy::Count {
+            return fmt::Display::fmt(&self.rendered().count(), fmt);
+        }
+        for (idx, (content, marker)) in self.selected().enumerate() {
+            if idx > 0 {
+                fmt.write_str(self.separator)?;
+            }
+            fmt::Display::fmt(&content, fmt)?;
+            fmt.write_str(marker)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for DisplayHeaderValues<'_> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, fmt)
+    }
+}
+
+//Writes headers as debug map or JSON object, skipping headers without values left after redaction
+fn fmt_headers<'a>(fmt: &mut fmt::Formatter<'_>, keys: impl Iterator<Item = &'a http::HeaderName>, headers: &'a http::HeaderMap, format: ValueFormat) -> fmt::Result {
+    if !format.json {
+        let mut out = fmt.debug_map();
+        for key in keys {
+            let all_values = DisplayHeaderValues::new(headers, key, format);
+            if all_values.is_visible() {
+                out.entry(&key.as_str(), &all_values);
+            }
+        }
+        return out.finish();
+    }
+
+    fmt.write_str("{")?;
+    let mut is_first = true;
+    for key in keys {
+        let all_values = DisplayHeaderValues::new(headers, key, format);
+        if !all_values.is_visible() {
+            continue;
+        }
+        if !is_first {
+            fmt.write_str(",")?;
+        }
+        is_first = false;
+        fmt.write_str("\"")?;
+        write_json_escaped(fmt, key.as_str())?;
+        fmt.write_str("\":")?;
+        all_values.fmt_json(fmt)?;
+    }
+    fmt.write_str("}")
+}
+
+//Writes `value` as content of JSON string, escaping quotes, backslashes and control characters
+fn write_json_escaped(fmt: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    let mut start = 0;
+    //Only ASCII bytes are escaped, so slicing never splits UTF-8 character
+    for (idx, byt) in value.bytes().enumerate() {
+        let escaped = match byt {
+            b'"' => Some("\\\""),
+            b'\\' => Some("\\\\"),
+            b'\n' => Some("\\n"),
+            b'\r' => Some("\\r"),
+            b'\t' => Some("\\t"),
+            0..=0x1f | 0x7f => None,
+            _ => continue,
+        };
+        fmt.write_str(&value[start..idx])?;
+        start = idx + 1;
+        match escaped {
+            Some(escaped) => fmt.write_str(escaped)?,
+            None => write!(fmt, "\\u{byt:04x}")?,
+        }
+    }
+    fmt.write_str(&value[start..])
+}
+
+//Records each header in `header_list` as its own `http.request.header.<name>` field, as long as span declares it
+fn record_header_fields<'a>(span: &tracing::Span, header_list: impl Iterator<Item = &'a http::HeaderName>, headers: &'a http::HeaderMap, format: ValueFormat) {
+    let metadata = match span.metadata() {
+        Some(metadata) => metadata,
+        None => return,
+    };
+    for name in header_list {
+        let field = metadata.fields().iter().find(|field| field.name().strip_prefix(HEADER_FIELD_PREFIX) == Some(name.as_str()));
+        if let Some(field) = field {
+            let values = DisplayHeaderValues::joined(headers, name, format);
+            if values.is_visible() {
+                span.record(&field, tracing::field::display(&values));
+            }
+        }
+    }
+}
+
+///Displays all values of header, except the first one, separated by comma
+pub struct ExtraHeaderValues<'a>(pub http::header::GetAll<'a, http::header::HeaderValue>);
+
+impl fmt::Display for ExtraHeaderValues<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         const FALLBACK_STR: &str = "<non-utf8>";
 
-        let mut headers = self.0.iter();
-        if let Some(header) = headers.next() {
+        for (idx, header) in self.0.iter().enumerate().skip(1) {
+            if idx > 1 {
+                fmt.write_str(",")?;
+            }
             match header.to_str() {
                 Ok(header) => fmt.write_str(header)?,
                 Err(_) => fmt.write_str(FALLBACK_STR)?,
             }
-
-            for header in headers {
-                fmt.write_str(" ,")?;
-                match header.to_str() {
-                    Ok(header) => fmt.write_str(header)?,
-                    Err(_) => fmt.write_str(FALLBACK_STR)?,
-                }
-            }
         }
 
         Ok(())
@@ -28,19 +356,298 @@ impl fmt::Debug for DisplayHeaderValues<'_> {
 
 pub struct InspectHeaders<'a> {
     pub header_list: &'a [&'a http::HeaderName],
+    //Headers specified at runtime, inspected after `header_list`
+    pub extra_header_list: &'a [http::HeaderName],
+    //Prefixes of header names. If any is specified, headers are inspected in order of `headers` instead
+    pub prefix_list: &'a [&'static str],
     pub headers: &'a http::HeaderMap,
+    pub format: ValueFormat,
+}
+
+impl<'a> InspectHeaders<'a> {
+    #[inline]
+    fn keys(&self) -> impl Iterator<Item = &'a http::HeaderName> {
+        let header_list = self.header_list;
+        let extra_header_list = self.extra_header_list;
+        let prefix_list = self.prefix_list;
+        let (listed, matched) = match prefix_list.is_empty() {
+            true => {
+                let extra_header_list = extra_header_list.iter().filter(move |key| !header_list.contains(key));
+                (Some(header_list.iter().copied().chain(extra_header_list)), None)
+            },
+            //Iterate over map once, so that each header is matched only once regardless of patterns
+            false => (None, Some(self.headers.keys().filter(move |key| {
+                header_list.contains(key) || extra_header_list.contains(key) || prefix_list.iter().any(|prefix| starts_with_ignore_case(key.as_str(), prefix))
+            }))),
+        };
+        listed.into_iter().flatten().chain(matched.into_iter().flatten())
+    }
+
+    #[inline]
+    ///Records each header as `http.request.header.<name>`, instead of single map
+    pub fn record_fields(&self, span: &tracing::Span) {
+        record_header_fields(span, self.keys(), self.headers, self.format)
+    }
+
+    #[inline]
+    ///Returns whether any of inspected headers is present
+    pub fn is_present(&self) -> bool {
+        self.keys().any(|key| self.headers.contains_key(key))
+    }
 }
 
 impl fmt::Debug for InspectHeaders<'_> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_headers(fmt, self.keys(), self.headers, self.format)
+    }
+}
+
+///Headers that are never inspected by [InspectAllHeaders]
+pub const ALWAYS_DENIED: [http::HeaderName; 3] = [http::header::AUTHORIZATION, http::header::COOKIE, http::header::PROXY_AUTHORIZATION];
+
+///Inspects every header, except those in `deny_list` or [ALWAYS_DENIED]
+pub struct InspectAllHeaders<'a> {
+    pub deny_list: &'a [http::HeaderName],
+    pub headers: &'a http::HeaderMap,
+    pub format: ValueFormat,
+}
+
+impl<'a> InspectAllHeaders<'a> {
+    #[inline]
+    fn keys(&self) -> impl Iterator<Item = &'a http::HeaderName> {
+        let deny_list = self.deny_list;
+        self.headers.keys().filter(move |key| !ALWAYS_DENIED.contains(key) && !deny_list.contains(key))
+    }
+
+    #[inline]
+    ///Records each header as `http.request.header.<name>`, instead of single map
+    pub fn record_fields(&self, span: &tracing::Span) {
+        record_header_fields(span, self.keys(), self.headers, self.format)
+    }
+}
+
+impl fmt::Debug for InspectAllHeaders<'_> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_headers(fmt, self.keys(), self.headers, self.format)
+    }
+}
+
+///Inspects cookies with names in `cookie_list`, recording only first occurrence of each
+pub struct InspectCookies<'a> {
+    pub cookie_list: &'a [&'a str],
+    pub headers: &'a http::HeaderMap,
+}
+
+impl<'a> InspectCookies<'a> {
+    //Iterates over `name=value` pairs of all `Cookie` headers, skipping malformed ones
+    fn cookies(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.headers.get_all(http::header::COOKIE)
+                    .iter()
+                    .filter_map(|value| core::str::from_utf8(value.as_bytes()).ok())
+                    .flat_map(|value| value.split(';'))
+                    .filter_map(|pair| {
+                        let (name, value) = pair.split_once('=')?;
+                        let name = name.trim();
+                        match name.is_empty() {
+                            true => None,
+                            false => Some((name, value.trim())),
+                        }
+                    })
+    }
+
+    #[inline]
+    ///Returns value of the first occurrence of cookie `name`
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.cookies().find(|(cookie, _)| *cookie == name).map(|(_, value)| value)
+    }
+
+    #[inline]
+    ///Returns whether any of inspected cookies is present
+    pub fn is_present(&self) -> bool {
+        self.cookie_list.iter().any(|name| self.get(name).is_some())
+    }
+}
+
+impl fmt::Debug for InspectCookies<'_> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut out = fmt.debug_map();
-        for key in self.header_list {
-            let all_values = self.headers.get_all(*key);
-            if all_values.iter().next().is_some() {
-                out.entry(&key.as_str(), &DisplayHeaderValues(all_values));
+        for name in self.cookie_list {
+            if let Some(value) = self.get(name) {
+                out.entry(name, &format_args!("{value}"));
             }
         }
 
         out.finish()
     }
 }
+
+///Returns first entry of `X-Forwarded-For` value, truncated to `FORWARDED_FOR_FIRST_MAX_LEN`
+pub fn forwarded_for_first(value: &[u8]) -> Option<&str> {
+    let first = match value.iter().position(|byt| *byt == b',') {
+        Some(idx) => &value[..idx],
+        None => value,
+    };
+    let first = first.trim_ascii();
+    if first.is_empty() {
+        return None;
+    }
+
+    let first = match core::str::from_utf8(first) {
+        Ok(first) => first,
+        //Cut off at the last valid character
+        Err(error) => unsafe {
+            core::str::from_utf8_unchecked(&first[..error.valid_up_to()])
+        },
+    };
+    Some(truncate_str(first, crate::FORWARDED_FOR_FIRST_MAX_LEN))
+}
+
+///Returns whether comma separated list in any of header's `values` contains `token` (case-insensitive)
+pub fn contains_token(values: http::header::GetAll<'_, http::HeaderValue>, token: &str) -> bool {
+    values.iter().any(|value| {
+        value.as_bytes().split(|byt| *byt == b',').any(|item| item.trim_ascii().eq_ignore_ascii_case(token.as_bytes()))
+    })
+}
+
+///Returns value of `Content-Length`, unless it is absent or not a number
+pub fn content_length(headers: &http::HeaderMap) -> Option<u64> {
+    let value = headers.get(http::header::CONTENT_LENGTH)?.as_bytes().trim_ascii();
+    match !value.is_empty() && value.iter().all(u8::is_ascii_digit) {
+        //Cannot fail as it is ASCII digits, unless it overflows
+        true => core::str::from_utf8(value).ok()?.parse().ok(),
+        false => None,
+    }
+}
+
+///Returns host and, if explicit, port addressed by request, taken from `Host` header or, if absent, URI's authority
+///
+///Brackets of IPv6 address are stripped. Returns `None` if `Host` is malformed
+pub fn server_address(parts: &http::request::Parts) -> Option<(&str, Option<u16>)> {
+    let (host, port) = match parts.headers.get(http::header::HOST) {
+        Some(host) => split_host_port(core::str::from_utf8(host.as_bytes()).ok()?.trim())?,
+        None => {
+            let authority = parts.uri.authority()?;
+            (authority.host(), authority.port_u16())
+        },
+    };
+    let host = host.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(host);
+    match host.is_empty() {
+        true => None,
+        false => Some((host, port)),
+    }
+}
+
+///Returns `host[:port]` addressed by request, taken from `Host` header or, if absent, URI's authority without user info
+///
+///Returns `None` if `Host` is malformed
+pub fn request_authority(parts: &http::request::Parts) -> Option<&str> {
+    match parts.headers.get(http::header::HOST) {
+        Some(host) => core::str::from_utf8(host.as_bytes()).ok().map(str::trim).filter(|host| !host.is_empty() && split_host_port(host).is_some()),
+        None => parts.uri.authority().map(|authority| authority.as_str().rsplit_once('@').map_or(authority.as_str(), |(_, host)| host)),
+    }
+}
+
+///Replacement of redacted query parameter's value
+pub const REDACTED_QUERY_VALUE: &str = "REDACTED";
+
+///Replaces values of `query` parameters, whose names match any of `params` (case-insensitive), with [REDACTED_QUERY_VALUE]
+///
+///Order of parameters is preserved. Returns `query` as it is, unless any value is redacted
+pub fn redact_query<'a>(query: &'a str, params: &[&str]) -> std::borrow::Cow<'a, str> {
+    let mut redacted = None;
+    //End of `query` already copied into `redacted`
+    let mut copied = 0;
+    let mut start = 0;
+    for pair in query.split('&') {
+        let end = start + pair.len();
+        if let Some((name, _)) = pair.split_once('=') {
+            if params.iter().any(|param| param.eq_ignore_ascii_case(name)) {
+                let redacted = redacted.get_or_insert_with(|| String::with_capacity(query.len()));
+                redacted.push_str(&query[copied..start]);
+                redacted.push_str(name);
+                redacted.push('=');
+                redacted.push_str(REDACTED_QUERY_VALUE);
+                copied = end;
+            }
+        }
+        start = end + 1;
+    }
+    match redacted {
+        Some(mut redacted) => {
+            redacted.push_str(&query[copied..]);
+            std::borrow::Cow::Owned(redacted)
+        },
+        None => std::borrow::Cow::Borrowed(query),
+    }
+}
+
+///Displays absolute URL out of its components
+pub struct FullUrl<'a> {
+    pub scheme: &'a str,
+    pub authority: Option<&'a str>,
+    pub path: &'a str,
+    pub query: Option<&'a str>,
+}
+
+impl fmt::Display for FullUrl<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(authority) = self.authority {
+            fmt.write_fmt(format_args!("{}://{authority}", self.scheme))?;
+        }
+        fmt.write_str(self.path)?;
+        if let Some(query) = self.query {
+            fmt.write_fmt(format_args!("?{query}"))?;
+        }
+        Ok(())
+    }
+}
+
+//Splits `host[:port]`, where host may be IPv6 address in brackets
+fn split_host_port(value: &str) -> Option<(&str, Option<u16>)> {
+    let port_idx = match value.starts_with('[') {
+        true => value.find(']')? + 1,
+        false => value.find(':').unwrap_or(value.len()),
+    };
+    let (host, port) = value.split_at(port_idx);
+    match port.strip_prefix(':') {
+        Some(port) => Some((host, Some(port.parse().ok()?))),
+        None if port.is_empty() => Some((host, None)),
+        None => None,
+    }
+}
+
+#[inline]
+///Returns name and, if present, version of leading product token of `User-Agent` (e.g. `curl/8.4.0`)
+///
+///Returns `None` if there is no product token
+pub fn user_agent_product(value: &str) -> Option<(&str, Option<&str>)> {
+    let product = value.trim_ascii_start().split(|ch: char| ch.is_ascii_whitespace()).next()?;
+    let (name, version) = match product.split_once('/') {
+        Some((name, version)) => (name, Some(version).filter(|version| !version.is_empty())),
+        None => (product, None),
+    };
+    match name.is_empty() {
+        true => None,
+        false => Some((name, version)),
+    }
+}
+
+///Returns whether `value` starts with `prefix`, ignoring ASCII case
+pub fn starts_with_ignore_case(value: &str, prefix: &str) -> bool {
+    value.len() >= prefix.len() && value.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+///Truncates `value` to at most `max_len` bytes without splitting UTF-8 character
+pub fn truncate_str(value: &str, max_len: usize) -> &str {
+    if value.len() <= max_len {
+        return value;
+    }
+
+    let mut idx = max_len;
+    while !value.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    &value[..idx]
+}
@@ -35,23 +35,70 @@
 //!
 //![RequestInfo] is added to request's extensions map to provide information about request including [RequestId]
 //!
+//![SpanHandle] is added to request's extensions map to provide access to the request's span.
+//!
+//!### Background tasks
+//!
+//!Tasks spawned by handler do not inherit request's span, hence in order to preserve it you should instrument them using [TracingScope]:
+//!
+//!```rust
+//!use tower_http_tracing::{http, SpanHandle};
+//!
+//!async fn handler(request: http::Request<()>) -> Result<http::Response<()>, core::convert::Infallible> {
+//!    if let Some(handle) = request.extensions().get::<SpanHandle>() {
+//!        let scope = handle.scope();
+//!        tokio::spawn(scope.instrument(async move {
+//!            tracing::info!("Background work of the request");
+//!        }));
+//!    }
+//!    Ok(http::Response::new(()))
+//!}
+//!```
+//!
 //!## Features
 //!
 //!- `opentelemetry` - Enables integration with opentelemetry to propagate context from requests and into responses
 //!- `datadog` - Enables integration with specialized datadog tracing layer to propagate context from requests and into responses
+//!- `axum` - Enables axum extractors for request's information
+//!- `tower` - Enables error category [probe](error/fn.tower_timeout.html) for `tower`'s timeout
+//!- `hyper` - Enables error category [probe](error/fn.hyper_error.html) for `hyper`'s errors
+//!- `log-bridge` - Enables mirroring of completed requests into `log` crate via [HttpRequestLayer::with_log_bridge]
+//!- `tracing-error` - Enables capture of `SpanTrace` within request's span via [SpanHandle::capture_spantrace] and [TracingScope::capture_spantrace]
 
 #![warn(missing_docs)]
 #![allow(clippy::style)]
 
 pub use http;
-mod grpc;
+pub mod grpc;
 mod headers;
+#[cfg(feature = "log-bridge")]
+mod log_bridge;
+mod stats;
+pub mod time;
+mod rate_limit;
+mod experiment;
+mod fetch_metadata;
+mod response_headers;
+mod connection;
+pub mod clock;
+pub mod propagation;
+pub mod semconv;
+pub mod targets;
+pub mod fingerprint;
+pub mod resource;
+pub mod body;
+pub mod client;
+pub mod error;
+pub use stats::MiddlewareStatsSnapshot;
 #[cfg(feature = "opentelemetry")]
 pub mod opentelemetry;
 #[cfg(feature = "datadog")]
 pub mod datadog;
+#[cfg(feature = "axum")]
+pub mod axum;
 
 use std::net::IpAddr;
+use std::sync::Arc;
 use core::{cmp, fmt, ptr, task};
 use core::pin::Pin;
 use core::future::Future;
@@ -60,8 +107,186 @@ pub use tracing;
 
 ///RequestId's header name
 pub const REQUEST_ID: http::HeaderName = http::HeaderName::from_static("x-request-id");
+///`X-Forwarded-For` header name
+pub const X_FORWARDED_FOR: http::HeaderName = http::HeaderName::from_static("x-forwarded-for");
+///`Idempotency-Key` header name
+pub const IDEMPOTENCY_KEY: http::HeaderName = http::HeaderName::from_static("idempotency-key");
+///Names of fields declared by [make_request_spanner](macro.make_request_spanner.html) with default semantic convention, in canonical order.
+///
+///Middleware records fields in exactly this order, hence it can be used to sort fields in snapshots of tracing output.
+pub const REQUEST_SPAN_FIELDS: &[&str] = &[
+    "span.kind",
+    "http.request.method",
+    "http.request.method_original",
+    "url.path",
+    "url.query",
+    "url.length",
+    "url.truncated",
+    "url.scheme",
+    "url.full",
+    "server.address",
+    "server.endpoint",
+    "server.port",
+    "http.request_id",
+    "http.request_id.origin",
+    "http.request_id.incoming",
+    "http.request_id.rejected",
+    "http.request_id.duplicates",
+    "http.correlation_id",
+    "peer.service",
+    "http.request.resend_count",
+    "http.request.idempotency_key.present",
+    "user_agent.original",
+    "user_agent.name",
+    "user_agent.version",
+    "client.address",
+    "client.address.family",
+    "client.port",
+    "network.peer.address",
+    "network.peer.port",
+    "network.local.address",
+    "network.local.port",
+    "tls.protocol.version",
+    "tls.cipher",
+    "http.request.timeout_budget_ms",
+    "http.request.body.size",
+    "network.protocol.name",
+    "network.protocol.version",
+    "rpc.service",
+    "rpc.method",
+    "http.server.ready_wait_ms",
+    "network.stream.id",
+    "network.connection.id",
+    "network.connection.requests_seen",
+    "http.request.fingerprint",
+    "http.headers",
+    "http.request.cookies",
+    "session.id",
+    "http.request.header.x-forwarded-for.first",
+    "http.request.expect_continue",
+    "http.request.header.sec-fetch-site",
+    "http.request.header.sec-fetch-mode",
+    "http.request.header.sec-fetch-dest",
+    "http.request.header.sec-fetch-user",
+    "trace_id",
+    "span_id",
+    "http.request.body.first_byte_delay_ms",
+    "http.request.body.sha256",
+    "http.request.body.hash_skipped",
+    "http.route",
+    "enduser.id",
+    "http.request_id.response_mismatch",
+    "http.response.status_code",
+    "http.response.body.size",
+    "http.response.rate_limit.limit",
+    "http.response.rate_limit.remaining",
+    "http.response.rate_limit.retry_after_seconds",
+    "http.response.retry_after",
+    "http.response.ratelimit_remaining",
+    "http.response.connection_close",
+    "http.response.alt_svc.present",
+    "http.response.headers",
+    "error.type",
+    "error.category",
+    "error.message",
+    "http.response.headers_stripped",
+    "process.request.allocated_bytes",
+    "http.server.request.duration",
+    "http.response.trailers",
+];
+static DEFAULT_REQUEST_ID_HEADERS: [http::HeaderName; 1] = [REQUEST_ID];
+const FORWARDED_FOR_FIRST_MAX_LEN: usize = 64;
+const USER_AGENT_MAX_PARSE_LEN: usize = 512;
+///Default limit of URI's path and query length, see [HttpRequestLayer::with_max_uri_len]
+pub const DEFAULT_MAX_URI_LEN: usize = 8 * 1024;
 ///Alias to function signature required to create span
 pub type MakeSpan = fn() -> tracing::Span;
+///Alias to function signature required to create child span, declared via [make_child_spanner](macro.make_child_spanner.html)
+pub type MakeChildSpan = fn(&tracing::Span) -> tracing::Span;
+///Alias to function signature required to extract stream id from request
+pub type ExtractStreamInfo = fn(&http::request::Parts) -> Option<u64>;
+///Alias to function signature required to extract connection id of the request
+pub type ExtractConnectionId = fn(&http::request::Parts) -> Option<u64>;
+///Alias to function signature required to extract server's endpoint, which accepted the request
+pub type ExtractEndpoint = fn(&http::request::Parts) -> Option<Endpoint>;
+///Alias to function signature required to handle request completion
+pub type OnComplete = fn(&tracing::Span, &RequestSummary);
+///Alias to function signature required to determine whether error is benign
+pub type BenignErrorFilter = fn(&(dyn std::error::Error + 'static)) -> bool;
+///Alias to function signature required to redact values of inspected headers
+pub type HeaderRedactor = fn(&http::HeaderName, &http::HeaderValue) -> HeaderRedaction;
+
+///Determines whether `error` is caused by client disconnecting
+///
+///Returns `true` if `std::io::Error` with kind `BrokenPipe` or `ConnectionReset` is found anywhere in the `error`'s source chain
+pub fn is_client_disconnect(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut error = Some(error);
+    while let Some(cause) = error {
+        if let Some(error) = cause.downcast_ref::<std::io::Error>() {
+            if matches!(error.kind(), std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::ConnectionReset) {
+                return true;
+            }
+        }
+        error = cause.source();
+    }
+    false
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+///Protocol specific details of the request, accessible via [RequestInfo::details]
+pub enum ProtocolDetails {
+    ///Regular HTTP call
+    Http {
+        ///HTTP version of the request
+        version: http::Version,
+    },
+    ///gRPC call
+    Grpc {
+        ///Fully qualified service name, empty if path is not valid gRPC path
+        service: Box<str>,
+        ///Method name, empty if path is not valid gRPC path
+        method: Box<str>,
+        ///Timeout of the call, taken from `grpc-timeout`
+        timeout: Option<std::time::Duration>,
+    },
+    ///gRPC-Web call
+    GrpcWeb {
+        ///Fully qualified service name, empty if path is not valid gRPC path
+        service: Box<str>,
+        ///Method name, empty if path is not valid gRPC path
+        method: Box<str>,
+        ///Timeout of the call, taken from `grpc-timeout`
+        timeout: Option<std::time::Duration>,
+    },
+    ///HTTP call requesting upgrade to WebSocket via `Upgrade: websocket`
+    WebSocket {
+        ///HTTP version of the request
+        version: http::Version,
+    },
+}
+
+impl ProtocolDetails {
+    //Determines details from already detected `protocol` and parsed gRPC `timeout`
+    fn from_parts(protocol: Protocol, timeout: Option<std::time::Duration>, parts: &http::request::Parts) -> Self {
+        match protocol {
+            Protocol::Http => if headers::contains_token(parts.headers.get_all(http::header::UPGRADE), "websocket") {
+                Self::WebSocket { version: parts.version }
+            } else {
+                Self::Http { version: parts.version }
+            },
+            Protocol::Grpc | Protocol::GrpcWeb => {
+                let (service, method) = grpc::parse_grpc_path(parts.uri.path()).unwrap_or_default();
+                let service = service.into();
+                let method = method.into();
+                match protocol {
+                    Protocol::GrpcWeb => Self::GrpcWeb { service, method, timeout },
+                    _ => Self::Grpc { service, method, timeout },
+                }
+            }
+        }
+    }
+}
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 ///Possible request protocol
@@ -72,13 +297,17 @@ pub enum Protocol {
     Http,
     ///gRPC call, identified by presence of `Content-Type` with grpc protocol signature
     Grpc,
+    ///gRPC-Web call, identified by presence of `Content-Type` with grpc-web protocol signature
+    GrpcWeb,
 }
 
 impl Protocol {
     #[inline(always)]
     ///Determines protocol from value of `Content-Type`
     pub fn from_content_type(typ: &[u8]) -> Self {
-        if typ.starts_with(b"application/grpc") {
+        if typ.starts_with(b"application/grpc-web") {
+            Self::GrpcWeb
+        } else if typ.starts_with(b"application/grpc") {
             Self::Grpc
         } else {
             Self::Http
@@ -90,6 +319,7 @@ impl Protocol {
     pub const fn as_str(&self) -> &'static str {
         match self {
             Self::Grpc => "grpc",
+            Self::GrpcWeb => "grpc-web",
             Self::Http => "http"
         }
     }
@@ -110,29 +340,76 @@ impl fmt::Display for Protocol {
 }
 
 type RequestIdBuffer = [u8; 64];
+///Max length of request's id, longer values are truncated
+pub const REQUEST_ID_MAX_LEN: usize = 1024;
+
+#[derive(Clone)]
+enum RequestIdStorage {
+    Inline {
+        buffer: RequestIdBuffer,
+        len: u8,
+    },
+    //Ids that do not fit inline buffer (e.g. AWS ALB trace ids)
+    Heap(Box<[u8]>),
+}
 
 #[derive(Clone)]
 ///Request's id
 ///
 ///By default it is extracted from `X-Request-Id` header
+///
+///Ids up to 64 bytes are stored inline, longer ids are allocated on heap, up to [REQUEST_ID_MAX_LEN]
+///
+///Ids are compared and hashed by their bytes
 pub struct RequestId {
-    buffer: RequestIdBuffer,
-    len: u8,
+    storage: RequestIdStorage,
+    truncated: bool,
 }
 
 impl RequestId {
-    fn from_bytes(bytes: &[u8]) -> Self {
-        let mut buffer: RequestIdBuffer = [0; 64];
+    #[inline(always)]
+    const fn inline(buffer: RequestIdBuffer, len: u8) -> Self {
+        Self {
+            storage: RequestIdStorage::Inline {
+                buffer,
+                len,
+            },
+            truncated: false,
+        }
+    }
 
-        let len = cmp::min(buffer.len(), bytes.len());
+    ///Creates new id from raw bytes, truncating it to [REQUEST_ID_MAX_LEN] if necessary
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let truncated = bytes.len() > REQUEST_ID_MAX_LEN;
+        let bytes = &bytes[..cmp::min(bytes.len(), REQUEST_ID_MAX_LEN)];
+        let mut buffer: RequestIdBuffer = [0; 64];
+        if bytes.len() > buffer.len() {
+            return Self {
+                storage: RequestIdStorage::Heap(bytes.into()),
+                truncated,
+            };
+        }
 
         unsafe {
-            ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.as_mut_ptr(), len)
+            ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.as_mut_ptr(), bytes.len())
         };
 
-        Self {
-            buffer,
-            len: len as _,
+        Self::inline(buffer, bytes.len() as _)
+    }
+
+    #[inline]
+    ///Generates new id as random UUID, same as middleware does by default when request has no id
+    pub fn new_v4() -> Self {
+        Self::from_uuid(uuid::Uuid::new_v4())
+    }
+
+    #[inline]
+    ///Generates new id using specified `format`
+    pub fn generate(format: RequestIdFormat) -> Self {
+        match format {
+            RequestIdFormat::UuidV4Hyphenated => Self::new_v4(),
+            RequestIdFormat::UuidV4Simple => Self::from_uuid_simple(uuid::Uuid::new_v4()),
+            RequestIdFormat::UuidV7 => Self::from_uuid(uuid::Uuid::now_v7()),
         }
     }
 
@@ -141,20 +418,51 @@ impl RequestId {
         let uuid = uuid.as_hyphenated();
         let len = uuid.encode_lower(&mut buffer).len();
 
-        Self {
-            buffer,
-            len: len as _,
+        Self::inline(buffer, len as _)
+    }
+
+    fn from_uuid_simple(uuid: uuid::Uuid) -> Self {
+        let mut buffer: RequestIdBuffer = [0; 64];
+        let uuid = uuid.as_simple();
+        let len = uuid.encode_lower(&mut buffer).len();
+
+        Self::inline(buffer, len as _)
+    }
+
+    fn from_trace_id(trace_id: u128) -> Self {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        let mut buffer: RequestIdBuffer = [0; 64];
+        for (idx, byt) in buffer[..32].iter_mut().enumerate() {
+            let nibble = (trace_id >> ((31 - idx) * 4)) & 0xf;
+            *byt = HEX[nibble as usize];
         }
+
+        Self::inline(buffer, 32)
     }
 
     #[inline]
     ///Returns slice to already written data.
     pub const fn as_bytes(&self) -> &[u8] {
-        unsafe {
-            core::slice::from_raw_parts(self.buffer.as_ptr(), self.len as _)
+        match &self.storage {
+            RequestIdStorage::Inline { buffer, len } => unsafe {
+                core::slice::from_raw_parts(buffer.as_ptr(), *len as _)
+            },
+            RequestIdStorage::Heap(bytes) => bytes,
         }
     }
 
+    #[inline(always)]
+    ///Returns length of the id in bytes
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    #[inline(always)]
+    ///Returns `true` if id is empty
+    pub fn is_empty(&self) -> bool {
+        self.as_bytes().is_empty()
+    }
+
     #[inline(always)]
     ///Gets textual representation of the request id, if header value is string
     pub const fn as_str(&self) -> Option<&str> {
@@ -163,6 +471,51 @@ impl RequestId {
             Err(_) => None,
         }
     }
+
+    #[inline(always)]
+    ///Returns lowercase hex rendering of the request id, used to display it when it is not valid UTF-8
+    pub fn hex(&self) -> HexRequestId<'_> {
+        HexRequestId(self.as_bytes())
+    }
+
+    //Returns header value of the id, falling back to hex rendering if raw bytes are not valid header value
+    fn to_header_value(&self) -> Option<http::HeaderValue> {
+        match http::HeaderValue::from_bytes(self.as_bytes()) {
+            Ok(value) => Some(value),
+            Err(_) => http::HeaderValue::try_from(self.hex().to_string()).ok(),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns `true` if incoming request id exceeded [REQUEST_ID_MAX_LEN] and had to be truncated
+    pub const fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl PartialEq for RequestId {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for RequestId {}
+
+impl core::hash::Hash for RequestId {
+    #[inline(always)]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+impl core::str::FromStr for RequestId {
+    type Err = core::convert::Infallible;
+
+    #[inline(always)]
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from_bytes(id.as_bytes()))
+    }
 }
 
 impl fmt::Debug for RequestId {
@@ -170,374 +523,2890 @@ impl fmt::Debug for RequestId {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.as_str() {
             Some(id) => fmt::Debug::fmt(id, fmt),
-            None => fmt::Debug::fmt(self.as_bytes(), fmt),
+            None => fmt.write_fmt(format_args!("\"{}\"", self.hex())),
         }
     }
 }
 
 impl fmt::Display for RequestId {
     #[inline(always)]
+    ///Displays id as it is, if it is valid UTF-8, otherwise as lowercase hex
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.as_str() {
             Some(id) => fmt::Display::fmt(id, fmt),
-            None => fmt::Display::fmt("<non-utf8>", fmt),
+            None => fmt::Display::fmt(&self.hex(), fmt),
         }
     }
 }
 
-#[macro_export]
-///Declares `fn` function compatible with `MakeSpan` using provided parameters
+#[derive(Copy, Clone)]
+///Lowercase hex rendering of [RequestId]
+pub struct HexRequestId<'a>(&'a [u8]);
+
+impl fmt::Display for HexRequestId<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byt in self.0 {
+            fmt.write_fmt(format_args!("{byt:02x}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Final status of the request
+pub enum ResponseStatus {
+    ///Response is returned with specified status code, semantics of which depend on protocol
+    Code(u16),
+    ///Inner service returned error, which corresponds to specified status code
+    Error(u16),
+    ///Inner service returned error considered [benign](struct.HttpRequestLayer.html#method.with_benign_error_filter) (i.e. client disconnected), which corresponds to specified status code
+    ClientDisconnect(u16),
+    ///Request is cancelled before completion (i.e. response future is dropped)
+    Cancelled,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Server's endpoint (i.e. listener), which accepted the request
 ///
-///## Span fields
+///Recorded as `server.endpoint` if extractor is specified via layer [config](struct.HttpRequestLayer.html#method.with_extract_endpoint).
+///Its port is recorded as `server.port` only if port cannot be determined from request itself
 ///
-///Following fields are declared when span is created:
-///- `http.request.method`
-///- `url.path`
-///- `url.query`
-///- `url.scheme`
-///- `http.request_id` - Inherited from request 'X-Request-Id' or random uuid
-///- `user_agent.original` - Only populated if user agent header is present
-///- `http.headers` - Optional. Populated if more than 1 header specified via layer [config](struct.HttpRequestLayer.html#method.with_inspect_headers)
-///- `network.protocol.name` - Either `http` or `grpc` depending on `content-type`
-///- `network.protocol.version` - Set to HTTP version in case of plain `http` protocol.
-///- `client.address` - Optionally added if IP extractor is specified via layer [config](struct.HttpRequestLayer.html#method.with_extract_client_ip)
-///- `http.response.status_code` - Semantics of this code depends on `protocol`
-///- `error.type` - Populated with `core::any::type_name` value of error type used by the service.
-///- `error.message` - Populated with `Display` content of the error, returned by underlying service, after processing request.
+///## Usage
 ///
-///Loosely follows <https://opentelemetry.io/docs/specs/semconv/http/http-spans/#http-server>
+///When serving on multiple listeners, insert endpoint into request's extensions in accept loop of each listener and use [Endpoint::from_extensions] as extractor:
 ///
-///## Additional fields
+///```rust
+///use tower_http_tracing::{Endpoint, HttpRequestLayer};
 ///
-///Additional fields can be declared by passing extra arguments after `level` in the same way as you would pass it to `tracing::span!` macro
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
 ///
-///Note that you need to use `tracing::field::Empty` if you want to add value later
+///const PUBLIC: Endpoint = Endpoint { name: "public", port: 443 };
+///
+///let layer = HttpRequestLayer::new_simple(make_my_request_span).with_extract_endpoint(Endpoint::from_extensions);
+/////Service passed to accept loop of public listener
+///let service = tower::ServiceBuilder::new().map_request(|mut req: http::Request<()>| {
+///    req.extensions_mut().insert(PUBLIC);
+///    req
+///}).layer(layer).service_fn(|_: http::Request<()>| async move {
+///    Ok::<_, core::convert::Infallible>(http::Response::new(()))
+///});
+///```
+pub struct Endpoint {
+    ///Name of the endpoint
+    pub name: &'static str,
+    ///Port of the endpoint
+    pub port: u16,
+}
+
+impl Endpoint {
+    #[inline]
+    ///Extracts endpoint from request's extensions
+    pub fn from_extensions(parts: &http::request::Parts) -> Option<Self> {
+        parts.extensions.get::<Self>().copied()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Information about connection, over which request is received
+///
+///Server should insert it into request's extensions before middleware runs, in which case its addresses are recorded as `network.peer.*` and `network.local.*` fields.
 ///
 ///## Usage
 ///
+///With axum, convert its `ConnectInfo` (requires `into_make_service_with_connect_info`):
+///
+///```rust
+///use tower_http_tracing::{ConnectionInfo, HttpRequestLayer};
+///
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+///
+///let layer = HttpRequestLayer::new_simple(make_my_request_span);
+///let service = tower::ServiceBuilder::new().map_request(|mut req: http::Request<()>| {
+///    let peer = req.extensions().get::<axum::extract::ConnectInfo<std::net::SocketAddr>>().map(|info| info.0);
+///    if let Some(peer) = peer {
+///        req.extensions_mut().insert(ConnectionInfo::from(peer));
+///    }
+///    req
+///}).layer(layer).service_fn(|_: http::Request<()>| async move {
+///    Ok::<_, core::convert::Infallible>(http::Response::new(()))
+///});
 ///```
-///use tower_http_tracing::make_request_spanner;
+pub struct ConnectionInfo {
+    ///Address of the connection's remote peer
+    pub peer: std::net::SocketAddr,
+    ///Local address, which accepted the connection, if known
+    pub local: Option<std::net::SocketAddr>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+///Information about TLS session, over which request is received
 ///
-///make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
-/////Customize span with extra fields. You can use tracing::field::Empty if you want to omit value
-///make_request_spanner!(make_my_service_request_span("my_request", tracing::Level::INFO, service_name = "<your name>"));
+///Server, terminating TLS, should insert it into request's extensions before middleware runs, in which case it is recorded as `tls.*` fields.
 ///
-///let span = make_my_request_span();
-///span.record("url.path", "I can override span field");
+///## Usage
+///
+///```rust
+///use tower_http_tracing::{HttpRequestLayer, TlsInfo};
 ///
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+///
+///let layer = HttpRequestLayer::new_simple(make_my_request_span);
+/////Service created per accepted connection, once handshake is complete
+///let service = tower::ServiceBuilder::new().map_request(|mut req: http::Request<()>| {
+///    req.extensions_mut().insert(TlsInfo {
+///        protocol_version: "1.3",
+///        cipher: Some("TLS13_AES_128_GCM_SHA256"),
+///        sni: Some("example.com".to_owned()),
+///    });
+///    req
+///}).layer(layer).service_fn(|_: http::Request<()>| async move {
+///    Ok::<_, core::convert::Infallible>(http::Response::new(()))
+///});
 ///```
-macro_rules! make_request_spanner {
-    ($fn:ident($name:literal, $level:expr)) => {
-        $crate::make_request_spanner!($fn($name, $level,));
-    };
-    ($fn:ident($name:literal, $level:expr, $($fields:tt)*)) => {
-        #[track_caller]
-        pub fn $fn() -> $crate::tracing::Span {
-            use $crate::tracing::field;
+pub struct TlsInfo {
+    ///Negotiated protocol version (e.g. `1.3`)
+    pub protocol_version: &'static str,
+    ///Negotiated cipher suite, if known
+    pub cipher: Option<&'static str>,
+    ///Server name requested by client via SNI extension, used as `server.address` if request has no `Host`
+    pub sni: Option<String>,
+}
 
-            $crate::tracing::span!(
-                $level,
-                $name,
-                //Defaults
-                span.kind = "server",
-                //Assigned on creation of span
-                http.request.method = field::Empty,
-                url.path = field::Empty,
-                url.query = field::Empty,
-                url.scheme = field::Empty,
-                http.request_id = field::Empty,
-                user_agent.original = field::Empty,
-                http.headers = field::Empty,
-                network.protocol.name = field::Empty,
-                network.protocol.version = field::Empty,
-                //Optional
-                client.address = field::Empty,
-                //Assigned after request is complete
-                http.response.status_code = field::Empty,
-                error.type = field::Empty,
-                error.message = field::Empty,
-                $(
-                    $fields
-                )*
-            )
+impl From<std::net::SocketAddr> for ConnectionInfo {
+    #[inline(always)]
+    fn from(peer: std::net::SocketAddr) -> Self {
+        Self {
+            peer,
+            local: None,
         }
-    };
+    }
 }
 
 #[derive(Clone, Debug)]
-///Request's information
+#[non_exhaustive]
+///Summary of completed request
 ///
-///It is accessible via [extensions](https://docs.rs/http/latest/http/struct.Extensions.html)
-pub struct RequestInfo {
+///Passed to [completion callback](struct.HttpRequestLayer.html#method.with_on_complete)
+pub struct RequestSummary {
+    ///Request's method
+    pub method: http::Method,
+    ///Request's URI
+    pub uri: http::Uri,
+    ///Request's path
+    pub path: String,
+    ///Matched route template, if reported via [MatchedRoute]
+    pub route: Option<String>,
     ///Request's protocol
     pub protocol: Protocol,
     ///Request's id
     pub request_id: RequestId,
-    ///Client's IP address extracted, if available.
+    ///Client's IP address, if available
     pub client_ip: Option<IpAddr>,
+    ///Final status
+    pub status: ResponseStatus,
+    ///Time elapsed since request is received until completion
+    pub duration: std::time::Duration,
+    ///Size of request's body, as specified by `Content-Length`
+    pub request_body_size: Option<u64>,
+    ///Size of response's body, as specified by `Content-Length`
+    ///
+    ///Replaced with number of bytes actually streamed, if body is [tracked](body/struct.ResponseBodyLayer.html#method.with_track_response_body)
+    pub response_body_size: Option<u64>,
+    ///Marker `tracing.shed`, set when request was not given span due to [limit](struct.HttpRequestLayer.html#method.with_max_concurrent_spans)
+    pub shed: bool,
 }
 
-///Request's span information
-///
-///Created on every request by the middleware, but not accessible to the user directly
-pub struct RequestSpan {
-    ///Underlying tracing span
-    pub span: tracing::Span,
-    ///Request's information
-    pub info: RequestInfo,
+impl fmt::Display for RequestSummary {
+    ///Formats summary as single line of access log, omitting query to avoid leaking its content
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { method, path, protocol, request_id, client_ip, status, duration, shed, .. } = self;
+        fmt.write_fmt(format_args!("{method} {path} {protocol} "))?;
+        match status {
+            ResponseStatus::Code(code) => fmt.write_fmt(format_args!("{code}"))?,
+            ResponseStatus::Error(code) => fmt.write_fmt(format_args!("{code} error"))?,
+            ResponseStatus::ClientDisconnect(code) => fmt.write_fmt(format_args!("{code} client_disconnect"))?,
+            ResponseStatus::Cancelled => fmt.write_str("cancelled")?,
+        }
+        fmt.write_fmt(format_args!(" {}ms request_id={request_id}", duration.as_millis()))?;
+        if let Some(client_ip) = client_ip {
+            fmt.write_fmt(format_args!(" client_ip={client_ip}"))?;
+        }
+        if *shed {
+            fmt.write_str(" tracing.shed=true")?;
+        }
+        Ok(())
+    }
 }
 
-impl RequestSpan {
-    ///Creates new request span
-    pub fn new(context: &impl LayerContext, span: tracing::Span, parts: &http::request::Parts) -> Self {
-        let _entered = span.enter();
-
-        let client_ip = context.extract_client_ip(&span, parts);
-        let protocol = parts.headers
-                            .get(http::header::CONTENT_TYPE)
-                            .map_or(Protocol::Http, |content_type| Protocol::from_content_type(content_type.as_bytes()));
+//gRPC codes, which indicate failure of the server rather than client's
+const GRPC_SERVER_ERRORS: &[u16] = &[grpc::UNKNOWN, 4, 12, grpc::INTERNAL, 14, 15];
+
+impl RequestSummary {
+    //Level of access log record, mapped from final status
+    pub(crate) fn level(&self) -> tracing::Level {
+        match (self.status, self.protocol) {
+            (ResponseStatus::Cancelled, _) => tracing::Level::WARN,
+            (ResponseStatus::Error(_), _) => tracing::Level::ERROR,
+            (ResponseStatus::ClientDisconnect(_), _) => tracing::Level::INFO,
+            (ResponseStatus::Code(code), Protocol::Http) => match code {
+                500.. => tracing::Level::ERROR,
+                400.. => tracing::Level::WARN,
+                _ => tracing::Level::INFO,
+            },
+            (ResponseStatus::Code(code), Protocol::Grpc | Protocol::GrpcWeb) => match code {
+                0 => tracing::Level::INFO,
+                code if GRPC_SERVER_ERRORS.contains(&code) => tracing::Level::ERROR,
+                _ => tracing::Level::WARN,
+            },
+        }
+    }
+}
 
-        let request_id = if let Some(request_id) = parts.headers.get(REQUEST_ID) {
-            RequestId::from_bytes(request_id.as_bytes())
-        } else {
-            RequestId::from_uuid(uuid::Uuid::new_v4())
-        };
+struct Completion {
+    on_complete: Option<OnComplete>,
+    #[cfg(feature = "log-bridge")]
+    log_bridge: bool,
+    access_log: bool,
+    started: std::time::Instant,
+    summary: RequestSummary,
+}
 
-        if let Some(user_agent) = parts.headers.get(http::header::USER_AGENT).and_then(|header| header.to_str().ok()) {
-            span.record("user_agent.original", user_agent);
+impl Completion {
+    #[inline]
+    fn complete(mut self, span: &tracing::Span, status: ResponseStatus, now: std::time::Instant) {
+        self.summary.status = status;
+        self.summary.duration = now.saturating_duration_since(self.started);
+        if let Some(on_complete) = self.on_complete {
+            on_complete(span, &self.summary);
         }
-        span.record("http.request.method", parts.method.as_str());
-        span.record("url.path", parts.uri.path());
-        if let Some(query) = parts.uri.query() {
-            span.record("url.query", query);
+        #[cfg(feature = "log-bridge")]
+        if self.log_bridge {
+            log_bridge::log(&self.summary);
         }
-        if let Some(scheme) = parts.uri.scheme() {
-            span.record("url.scheme", scheme.as_str());
+        if self.access_log {
+            let summary = &self.summary;
+            //Summary is only formatted if event is going to be logged
+            match summary.level() {
+                tracing::Level::ERROR => tracing::error!(target: targets::ACCESS, parent: span, "{summary}"),
+                tracing::Level::WARN => tracing::warn!(target: targets::ACCESS, parent: span, "{summary}"),
+                _ => tracing::info!(target: targets::ACCESS, parent: span, "{summary}"),
+            }
         }
-        if let Some(request_id) = request_id.as_str() {
-            span.record("http.request_id", &request_id);
-        } else {
-            span.record("http.request_id", request_id.as_bytes());
+    }
+}
+
+//Completion waiting for the end of response's body
+struct PendingCompletion {
+    completion: Option<Completion>,
+    span: tracing::Span,
+    protocol: Protocol,
+    //gRPC status is not known until trailers are received, unless it is sent in headers
+    status: Option<u16>,
+    options: Arc<Options>,
+    //Request stays active until its body ends
+    active_request: Option<stats::ActiveRequestGuard>,
+}
+
+#[derive(Clone, Default)]
+//Inserted into response's extensions by body layer, which tracks end of response's body, in order to complete request once body ends
+pub(crate) struct DeferredCompletion(Arc<std::sync::Mutex<Option<PendingCompletion>>>);
+
+impl DeferredCompletion {
+    #[inline]
+    fn defer(&self, pending: PendingCompletion) {
+        *self.0.lock().unwrap_or_else(|error| error.into_inner()) = Some(pending);
+    }
+
+    ///Records gRPC status from response's trailers, unless it is already known from headers
+    pub(crate) fn on_trailers(&self, trailers: &http::HeaderMap) {
+        let mut pending = self.0.lock().unwrap_or_else(|error| error.into_inner());
+        if let Some(pending) = pending.as_mut() {
+            if pending.status.is_none() {
+                let status = grpc::resolve_grpc_status(pending.protocol, &http::HeaderMap::new(), Some(trailers));
+                pending.options.semconv().record_status_code(&pending.span, status);
+                pending.status = Some(status);
+            }
         }
-        if let Some(client_ip) = client_ip {
-            span.record("client.address", tracing::field::display(client_ip));
-        }
-        span.record("network.protocol.name", protocol.as_str());
-        if let Protocol::Http = protocol {
-            match parts.version {
-                http::Version::HTTP_09 => span.record("network.protocol.version", 0.9),
-                http::Version::HTTP_10 => span.record("network.protocol.version", 1.0),
-                http::Version::HTTP_11 => span.record("network.protocol.version", 1.1),
-                http::Version::HTTP_2 => span.record("network.protocol.version", 2),
-                http::Version::HTTP_3 => span.record("network.protocol.version", 3),
-                //Invalid version so just set 0
-                _ => span.record("network.protocol.version", 0),
+    }
+
+    ///Completes request, once response's body ends or is dropped
+    ///
+    ///`response_body_size` is only known when response's body is tracked
+    pub(crate) fn complete(&self, response_body_size: Option<u64>) {
+        let pending = self.0.lock().unwrap_or_else(|error| error.into_inner()).take();
+        if let Some(PendingCompletion { completion, span, protocol: _, status, options, active_request }) = pending {
+            let status = match status {
+                Some(status) => status,
+                //Body ended without gRPC status
+                None => {
+                    options.semconv().record_status_code(&span, grpc::UNKNOWN);
+                    grpc::UNKNOWN
+                }
             };
+            if let Some(mut completion) = completion {
+                if let Some(response_body_size) = response_body_size {
+                    completion.summary.response_body_size = Some(response_body_size);
+                }
+                let _entered = span.enter();
+                completion.complete(&span, ResponseStatus::Code(status), options.instant());
+            }
+            drop(active_request);
         }
+    }
+}
 
-        drop(_entered);
+#[derive(Copy, Clone, Debug, Default)]
+///Privacy mode for client's IP address
+pub enum IpPrivacy {
+    #[default]
+    ///Use address as it is
+    Raw,
+    ///Zero last octet of IPv4 or last 80 bits of IPv6
+    Truncate,
+    ///Replace address with output of the function, typically keyed hash
+    Hash(fn(&IpAddr) -> String),
+}
 
-        Self {
-            span,
-            info: RequestInfo {
-                protocol,
-                request_id,
-                client_ip
+impl IpPrivacy {
+    ///Truncates `ip` zeroing last octet of IPv4 or last 80 bits of IPv6
+    pub const fn truncate(ip: IpAddr) -> IpAddr {
+        match ip {
+            IpAddr::V4(ip) => {
+                let [a, b, c, _] = ip.octets();
+                IpAddr::V4(std::net::Ipv4Addr::new(a, b, c, 0))
+            },
+            IpAddr::V6(ip) => {
+                let [a, b, c, ..] = ip.segments();
+                IpAddr::V6(std::net::Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 0))
             }
         }
     }
 }
 
-///[HttpRequestLayer](struct.HttpRequestLayer.html) context interface
-pub trait LayerContext: Clone + Send + Sync {
-    ///Specifies list of headers you want to inspect via `http.headers` attribute
-    const INSPECT_HEADERS: &'static [&'static http::HeaderName];
+#[derive(Clone, Debug, PartialEq, Eq)]
+///Matcher of inspected header's name, see [HttpRequestLayer::with_inspect_headers]
+pub enum HeaderMatch {
+    ///Matches header with exactly the same name
+    Exact(http::HeaderName),
+    ///Matches any header, which name starts with prefix, ignoring case
+    Prefix(&'static str),
+}
 
-    #[allow(unused)]
-    #[inline(always)]
-    ///Defines way to extract `IpAddr` from `parts`
-    ///
-    ///Defaults to always return `None`
-    fn extract_client_ip(&self, span: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
-        None
+impl HeaderMatch {
+    #[inline]
+    ///Returns whether header's `name` is matched
+    pub fn matches(&self, name: &http::HeaderName) -> bool {
+        match self {
+            Self::Exact(expected) => expected == name,
+            Self::Prefix(prefix) => headers::starts_with_ignore_case(name.as_str(), prefix),
+        }
     }
+}
 
-    #[allow(unused)]
+impl From<http::HeaderName> for HeaderMatch {
     #[inline(always)]
-    ///Callback to be called on incoming request
-    ///
-    ///Defaults to be noop
-    fn on_request<T>(&self, span: &tracing::Span, request: &http::Request<T>) {
+    fn from(name: http::HeaderName) -> Self {
+        Self::Exact(name)
     }
+}
 
-    #[allow(unused)]
-    #[inline(always)]
-    ///Callback to be called when successful response is returned
-    ///
-    ///Defaults to be noop
-    fn on_response_ok<T>(&self, span: &tracing::Span, response: &mut http::Response<T>) {
-    }
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Redaction of inspected header's value, returned by [HeaderRedactor]
+pub enum HeaderRedaction {
+    ///Record value as it is
+    Keep,
+    ///Omit value. Header is omitted entirely if none of its values remains
+    Drop,
+    ///Replace value with `[REDACTED]`
+    Replace,
+    ///Keep at most specified number of bytes at the start of value, replacing the rest with `[REDACTED]`
+    Prefix(usize),
+}
 
-    #[allow(unused)]
-    #[inline(always)]
-    ///Callback to be called when error is returned instead of response
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Describes how to record inspected header with multiple values
+pub enum HeaderValuesStrategy {
+    #[default]
+    ///Record all values, separated by comma (or as array within [JSON](struct.HttpRequestLayer.html#method.with_inspect_headers_json))
+    Join,
+    ///Record only the first value
+    First,
+    ///Record only the last value
+    Last,
+    ///Record only number of values
+    Count,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Describes how to determine request's id
+pub enum RequestIdPolicy {
+    #[default]
+    ///Inherit request's id from `X-Request-Id`, if present, otherwise generate new one
+    Generate,
+    ///Use trace id of the incoming trace context, formatted as 32 hex characters.
     ///
-    ///Defaults to be noop
+    ///Requires trace context extraction to be [configured](struct.HttpRequestLayer.html#method.with_propagation_extract), otherwise falls back to generating new one
+    DeriveFromTrace,
+    ///Always generate new id, never trusting id supplied by client.
+    ///
+    ///Id supplied by client is recorded as `http.request_id.incoming` and available via [RequestInfo::incoming_request_id]
+    AlwaysGenerate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Origin of request's id, recorded as `http.request_id.origin`
+pub enum RequestIdOrigin {
+    ///Inherited from request's header
+    Inherited,
+    ///Generated by middleware
+    Generated,
+    ///Derived from trace id of the incoming trace context, see [RequestIdPolicy::DeriveFromTrace]
+    DerivedFromTrace,
+}
+
+impl RequestIdOrigin {
+    #[inline(always)]
+    ///Returns textual representation of the `self`
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Inherited => "inherited",
+            Self::Generated => "generated",
+            Self::DerivedFromTrace => "trace",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Describes how to validate request's id supplied by client
+///
+///Bytes outside of visible ASCII (i.e. control characters, whitespace or non-ASCII) can be used to inject fake lines into logs.
+///When incoming id is rejected, new one is generated and `http.request_id.rejected` is set to `true`
+pub enum RequestIdValidation {
+    #[default]
+    ///Accept id as it is
+    Verbatim,
+    ///Strip bytes outside of visible ASCII, rejecting id if nothing remains
+    Strip,
+    ///Reject id if it is empty or contains any byte outside of visible ASCII
+    Reject,
+}
+
+impl RequestIdValidation {
+    #[inline]
+    fn accepts(self, bytes: &[u8]) -> bool {
+        match self {
+            Self::Verbatim => true,
+            Self::Strip => bytes.iter().any(u8::is_ascii_graphic),
+            Self::Reject => !bytes.is_empty() && bytes.iter().all(u8::is_ascii_graphic),
+        }
+    }
+
+    fn apply(self, bytes: &[u8]) -> Option<RequestId> {
+        if !self.accepts(bytes) {
+            None
+        } else if self == Self::Strip && !bytes.iter().all(u8::is_ascii_graphic) {
+            let stripped = bytes.iter().copied().filter(u8::is_ascii_graphic).collect::<Vec<_>>();
+            Some(RequestId::from_bytes(&stripped))
+        } else {
+            Some(RequestId::from_bytes(bytes))
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Format of request's id generated by middleware
+pub enum RequestIdFormat {
+    #[default]
+    ///Random UUID in hyphenated format (36 characters)
+    UuidV4Hyphenated,
+    ///Random UUID in simple format without hyphens (32 characters)
+    UuidV4Simple,
+    ///Time-ordered UUID in hyphenated format (36 characters).
+    ///
+    ///Ids generated by the same process are ordered by their creation, hence sorting by id sorts requests by their start
+    UuidV7,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Describes how to handle `X-Request-Id` which is already present in response
+pub enum ResponseRequestIdConflict {
+    ///Keep value set by the inner service.
+    ///
+    ///If it differs from request's id, then `http.request_id.response_mismatch` is set to `true`
+    KeepExisting,
+    #[default]
+    ///Overwrite with request's id
+    Overwrite,
+    ///Join both values with comma, placing existing value first.
+    ///
+    ///If it differs from request's id, then `http.request_id.response_mismatch` is set to `true`
+    AppendBoth,
+}
+
+impl ResponseRequestIdConflict {
+    fn apply(self, span: &tracing::Span, stats: &stats::MiddlewareStats, headers: &mut http::HeaderMap, request_id: &RequestId) {
+        let existing = match headers.get(REQUEST_ID) {
+            Some(existing) if self != Self::Overwrite => existing,
+            _ => {
+                match request_id.to_header_value() {
+                    Some(request_id) => {
+                        headers.insert(REQUEST_ID, request_id);
+                    },
+                    None => stats.header_insert_failures.inc(),
+                }
+                return;
+            }
+        };
+
+        if existing.as_bytes() == request_id.as_bytes() {
+            return;
+        }
+        span.record("http.request_id.response_mismatch", true);
+
+        if let Self::AppendBoth = self {
+            let existing = existing.as_bytes();
+            let mut value = Vec::with_capacity(existing.len() + 1 + request_id.as_bytes().len());
+            value.extend_from_slice(existing);
+            value.push(b',');
+            value.extend_from_slice(request_id.as_bytes());
+            match http::HeaderValue::from_bytes(&value) {
+                Ok(value) => {
+                    headers.insert(REQUEST_ID, value);
+                },
+                Err(_) => stats.header_insert_failures.inc(),
+            }
+        }
+    }
+}
+
+#[macro_export]
+///Declares `fn` function compatible with `MakeSpan` using provided parameters
+///
+///## Span fields
+///
+///Following fields are declared when span is created:
+///- `span.kind` - Set to `server`
+///- `http.request.method` - Request's method or `_OTHER`, if it is not one of methods defined by RFC 9110 or RFC 5789 (i.e. `PATCH`)
+///- `http.request.method_original` - Original method, populated only if `http.request.method` is `_OTHER`
+///- `url.path`
+///- `url.query` - Not populated if URI exceeds limit. Values of parameters specified via layer [config](struct.HttpRequestLayer.html#method.with_redact_query_params) are redacted
+///- `url.length` - Original length of URI's path and query, populated only if it exceeds layer's [limit](struct.HttpRequestLayer.html#method.with_max_uri_len)
+///- `url.truncated` - Set to `true` if URI exceeds limit, in which case `url.path` is truncated to the limit
+///- `url.scheme`
+///- `url.full` - Optional. Absolute URL, reconstructed from URI and `Host` header (scheme defaults to `http` if URI has none), populated if enabled via layer [config](struct.HttpRequestLayer.html#method.with_record_full_url). URL is truncated in the same way as `url.path` and its query is redacted in the same way as `url.query`
+///- `server.address` - Host addressed by request, taken from `Host` header or, if absent, URI's authority or [TlsInfo::sni]. IPv6 address is recorded without brackets
+///- `server.endpoint` - Optional. Name of the [endpoint](struct.Endpoint.html) that accepted request, populated if endpoint extractor is specified via layer [config](struct.HttpRequestLayer.html#method.with_extract_endpoint)
+///- `server.port` - Port addressed by request, taken from the same source as `server.address`. If port is not explicit, it is derived from URI's scheme (`80` or `443`) or, if scheme is unknown, taken from the [endpoint](struct.Endpoint.html) that accepted request
+///- `http.request_id` - Inherited from request 'X-Request-Id' (or headers specified via layer [config](struct.HttpRequestLayer.html#method.with_request_id_headers)) or generated in configured [format](struct.HttpRequestLayer.html#method.with_request_id_generator), depending on [policy](struct.HttpRequestLayer.html#method.with_request_id_policy)
+///- `http.request_id.origin` - Origin of request's id: `inherited`, `generated` or `trace`
+///- `http.request_id.incoming` - Request's id supplied by client, populated only with [RequestIdPolicy::AlwaysGenerate]
+///- `http.request_id.rejected` - Set to `true` if request's id supplied by client is rejected as per layer [config](struct.HttpRequestLayer.html#method.with_request_id_validation)
+///- `http.request_id.duplicates` - Number of extra values of request's id header, populated only if header is repeated. Only first value is used
+///- `http.correlation_id` - Optional. End-to-end correlation id, populated if its header is specified via layer [config](struct.HttpRequestLayer.html#method.with_correlation_id_header) and present in request
+///- `peer.service` - Optional. Name of the calling service, populated if its header is specified via layer [config](struct.HttpRequestLayer.html#method.with_peer_service_header) and present in request with valid UTF-8 value
+///- `http.request.resend_count` - Optional. Number of retry attempt, populated if retry header is specified via layer [config](struct.HttpRequestLayer.html#method.with_retry_header) and present in request with valid value
+///- `http.request.idempotency_key.present` - Optional. Set to `true` if request contains `Idempotency-Key`, populated under the same config as `http.request.resend_count`
+///- `user_agent.original` - Only populated if user agent header is present
+///- `user_agent.name` - Name of leading product token of user agent (e.g. `curl` out of `curl/8.4.0`). Not populated if user agent exceeds 512 bytes
+///- `user_agent.version` - Version of leading product token of user agent, populated together with `user_agent.name` if present
+///- `client.address` - Optionally added if IP extractor is specified via layer [config](struct.HttpRequestLayer.html#method.with_extract_client_ip). IPv4-mapped IPv6 addresses are recorded as IPv4, unless disabled via layer [config](struct.HttpRequestLayer.html#method.with_normalize_mapped_ipv4). Privacy transformation can be specified via layer [config](struct.HttpRequestLayer.html#method.with_client_ip_privacy)
+///- `client.address.family` - Either `ipv4` or `ipv6`, populated together with `client.address`
+///- `client.port` - Optional. Port of [ConnectionInfo::peer], populated only if `client.address` is the peer itself (i.e. it is not derived from proxy headers)
+///- `network.peer.address` - Optional. IP address of [ConnectionInfo::peer], populated if connection info is present in request's extensions. Privacy transformation of `client.address` applies to it as well
+///- `network.peer.port` - Optional. Port of [ConnectionInfo::peer], populated together with `network.peer.address`
+///- `network.local.address` - Optional. IP address of [ConnectionInfo::local], populated if it is known
+///- `network.local.port` - Optional. Port of [ConnectionInfo::local], populated together with `network.local.address`
+///- `tls.protocol.version` - Optional. Negotiated TLS version, populated if [TlsInfo] is present in request's extensions
+///- `tls.cipher` - Optional. Negotiated cipher suite, populated if it is specified by [TlsInfo]
+///- `http.request.timeout_budget_ms` - Optional. Time budget of the request, taken from `grpc-timeout` or layer's default [config](struct.HttpRequestLayer.html#method.with_default_timeout_budget)
+///- `http.request.body.size` - Optional. Request's `Content-Length`, populated only if header is present and valid. Replaced with number of received bytes once body is fully read, if [body::BodyLayer] tracks request body
+///- `network.protocol.name` - Either `http`, `grpc` or `grpc-web` depending on `content-type`
+///- `network.protocol.version` - HTTP version, over which request is received (including gRPC calls), as string: `0.9`, `1.0`, `1.1`, `2` or `3`
+///- `rpc.service` - Fully qualified service name of gRPC call, populated only if path is valid gRPC path (i.e. `/<service>/<method>`)
+///- `rpc.method` - Method name of gRPC call, populated together with `rpc.service`
+///- `http.server.ready_wait_ms` - Optional. Time spent waiting for inner service readiness before request is dispatched, populated if enabled via layer [config](struct.HttpRequestLayer.html#method.with_record_ready_wait)
+///- `network.stream.id` - Optional. Populated if stream info extractor is specified via layer [config](struct.HttpRequestLayer.html#method.with_extract_stream_info)
+///- `network.connection.id` - Optional. Populated if connection id extractor is specified via layer [config](struct.HttpRequestLayer.html#method.with_extract_connection_id)
+///- `network.connection.requests_seen` - Number of requests seen on connection so far, including current one. Populated together with `network.connection.id`
+///- `http.request.fingerprint` - Optional. Request's [fingerprint](fingerprint/index.html), populated if enabled via layer [config](struct.HttpRequestLayer.html#method.with_request_fingerprint)
+///- `http.headers` - Optional. Populated if at least 1 header specified via [LayerContext::INSPECT_HEADERS] or layer [config](struct.HttpRequestLayer.html#method.with_inspect_headers). Contains all headers, except denied ones, if enabled via layer [config](struct.HttpRequestLayer.html#method.with_inspect_headers_all). Not populated if headers are recorded as individual [fields](#header-fields). Values that are not valid UTF-8 (e.g. gRPC's binary `-bin` metadata) are rendered as base64, prefixed with `b64:`
+///- `http.request.cookies` - Optional. Cookies specified via layer [config](struct.HttpRequestLayer.html#method.with_inspect_cookies), populated if any of them is present in request
+///- `session.id` - Optional. Value of session cookie specified via layer [config](struct.HttpRequestLayer.html#method.with_session_cookie), populated if it is present in request. Replaced with output of [hash](struct.HttpRequestLayer.html#method.with_session_id_hash) function, if any
+///- `http.request.header.x-forwarded-for.first` - Optional. Raw first entry of `X-Forwarded-For`, populated if enabled via layer [config](struct.HttpRequestLayer.html#method.with_record_forwarded_for_first)
+///- `http.request.expect_continue` - Set to `true` if request contains `Expect: 100-continue`, i.e. interim `100 Continue` response is implied before client sends body
+///- `http.request.header.sec-fetch-site` - Optional. Value of `Sec-Fetch-Site`, populated if enabled via layer [config](struct.HttpRequestLayer.html#method.with_record_fetch_metadata). Unknown values are recorded as `other`
+///- `http.request.header.sec-fetch-mode` - Optional. Value of `Sec-Fetch-Mode`, populated under the same config as `http.request.header.sec-fetch-site`
+///- `http.request.header.sec-fetch-dest` - Optional. Value of `Sec-Fetch-Dest`, populated under the same config as `http.request.header.sec-fetch-site`
+///- `http.request.header.sec-fetch-user` - Optional. Value of `Sec-Fetch-User` (i.e. `?1`), populated under the same config as `http.request.header.sec-fetch-site`
+///- `trace_id` - Optional. Hex encoded trace id of the span, populated if enabled via layer [config](struct.HttpRequestLayer.html#method.with_record_trace_ids). Set to request's id, if trace context is not available
+///- `span_id` - Optional. Hex encoded id of the span, populated together with `trace_id` only if trace context is available
+///- `http.request.body.first_byte_delay_ms` - Optional. Time until the first chunk of request's body arrived, populated if enabled via [BodyLayer](body/struct.BodyLayer.html#method.with_request_first_byte_delay)
+///- `http.request.body.sha256` - Optional. First 16 hex characters of request's body digest, populated if enabled via [BodyLayer](body/struct.BodyLayer.html#method.with_request_body_hash)
+///- `http.request.body.hash_skipped` - Set to `true` if request's body is too large to be hashed
+///- `http.route` - Optional. Matched route template (e.g. `/users/{id}`), populated if inner service inserts [MatchedRoute] into response's extensions. Can be also recorded by handler via [SpanHandle::record_route]
+///- `enduser.id` - Optional. Authenticated user, populated if inner service inserts [AuthenticatedUser] into response's extensions. Can be also recorded by handler via [SpanHandle::record_user]
+///- `http.request_id.response_mismatch` - Set to `true` if inner service returned different `X-Request-Id`, which was kept as per layer [config](struct.HttpRequestLayer.html#method.with_response_request_id_conflict)
+///- `http.response.status_code` - Semantics of this code depends on `protocol`. gRPC status, which is sent only in trailers, is recorded once response's body yields them, which requires [ResponseBodyLayer](body/struct.ResponseBodyLayer.html), otherwise `UNKNOWN` is recorded
+///- `http.response.body.size` - Optional. Response's `Content-Length`, populated only if header is present and valid. Replaced with number of bytes actually streamed, once body ends, if tracked via [ResponseBodyLayer](body/struct.ResponseBodyLayer.html#method.with_track_response_body)
+///- `http.response.rate_limit.limit` - Value of `RateLimit-Limit` (or legacy `X-RateLimit-Limit`), populated only for throttled responses (`429` or `503` with `Retry-After`)
+///- `http.response.rate_limit.remaining` - Value of `RateLimit-Remaining` (or legacy `X-RateLimit-Remaining`), populated only for throttled responses
+///- `http.response.rate_limit.retry_after_seconds` - Value of `Retry-After` in seconds, populated only for throttled responses
+///- `http.response.retry_after` - Same as `http.response.rate_limit.retry_after_seconds`
+///- `http.response.ratelimit_remaining` - Same as `http.response.rate_limit.remaining`
+///- `http.response.connection_close` - Optional. Set to `true` if response contains `Connection: close`, populated if enabled via layer [config](struct.HttpRequestLayer.html#method.with_connection_diagnostics)
+///- `http.response.alt_svc.present` - Optional. Set to `true` if response contains `Alt-Svc`, populated if enabled via layer [config](struct.HttpRequestLayer.html#method.with_connection_diagnostics)
+///- `http.response.headers` - Optional. Response's headers specified via layer [config](struct.HttpRequestLayer.html#method.with_inspect_response_headers), populated if any of them is present in response
+///- `error.type` - Populated with `core::any::type_name` value of error type used by the service or `client_disconnect` if error is considered benign as per layer [config](struct.HttpRequestLayer.html#method.with_benign_error_filter)
+///- `error.category` - Optional. Category of the error, populated if error is recognized by categorizer specified via layer [config](struct.HttpRequestLayer.html#method.with_error_categorizer). Not populated for benign errors.
+///- `error.message` - Populated with `Display` content of the error, returned by underlying service, after processing request. Not populated for benign errors.
+///- `http.response.headers_stripped` - Optional. Number of response headers removed as not allow-listed, populated if allow-list is specified via layer [config](struct.HttpRequestLayer.html#method.with_response_header_allowlist) and any header is removed
+///- `process.request.allocated_bytes` - Optional. Amount of resource consumed by request, populated if tracker is specified via layer [config](struct.HttpRequestLayer.html#method.with_resource_tracker)
+///- `http.server.request.duration` - Time in seconds (as floating point number) elapsed since request is received until response or error is returned by underlying service. Not populated if request is cancelled
+///- `http.response.trailers` - Optional. Response's trailers specified via [ResponseBodyLayer](body/struct.ResponseBodyLayer.html#method.with_inspect_trailers), populated once body yields trailers containing any of them
+///
+///Loosely follows <https://opentelemetry.io/docs/specs/semconv/http/http-spans/#http-server>
+///
+///### Field order
+///
+///Fields are declared and recorded by middleware in the order listed above (also available as [REQUEST_SPAN_FIELDS](constant.REQUEST_SPAN_FIELDS.html)), regardless of which features are enabled.
+///Fields that are not populated are skipped, but never change relative order of the rest.
+///Additional fields are declared after all of the above, and their recording order is up to the user.
+///
+///## Additional fields
+///
+///Additional fields can be declared by passing extra arguments after `level` in the same way as you would pass it to `tracing::span!` macro
+///
+///Note that you need to use `tracing::field::Empty` if you want to add value later
+///
+///### Semantic conventions
+///
+///Field names above follow OpenTelemetry conventions, which is default.
+///To declare fields of different [convention](semconv/index.html) pass `semconv = <name>` right after `level`:
+///- `otel` - Default. Used with [OtelSemconv](semconv/struct.OtelSemconv.html)
+///- `ecs` - Used with [EcsSemconv](semconv/struct.EcsSemconv.html). Replaces `http.request_id` with `http.request.id`, `network.protocol.name` with `network.protocol`, `network.protocol.version` with `http.version`, `client.address` with `client.ip`, omits `span.kind` and records non-standard methods as they are, without `http.request.method_original`
+///
+///Selected convention must match layer's [config](struct.HttpRequestLayer.html#method.with_semantic_convention)
+///
+///```
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO, semconv = ecs, service_name = "<your name>"));
+///```
+///
+///### Experiment fields
+///
+///Experiments enabled via layer [config](struct.HttpRequestLayer.html#method.with_experiment_header) are recorded as `experiment.<name>` and must be declared as additional fields
+///
+///```
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO, experiment.checkout_v2 = tracing::field::Empty, experiment.search_rank = tracing::field::Empty));
+///```
+///
+///### Header fields
+///
+///Inspected headers can be recorded as `http.request.header.<name>` fields, if enabled via layer [config](struct.HttpRequestLayer.html#method.with_inspect_headers_as_fields).
+///Fields are declared by passing `headers = [...]` after `level` (and `semconv`), optionally followed by `keys` and additional fields.
+///Header is specified either as name of constant in [http::header](https://docs.rs/http/latest/http/header/index.html) (e.g. `ACCEPT_LANGUAGE` declares `http.request.header.accept-language`) or as string literal of lowercase name.
+///
+///```
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO, headers = [FORWARDED, ACCEPT, "x-tenant"], service_name = "<your name>"));
+///```
+///
+///### Typed fields
+///
+///Fields declared via [define_span_fields](macro.define_span_fields.html) can be declared by passing `keys = [...]` after `level`, optionally followed by additional fields.
+///
+///```
+///tower_http_tracing::define_span_fields!(pub UserId("user.id": str););
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO, keys = [UserId], service_name = "<your name>"));
+///```
+///
+///## Usage
+///
+///```
+///use tower_http_tracing::make_request_spanner;
+///
+///make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+/////Customize span with extra fields. You can use tracing::field::Empty if you want to omit value
+///make_request_spanner!(make_my_service_request_span("my_request", tracing::Level::INFO, service_name = "<your name>"));
+///
+///let span = make_my_request_span();
+///span.record("url.path", "I can override span field");
+///
+///```
+macro_rules! make_request_spanner {
+    ($fn:ident($name:literal, $level:expr)) => {
+        $crate::make_request_spanner!($fn($name, $level,));
+    };
+    ($fn:ident($name:literal, $level:expr, keys = [$($key:path),* $(,)?] $(, $($fields:tt)*)?)) => {
+        $crate::make_request_spanner!($fn($name, $level, $({ <$key as $crate::SpanFieldKey>::NAME } = $crate::tracing::field::Empty,)* $($($fields)*)?));
+    };
+    ($fn:ident($name:literal, $level:expr, headers = [$($header:tt),* $(,)?] $(, $($fields:tt)*)?)) => {
+        $crate::make_request_spanner!($fn($name, $level $(, $($fields)*)?, $({ $crate::__request_header_field!($header) } = $crate::tracing::field::Empty),*));
+    };
+    ($fn:ident($name:literal, $level:expr, semconv = otel $(, $($fields:tt)*)?)) => {
+        $crate::make_request_spanner!($fn($name, $level, $($($fields)*)?));
+    };
+    ($fn:ident($name:literal, $level:expr, semconv = ecs, keys = [$($key:path),* $(,)?] $(, $($fields:tt)*)?)) => {
+        $crate::make_request_spanner!($fn($name, $level, semconv = ecs, $({ <$key as $crate::SpanFieldKey>::NAME } = $crate::tracing::field::Empty,)* $($($fields)*)?));
+    };
+    ($fn:ident($name:literal, $level:expr, semconv = ecs, headers = [$($header:tt),* $(,)?] $(, $($fields:tt)*)?)) => {
+        $crate::make_request_spanner!($fn($name, $level, semconv = ecs $(, $($fields)*)?, $({ $crate::__request_header_field!($header) } = $crate::tracing::field::Empty),*));
+    };
+    ($fn:ident($name:literal, $level:expr, semconv = ecs $(, $($fields:tt)*)?)) => {
+        #[track_caller]
+        pub fn $fn() -> $crate::tracing::Span {
+            $crate::__request_span!($level, $name, kind = [], method_original = [], request_id = "http.request.id", client_address = "client.ip", protocol_name = "network.protocol", protocol_version = "http.version", fields = [$($($fields)*)?])
+        }
+    };
+    ($fn:ident($name:literal, $level:expr, $($fields:tt)*)) => {
+        #[track_caller]
+        pub fn $fn() -> $crate::tracing::Span {
+            $crate::__request_span!($level, $name, kind = ["server"], method_original = ["http.request.method_original"], request_id = "http.request_id", client_address = "client.address", protocol_name = "network.protocol.name", protocol_version = "network.protocol.version", fields = [$($fields)*])
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+//Declares request's span with fields of all conventions, which differ only by names passed to it
+macro_rules! __request_span {
+    ($level:expr, $name:literal, kind = [$($kind:literal)?], method_original = [$($method_original:literal)?], request_id = $request_id:literal, client_address = $client_address:literal, protocol_name = $protocol_name:literal, protocol_version = $protocol_version:literal, fields = [$($fields:tt)*]) => {{
+        use $crate::tracing::field;
+
+        $crate::tracing::span!(
+            $level,
+            $name,
+            //Defaults
+            $(span.kind = $kind,)?
+            //Assigned on creation of span
+            http.request.method = field::Empty,
+            $($method_original = field::Empty,)?
+            url.path = field::Empty,
+            url.query = field::Empty,
+            url.length = field::Empty,
+            url.truncated = field::Empty,
+            url.scheme = field::Empty,
+            url.full = field::Empty,
+            server.address = field::Empty,
+            server.endpoint = field::Empty,
+            server.port = field::Empty,
+            $request_id = field::Empty,
+            http.request_id.origin = field::Empty,
+            http.request_id.incoming = field::Empty,
+            http.request_id.rejected = field::Empty,
+            http.request_id.duplicates = field::Empty,
+            http.correlation_id = field::Empty,
+            peer.service = field::Empty,
+            http.request.resend_count = field::Empty,
+            http.request.idempotency_key.present = field::Empty,
+            user_agent.original = field::Empty,
+            user_agent.name = field::Empty,
+            user_agent.version = field::Empty,
+            $client_address = field::Empty,
+            client.address.family = field::Empty,
+            client.port = field::Empty,
+            network.peer.address = field::Empty,
+            network.peer.port = field::Empty,
+            network.local.address = field::Empty,
+            network.local.port = field::Empty,
+            tls.protocol.version = field::Empty,
+            tls.cipher = field::Empty,
+            http.request.timeout_budget_ms = field::Empty,
+            http.request.body.size = field::Empty,
+            $protocol_name = field::Empty,
+            $protocol_version = field::Empty,
+            rpc.service = field::Empty,
+            rpc.method = field::Empty,
+            //Assigned before request is passed to inner service
+            http.server.ready_wait_ms = field::Empty,
+            network.stream.id = field::Empty,
+            network.connection.id = field::Empty,
+            network.connection.requests_seen = field::Empty,
+            http.request.fingerprint = field::Empty,
+            http.headers = field::Empty,
+            http.request.cookies = field::Empty,
+            session.id = field::Empty,
+            "http.request.header.x-forwarded-for.first" = field::Empty,
+            http.request.expect_continue = field::Empty,
+            "http.request.header.sec-fetch-site" = field::Empty,
+            "http.request.header.sec-fetch-mode" = field::Empty,
+            "http.request.header.sec-fetch-dest" = field::Empty,
+            "http.request.header.sec-fetch-user" = field::Empty,
+            trace_id = field::Empty,
+            span_id = field::Empty,
+            //Assigned while request's body is consumed
+            http.request.body.first_byte_delay_ms = field::Empty,
+            http.request.body.sha256 = field::Empty,
+            http.request.body.hash_skipped = field::Empty,
+            //Assigned after request is complete
+            http.route = field::Empty,
+            enduser.id = field::Empty,
+            http.request_id.response_mismatch = field::Empty,
+            http.response.status_code = field::Empty,
+            http.response.body.size = field::Empty,
+            http.response.rate_limit.limit = field::Empty,
+            http.response.rate_limit.remaining = field::Empty,
+            http.response.rate_limit.retry_after_seconds = field::Empty,
+            http.response.retry_after = field::Empty,
+            http.response.ratelimit_remaining = field::Empty,
+            http.response.connection_close = field::Empty,
+            http.response.alt_svc.present = field::Empty,
+            http.response.headers = field::Empty,
+            error.type = field::Empty,
+            error.category = field::Empty,
+            error.message = field::Empty,
+            http.response.headers_stripped = field::Empty,
+            process.request.allocated_bytes = field::Empty,
+            http.server.request.duration = field::Empty,
+            //Assigned while response's body is consumed
+            http.response.trailers = field::Empty,
+            $($fields)*
+        )
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+//Name of span field for header, specified by constant's name or by literal
+macro_rules! __request_header_field {
+    ($header:ident) => {{
+        const NAME: &str = concat!("http.request.header.", stringify!($header));
+        const FIELD: [u8; NAME.len()] = $crate::__header_field_name(NAME);
+        const FIELD_NAME: &str = match core::str::from_utf8(&FIELD) {
+            Ok(name) => name,
+            Err(_) => panic!("Header's constant name must be ASCII"),
+        };
+        FIELD_NAME
+    }};
+    ($header:literal) => {
+        concat!("http.request.header.", $header)
+    };
+}
+
+#[doc(hidden)]
+//Converts name of header's constant into field name, lowercasing it and replacing `_` with `-`
+pub const fn __header_field_name<const N: usize>(name: &str) -> [u8; N] {
+    let name = name.as_bytes();
+    let mut out = [0u8; N];
+    let mut idx = 0;
+    while idx < N {
+        out[idx] = match name[idx] {
+            b'_' => b'-',
+            byt => byt.to_ascii_lowercase(),
+        };
+        idx += 1;
+    }
+    out
+}
+
+#[macro_export]
+///Declares `fn` function compatible with `MakeChildSpan` using provided parameters
+///
+///Intended to create spans of downstream calls made by handler via [RequestInfo::child_span].
+///
+///Due to `tracing` requiring static callsite, span's name and level are fixed per function, so you need to declare function per kind of downstream call.
+///
+///## Span fields
+///
+///- `span.kind` - Set to `client`
+///- `http.request_id` - Request's id
+///- `deadline.remaining_ms` - Optional. Time remaining until request's deadline
+///- `traceresponse.trace_id` - Optional. Trace id returned by downstream via `traceresponse`, populated by [record_trace_response](propagation/fn.record_trace_response.html)
+///- `traceresponse.span_id` - Optional. Span id returned by downstream via `traceresponse`, populated together with `traceresponse.trace_id`
+///
+///Additional fields can be declared by passing extra arguments after `level` in the same way as you would pass it to `tracing::span!` macro
+///
+///## Usage
+///
+///```
+///use tower_http_tracing::{make_child_spanner, RequestInfo, SpanHandle};
+///
+///make_child_spanner!(make_db_span("db_query", tracing::Level::INFO, db.system = "postgresql"));
+///
+///fn handle(req: &http::Request<()>) {
+///    let info = req.extensions().get::<RequestInfo>().unwrap();
+///    let handle = req.extensions().get::<SpanHandle>().unwrap();
+///    let span = info.child_span(handle, make_db_span);
+///    let _entered = span.enter();
+///}
+///```
+macro_rules! make_child_spanner {
+    ($fn:ident($name:literal, $level:expr)) => {
+        $crate::make_child_spanner!($fn($name, $level,));
+    };
+    ($fn:ident($name:literal, $level:expr, $($fields:tt)*)) => {
+        #[track_caller]
+        pub fn $fn(parent: &$crate::tracing::Span) -> $crate::tracing::Span {
+            use $crate::tracing::field;
+
+            $crate::tracing::span!(
+                parent: parent,
+                $level,
+                $name,
+                span.kind = "client",
+                http.request_id = field::Empty,
+                deadline.remaining_ms = field::Empty,
+                traceresponse.trace_id = field::Empty,
+                traceresponse.span_id = field::Empty,
+                $(
+                    $fields
+                )*
+            )
+        }
+    };
+}
+
+#[derive(Clone)]
+///Request's information
+///
+///`Debug` implementation redacts request's id to the first 8 characters, use [full_debug](#method.full_debug) to print it fully
+///
+///It is accessible via [extensions](https://docs.rs/http/latest/http/struct.Extensions.html)
+pub struct RequestInfo {
+    ///Request's protocol
+    pub protocol: Protocol,
+    ///Request's id
+    pub request_id: RequestId,
+    ///Origin of request's id
+    pub request_id_origin: RequestIdOrigin,
+    ///Request's id supplied by client, when it is not trusted as request's id.
+    ///
+    ///Populated only with [RequestIdPolicy::AlwaysGenerate]
+    pub incoming_request_id: Option<RequestId>,
+    ///End-to-end correlation id, populated if its header is [configured](struct.HttpRequestLayer.html#method.with_correlation_id_header) and present in request
+    pub correlation_id: Option<RequestId>,
+    ///Name of the calling service, populated if its [header](struct.HttpRequestLayer.html#method.with_peer_service_header) is present in request
+    pub peer_service: Option<Box<str>>,
+    ///Client's IP address extracted, if available.
+    ///
+    ///If [IpPrivacy] is configured, then it is populated with address after applying privacy transformation, unless it is [IpPrivacy::Hash]
+    pub client_ip: Option<IpAddr>,
+    ///Textual representation of client's IP, as recorded in span, when it cannot be represented by `client_ip` (i.e. [IpPrivacy::Hash] is used)
+    pub client_ip_display: Option<Box<str>>,
+    ///Deadline by which request is expected to complete, if known.
+    ///
+    ///Determined from `grpc-timeout` for gRPC requests or from default budget for other requests, if [configured](struct.HttpRequestLayer.html#method.with_default_timeout_budget)
+    pub deadline: Option<std::time::Instant>,
+    ///Trace context extracted from request, if available.
+    ///
+    ///Populated only if layer is [configured](struct.HttpRequestLayer.html#method.with_propagation_extract) to extract it
+    pub trace_context: Option<propagation::TraceContext>,
+    ///Protocol specific details of the request
+    pub details: ProtocolDetails,
+    ///Name of the request's header, from which client supplied request's id. `None` if it is absent
+    ///
+    ///See [with_request_id_headers](struct.HttpRequestLayer.html#method.with_request_id_headers)
+    pub request_id_source: Option<http::HeaderName>,
+    ///Whether request is given span, as decided by [LayerContext::should_sample]
+    pub sampled: bool,
+    ///Instant at which request is received by middleware
+    pub started: std::time::Instant,
+    ///Clock used by middleware, which measures time [remaining](#method.remaining) until deadline.
+    ///
+    ///System clock is used if `None`
+    pub clock: Option<Arc<dyn clock::Clock>>,
+}
+
+///Typed span field key, declared via [define_span_fields](macro.define_span_fields.html)
+pub trait SpanFieldKey {
+    ///Field's name
+    const NAME: &'static str;
+    ///Field's value type
+    type Value: tracing::Value + ?Sized;
+}
+
+#[macro_export]
+///Declares typed span fields, generating zero sized key type implementing [SpanFieldKey] per field
+///
+///Declared keys can be passed to [make_request_spanner](macro.make_request_spanner.html) via `keys = [...]` to declare fields in span
+///and recorded via [SpanHandle::record_key], which only accepts declared keys with matching value type.
+///
+///## Usage
+///
+///```
+///tower_http_tracing::define_span_fields!(
+///    ///Authenticated user
+///    pub UserId("user.id": str);
+///    pub TenantId("tenant.id": u64);
+///);
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO, keys = [UserId, TenantId]));
+///
+///fn handle(handle: &tower_http_tracing::SpanHandle) {
+///    handle.record_key::<UserId>("42");
+///    handle.record_key::<TenantId>(&1);
+///}
+///```
+///
+///Undeclared keys cannot be recorded:
+///
+///```compile_fail
+///struct UserId;
+///
+///fn handle(handle: &tower_http_tracing::SpanHandle) {
+///    handle.record_key::<UserId>("42");
+///}
+///```
+///
+///Value must match declared type:
+///
+///```compile_fail
+///tower_http_tracing::define_span_fields!(pub TenantId("tenant.id": u64););
+///
+///fn handle(handle: &tower_http_tracing::SpanHandle) {
+///    handle.record_key::<TenantId>("42");
+///}
+///```
+macro_rules! define_span_fields {
+    ($($(#[$meta:meta])* $vis:vis $key:ident($name:literal: $typ:ty);)+) => {
+        $(
+            $(#[$meta])*
+            #[derive(Copy, Clone, Debug)]
+            $vis struct $key;
+
+            impl $crate::SpanFieldKey for $key {
+                const NAME: &'static str = $name;
+                type Value = $typ;
+            }
+        )+
+    };
+}
+
+#[derive(Clone)]
+///Handle to the request's span
+///
+///It is accessible via [extensions](https://docs.rs/http/latest/http/struct.Extensions.html)
+pub struct SpanHandle {
+    span: tracing::Span,
+}
+
+impl SpanHandle {
+    #[inline(always)]
+    ///Access underlying span
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    #[inline(always)]
+    ///Records typed field `K` into span
+    ///
+    ///Field must be declared in span via [make_request_spanner](macro.make_request_spanner.html)
+    pub fn record_key<K: SpanFieldKey>(&self, value: &K::Value) {
+        self.span.record(K::NAME, value);
+    }
+
+    #[inline(always)]
+    ///Records matched route template (e.g. `/users/{id}`) as `http.route`
+    ///
+    ///Routing happens after span is created, hence route must be reported back by the router or handler.
+    ///Overwritten by [MatchedRoute] of the response, if any
+    pub fn record_route(&self, route: &str) {
+        self.span.record("http.route", route);
+    }
+
+    #[inline]
+    ///Records identifier of authenticated user as `enduser.id`
+    ///
+    ///Overwritten by [AuthenticatedUser] of the response, if any
+    pub fn record_user(&self, user: &str) {
+        self.span.record("enduser.id", user);
+    }
+
+    #[cfg(feature = "tracing-error")]
+    #[inline]
+    ///Captures `SpanTrace` within request's span, regardless of currently entered span
+    ///
+    ///Requires `tracing_error::ErrorLayer` to be installed in subscriber
+    pub fn capture_spantrace(&self) -> tracing_error::SpanTrace {
+        let _entered = self.span.enter();
+        tracing_error::SpanTrace::capture()
+    }
+
+    #[inline(always)]
+    ///Creates scope to propagate request's span into background tasks
+    pub fn scope(&self) -> TracingScope {
+        TracingScope {
+            span: self.span.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for SpanHandle {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.span, fmt)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+///Route template matched by router (e.g. `/users/{id}`), recorded as `http.route` once inserted into response's extensions
+///
+///Unlike `url.path`, route has bounded cardinality, hence it is suitable for grouping of requests.
+///
+///## Usage
+///
+///```rust
+///use tower_http_tracing::{http, HttpRequestLayer, MatchedRoute};
+///use tower::ServiceBuilder;
+///
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+///let layer = HttpRequestLayer::new_simple(make_my_request_span);
+///let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+///    let mut response = http::Response::new(());
+///    response.extensions_mut().insert(MatchedRoute("/users/{id}".to_owned()));
+///    Ok::<_, core::convert::Infallible>(response)
+///});
+///```
+pub struct MatchedRoute(pub String);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+///Identifier of authenticated user, recorded as `enduser.id` once inserted into response's extensions
+///
+///Authentication usually happens in inner service, after span is created, hence user must be reported back by it.
+///
+///## Usage
+///
+///```rust
+///use tower_http_tracing::{http, AuthenticatedUser, HttpRequestLayer};
+///use tower::ServiceBuilder;
+///
+///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+///let layer = HttpRequestLayer::new_simple(make_my_request_span);
+///let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+///    let mut response = http::Response::new(());
+///    response.extensions_mut().insert(AuthenticatedUser("user-1".to_owned()));
+///    Ok::<_, core::convert::Infallible>(response)
+///});
+///```
+pub struct AuthenticatedUser(pub String);
+
+#[derive(Clone)]
+///Scope of the request's span, that can be moved into background tasks.
+///
+///Created via [SpanHandle::scope]
+pub struct TracingScope {
+    span: tracing::Span,
+}
+
+impl TracingScope {
+    #[inline(always)]
+    ///Instruments `fut` with request's span
+    ///
+    ///If request's span is disabled, then span active at the time of polling `fut` is used instead
+    pub fn instrument<F: Future>(&self, fut: F) -> tracing::instrument::Instrumented<F> {
+        tracing::Instrument::instrument(fut, self.span.clone().or_current())
+    }
+
+    #[cfg(feature = "tracing-error")]
+    #[inline]
+    ///Captures `SpanTrace` within request's span, which is useful to construct errors in background tasks
+    ///
+    ///Requires `tracing_error::ErrorLayer` to be installed in subscriber
+    pub fn capture_spantrace(&self) -> tracing_error::SpanTrace {
+        let _entered = self.span.enter();
+        tracing_error::SpanTrace::capture()
+    }
+
+    #[inline(always)]
+    ///Access underlying span
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+}
+
+///Request's span information
+///
+///Created on every request by the middleware, but not accessible to the user directly
+pub struct RequestSpan {
+    ///Underlying tracing span
+    pub span: tracing::Span,
+    ///Request's information
+    pub info: RequestInfo,
+}
+
+impl RequestInfo {
+    #[inline]
+    ///Returns time remaining until [deadline](#structfield.deadline), if any.
+    ///
+    ///Returns zero duration if deadline has already passed.
+    pub fn remaining(&self) -> Option<std::time::Duration> {
+        let deadline = self.deadline?;
+        let now = match &self.clock {
+            Some(clock) => clock.instant(),
+            None => std::time::Instant::now(),
+        };
+        Some(deadline.saturating_duration_since(now))
+    }
+
+    #[inline(always)]
+    ///Returns `Debug` implementation, that prints information without redaction
+    ///
+    ///Only use it in trusted contexts
+    pub fn full_debug(&self) -> impl fmt::Debug + '_ {
+        RequestInfoDebug {
+            info: self,
+            redact: false,
+        }
+    }
+
+    ///Creates child span of the request's span, recording request id and remaining time budget
+    ///
+    ///Span is created by `make_span` declared via [make_child_spanner](macro.make_child_spanner.html), which determines its name and level.
+    ///Records following fields:
+    ///- `http.request_id` - Request's id
+    ///- `deadline.remaining_ms` - Optional. Time remaining until [deadline](#structfield.deadline), if any
+    pub fn child_span(&self, parent: &SpanHandle, make_span: MakeChildSpan) -> tracing::Span {
+        let span = make_span(parent.span());
+        semconv::record_request_id(&span, "http.request_id", &self.request_id);
+        if let Some(remaining) = self.remaining() {
+            span.record("deadline.remaining_ms", remaining.as_millis() as u64);
+        }
+        span
+    }
+}
+
+const REDACTED_REQUEST_ID_LEN: usize = 8;
+
+struct RedactedRequestId<'a>(&'a RequestId);
+
+impl fmt::Debug for RedactedRequestId<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.as_str() {
+            Some(id) => match id.char_indices().nth(REDACTED_REQUEST_ID_LEN) {
+                Some((idx, _)) => fmt.write_fmt(format_args!("\"{}…\"", &id[..idx])),
+                None => fmt::Debug::fmt(id, fmt),
+            },
+            None => fmt.write_fmt(format_args!("\"{:.*}…\"", REDACTED_REQUEST_ID_LEN, self.0.hex().to_string())),
+        }
+    }
+}
+
+struct RequestInfoDebug<'a> {
+    info: &'a RequestInfo,
+    redact: bool,
+}
+
+impl fmt::Debug for RequestInfoDebug<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = fmt.debug_struct("RequestInfo");
+        out.field("protocol", &self.info.protocol);
+        if self.redact {
+            out.field("request_id", &RedactedRequestId(&self.info.request_id));
+        } else {
+            out.field("request_id", &self.info.request_id);
+        }
+        //client_ip is already transformed according to privacy mode
+        match self.info.client_ip_display.as_deref() {
+            Some(client_ip) => out.field("client_ip", &client_ip),
+            None => out.field("client_ip", &self.info.client_ip),
+        };
+        out.field("deadline", &self.info.deadline);
+        out.field("trace_context", &self.info.trace_context);
+        out.finish()
+    }
+}
+
+impl fmt::Debug for RequestInfo {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&RequestInfoDebug { info: self, redact: true }, fmt)
+    }
+}
+
+#[inline(always)]
+fn ip_family(ip: &IpAddr) -> &'static str {
+    match ip {
+        IpAddr::V4(_) => "ipv4",
+        IpAddr::V6(_) => "ipv6",
+    }
+}
+
+impl RequestInfo {
+    //Determines information required to handle and correlate request without recording anything.
+    //Requests without span are given only this information
+    fn resolve(context: &impl LayerContext, options: &Options, parts: &http::request::Parts) -> Self {
+        let started = options.instant();
+        let protocol = parts.headers
+                            .get(http::header::CONTENT_TYPE)
+                            .map_or(Protocol::Http, |content_type| Protocol::from_content_type(content_type.as_bytes()));
+
+        //Trace context must be known before request id can be derived from it
+        let trace_context = propagation::PropagationFormat::extract_first(options.propagation_extract, &parts.headers);
+        let inherited_request_id = options.request_id(&parts.headers);
+        let validated_request_id = inherited_request_id.and_then(|(_, request_id)| options.request_id_validation.apply(request_id.as_bytes()));
+        let (request_id, request_id_origin, incoming_request_id) = match (validated_request_id, options.request_id_policy, trace_context) {
+            (Some(incoming), RequestIdPolicy::AlwaysGenerate, _) => (context.generate_request_id(options.request_id_format), RequestIdOrigin::Generated, Some(incoming)),
+            (Some(request_id), _, _) => (request_id, RequestIdOrigin::Inherited, None),
+            (None, RequestIdPolicy::DeriveFromTrace, Some(trace_context)) => (RequestId::from_trace_id(trace_context.trace_id), RequestIdOrigin::DerivedFromTrace, None),
+            (None, _, _) => (context.generate_request_id(options.request_id_format), RequestIdOrigin::Generated, None),
+        };
+        let correlation_id = options.correlation_id_header.as_ref()
+                                                          .and_then(|name| parts.headers.get(name))
+                                                          .and_then(|correlation_id| options.request_id_validation.apply(correlation_id.as_bytes()));
+
+        let grpc_timeout = match protocol {
+            Protocol::Grpc | Protocol::GrpcWeb => parts.headers.get(grpc::GRPC_TIMEOUT).and_then(|timeout| time::parse_grpc_timeout(timeout.as_bytes())),
+            Protocol::Http => None,
+        };
+        let timeout_budget = match protocol {
+            Protocol::Grpc | Protocol::GrpcWeb => grpc_timeout,
+            Protocol::Http => options.default_timeout_budget,
+        };
+
+        Self {
+            protocol,
+            request_id,
+            request_id_origin,
+            incoming_request_id,
+            correlation_id,
+            peer_service: None,
+            client_ip: None,
+            client_ip_display: None,
+            deadline: timeout_budget.and_then(|budget| started.checked_add(budget)),
+            trace_context,
+            details: ProtocolDetails::from_parts(protocol, grpc_timeout, parts),
+            request_id_source: inherited_request_id.map(|(name, _)| name.clone()),
+            sampled: true,
+            started,
+            clock: options.clock.clone(),
+        }
+    }
+}
+
+impl RequestSpan {
+    #[inline]
+    ///Creates new request span
+    pub fn new(context: &impl LayerContext, span: tracing::Span, parts: &http::request::Parts) -> Self {
+        Self::with_options(context, &Options::default(), span, parts)
+    }
+
+    fn with_options(context: &impl LayerContext, options: &Options, span: tracing::Span, parts: &http::request::Parts) -> Self {
+        let mut info = RequestInfo::resolve(context, options, parts);
+        Self::record(context, options, &span, parts, &mut info);
+        Self {
+            span,
+            info,
+        }
+    }
+
+    //Records request's information into `span`, populating rest of `info`
+    fn record(context: &impl LayerContext, options: &Options, span: &tracing::Span, parts: &http::request::Parts, info: &mut RequestInfo) {
+        let _entered = span.enter();
+
+        let client_ip = match context.extract_client_ip(&span, parts) {
+            Some(client_ip) if !options.raw_mapped_ipv4 => Some(client_ip.to_canonical()),
+            client_ip => client_ip,
+        };
+        let client_ip_family = client_ip.as_ref().map(ip_family);
+        let connection = parts.extensions.get::<ConnectionInfo>();
+        let peer_ip = connection.map(|connection| match connection.peer.ip() {
+            peer_ip if !options.raw_mapped_ipv4 => peer_ip.to_canonical(),
+            peer_ip => peer_ip,
+        });
+        let client_port = match (connection, client_ip) {
+            (Some(connection), Some(client_ip)) if peer_ip == Some(client_ip) => Some(connection.peer.port()),
+            _ => None,
+        };
+        let (client_ip, client_ip_display) = match (client_ip, options.client_ip_privacy) {
+            (Some(client_ip), IpPrivacy::Raw) => (Some(client_ip), None),
+            (Some(client_ip), IpPrivacy::Truncate) => (Some(IpPrivacy::truncate(client_ip)), None),
+            (Some(client_ip), IpPrivacy::Hash(hash)) => (None, Some(hash(&client_ip).into_boxed_str())),
+            (None, _) => (None, None),
+        };
+        let inherited_request_id = info.request_id_source.as_ref().and_then(|name| parts.headers.get(name).map(|value| (name, value)));
+
+        //Fields are recorded in order of declaration, see `make_request_spanner`
+        let semconv = options.semconv();
+        semconv.record_method(&span, &parts.method);
+        let uri_len = parts.uri.path_and_query().map_or(0, |path_and_query| path_and_query.as_str().len());
+        let max_uri_len = options.max_uri_len.unwrap_or(DEFAULT_MAX_URI_LEN);
+        let is_uri_truncated = uri_len > max_uri_len;
+        let path = match is_uri_truncated {
+            true => headers::truncate_str(parts.uri.path(), max_uri_len),
+            false => parts.uri.path(),
+        };
+        let query = parts.uri.query().filter(|_| !is_uri_truncated).map(|query| headers::redact_query(query, options.redact_query_params));
+        semconv.record_path(&span, path);
+        if let Some(query) = query.as_deref() {
+            semconv.record_query(&span, query);
+        }
+        if is_uri_truncated {
+            span.record("url.length", uri_len as u64);
+            span.record("url.truncated", true);
+        }
+        if let Some(scheme) = parts.uri.scheme() {
+            semconv.record_scheme(&span, scheme.as_str());
+        }
+        if options.record_full_url {
+            let url = headers::FullUrl {
+                scheme: parts.uri.scheme_str().unwrap_or("http"),
+                authority: headers::request_authority(parts),
+                path,
+                query: query.as_deref(),
+            };
+            span.record("url.full", tracing::field::display(url));
+        }
+        let tls = parts.extensions.get::<TlsInfo>();
+        let server_address = headers::server_address(parts).or_else(|| {
+            let sni = tls?.sni.as_deref().filter(|sni| !sni.is_empty())?;
+            Some((sni, None))
+        });
+        if let Some((host, _)) = server_address {
+            span.record("server.address", host);
+        }
+        let endpoint = options.extract_endpoint.and_then(|extract| extract(parts));
+        if let Some(endpoint) = endpoint.as_ref() {
+            span.record("server.endpoint", endpoint.name);
+        }
+        let default_port = match parts.uri.scheme() {
+            Some(scheme) if *scheme == http::uri::Scheme::HTTPS => Some(443),
+            Some(scheme) if *scheme == http::uri::Scheme::HTTP => Some(80),
+            _ => None,
+        };
+        let server_port = match server_address {
+            Some((_, Some(port))) => Some(port),
+            Some((_, None)) => default_port.or(endpoint.map(|endpoint| endpoint.port)),
+            None => endpoint.map(|endpoint| endpoint.port),
+        };
+        if let Some(port) = server_port {
+            span.record("server.port", port);
+        }
+        semconv.record_request_id(&span, &info.request_id);
+        span.record("http.request_id.origin", info.request_id_origin.as_str());
+        if let Some(incoming_request_id) = info.incoming_request_id.as_ref() {
+            semconv::record_request_id(&span, "http.request_id.incoming", incoming_request_id);
+        }
+        //Supplied id is neither used nor kept as incoming one only when it is rejected
+        if inherited_request_id.is_some() && info.request_id_origin != RequestIdOrigin::Inherited && info.incoming_request_id.is_none() {
+            span.record("http.request_id.rejected", true);
+        }
+        if let Some((name, _)) = inherited_request_id {
+            let values = parts.headers.get_all(name);
+            let duplicates = values.iter().count() - 1;
+            if duplicates > 0 {
+                span.record("http.request_id.duplicates", duplicates as u64);
+                tracing::warn!(target: targets::SPAN, header = %name, values = %headers::ExtraHeaderValues(values), "Request id header is duplicated, only first value is used");
+            }
+        }
+        if let Some(correlation_id) = info.correlation_id.as_ref() {
+            semconv::record_request_id(&span, "http.correlation_id", correlation_id);
+        }
+        let peer_service = options.peer_service_header.as_ref()
+                                                      .and_then(|name| parts.headers.get(name))
+                                                      .and_then(|peer_service| peer_service.to_str().ok())
+                                                      .filter(|peer_service| !peer_service.is_empty());
+        if let Some(peer_service) = peer_service {
+            span.record("peer.service", peer_service);
+        }
+        if let Some(retry_header) = options.retry_header.as_ref() {
+            let resend_count = parts.headers.get(retry_header).and_then(|value| core::str::from_utf8(value.as_bytes()).ok()?.trim().parse::<u32>().ok());
+            if let Some(resend_count) = resend_count {
+                span.record("http.request.resend_count", resend_count);
+            }
+            if parts.headers.contains_key(IDEMPOTENCY_KEY) {
+                span.record("http.request.idempotency_key.present", true);
+            }
+        }
+        if let (true, Some((name, original))) = (info.incoming_request_id.as_ref().unwrap_or(&info.request_id).truncated(), inherited_request_id) {
+            tracing::warn!(target: targets::SPAN, header = %name, len = original.len(), "Request id exceeds {REQUEST_ID_MAX_LEN} bytes and has been truncated");
+        }
+        if let Some(user_agent) = parts.headers.get(http::header::USER_AGENT).and_then(|header| header.to_str().ok()) {
+            semconv.record_user_agent(&span, user_agent);
+            if let Some((name, version)) = Some(user_agent).filter(|user_agent| user_agent.len() <= USER_AGENT_MAX_PARSE_LEN).and_then(headers::user_agent_product) {
+                span.record("user_agent.name", name);
+                if let Some(version) = version {
+                    span.record("user_agent.version", version);
+                }
+            }
+        }
+        if let Some(client_ip) = client_ip {
+            semconv.record_client_address(&span, &tracing::field::display(client_ip));
+        } else if let Some(client_ip) = client_ip_display.as_deref() {
+            semconv.record_client_address(&span, &client_ip);
+        }
+        if let Some(family) = client_ip_family {
+            span.record("client.address.family", family);
+        }
+        if let Some(port) = client_port {
+            span.record("client.port", port);
+        }
+        if let (Some(connection), Some(peer_ip)) = (connection, peer_ip) {
+            match options.client_ip_privacy {
+                IpPrivacy::Raw => span.record("network.peer.address", tracing::field::display(peer_ip)),
+                IpPrivacy::Truncate => span.record("network.peer.address", tracing::field::display(IpPrivacy::truncate(peer_ip))),
+                IpPrivacy::Hash(hash) => span.record("network.peer.address", hash(&peer_ip)),
+            };
+            span.record("network.peer.port", connection.peer.port());
+            if let Some(local) = connection.local {
+                span.record("network.local.address", tracing::field::display(local.ip()));
+                span.record("network.local.port", local.port());
+            }
+        }
+        if let Some(tls) = tls {
+            span.record("tls.protocol.version", tls.protocol_version);
+            if let Some(cipher) = tls.cipher {
+                span.record("tls.cipher", cipher);
+            }
+        }
+        if let Some(deadline) = info.deadline {
+            span.record("http.request.timeout_budget_ms", deadline.saturating_duration_since(info.started).as_millis() as u64);
+        }
+        if let Some(size) = headers::content_length(&parts.headers) {
+            span.record("http.request.body.size", size);
+        }
+        semconv.record_protocol(&span, info.protocol, parts.version);
+        if let ProtocolDetails::Grpc { service, method, .. } | ProtocolDetails::GrpcWeb { service, method, .. } = &info.details {
+            if !service.is_empty() {
+                span.record("rpc.service", service.as_ref());
+                span.record("rpc.method", method.as_ref());
+            }
+        }
+
+        info.peer_service = peer_service.map(Into::into);
+        info.client_ip = client_ip;
+        info.client_ip_display = client_ip_display;
+    }
+}
+
+///[HttpRequestLayer](struct.HttpRequestLayer.html) context interface
+pub trait LayerContext: Clone + Send + Sync {
+    ///Specifies list of headers you want to inspect via `http.headers` attribute
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName];
+
+    #[allow(unused)]
+    #[inline(always)]
+    ///Defines way to extract `IpAddr` from `parts`
+    ///
+    ///Defaults to always return `None`
+    fn extract_client_ip(&self, span: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
+        None
+    }
+
+    #[allow(unused)]
+    #[inline(always)]
+    ///Callback to be called on incoming request
+    ///
+    ///Defaults to be noop
+    fn on_request<T>(&self, span: &tracing::Span, request: &http::Request<T>) {
+    }
+
+    #[allow(unused)]
+    #[inline(always)]
+    ///Callback to be called when successful response is returned
+    ///
+    ///Defaults to be noop
+    fn on_response_ok<T>(&self, span: &tracing::Span, response: &mut http::Response<T>) {
+    }
+
+    #[allow(unused)]
+    #[inline(always)]
+    ///Callback to be called when error is returned instead of response
+    ///
+    ///Defaults to be noop
+    fn on_response_error(&self, span: &tracing::Span, error: &impl std::error::Error) {
+    }
+
+    #[inline(always)]
+    ///Generates request's id, when it is not inherited from request
+    ///
+    ///`format` is specified via layer [config](struct.HttpRequestLayer.html#method.with_request_id_generator)
+    ///
+    ///Defaults to [RequestId::generate]
+    fn generate_request_id(&self, format: RequestIdFormat) -> RequestId {
+        RequestId::generate(format)
+    }
+
+    #[allow(unused)]
+    #[inline(always)]
+    ///Decides whether request should be given span, before span is created
+    ///
+    ///When `false` is returned, span is not created and no fields are recorded, while request's id is still handled as usual.
+    ///Request is not inspected beyond that: [RequestInfo] carries only request's id, correlation id, trace context and protocol details, without client's address or peer service.
+    ///Decision is available via [RequestInfo::sampled]
+    ///
+    ///Defaults to always return `true`
+    fn should_sample<T>(&self, request: &http::Request<T>) -> bool {
+        true
+    }
+}
+
+#[derive(Copy, Clone)]
+///Default context that does nothing by default
+pub struct Noop;
+impl LayerContext for Noop {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+}
+
+impl<I: LayerContext> LayerContext for Box<I> {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = I::INSPECT_HEADERS;
+
+    #[inline(always)]
+    fn on_request<T>(&self, span: &tracing::Span, request: &http::Request<T>) {
+        I::on_request(self, span, request)
+    }
+
+    #[inline(always)]
+    fn on_response_ok<T>(&self, span: &tracing::Span, response: &mut http::Response<T>) {
+        I::on_response_ok(self, span, response)
+    }
+
+    #[inline(always)]
+    fn on_response_error(&self, span: &tracing::Span, error: &impl std::error::Error) {
+        I::on_response_error(self, span, error)
+    }
+
+    #[inline(always)]
+    fn extract_client_ip(&self, span: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
+        I::extract_client_ip(self, span, parts)
+    }
+
+    #[inline(always)]
+    fn generate_request_id(&self, format: RequestIdFormat) -> RequestId {
+        I::generate_request_id(self, format)
+    }
+
+    #[inline(always)]
+    fn should_sample<T>(&self, request: &http::Request<T>) -> bool {
+        I::should_sample(self, request)
+    }
+}
+
+impl<I: LayerContext> LayerContext for std::sync::Arc<I> {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = I::INSPECT_HEADERS;
+
+    #[inline(always)]
+    fn on_request<T>(&self, span: &tracing::Span, request: &http::Request<T>) {
+        I::on_request(self, span, request)
+    }
+
+    #[inline(always)]
+    fn on_response_ok<T>(&self, span: &tracing::Span, response: &mut http::Response<T>) {
+        I::on_response_ok(self, span, response)
+    }
+
+    #[inline(always)]
+    fn on_response_error(&self, span: &tracing::Span, error: &impl std::error::Error) {
+        I::on_response_error(self, span, error)
+    }
+
+    #[inline(always)]
+    fn extract_client_ip(&self, span: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
+        I::extract_client_ip(self, span, parts)
+    }
+
+    #[inline(always)]
+    fn generate_request_id(&self, format: RequestIdFormat) -> RequestId {
+        I::generate_request_id(self, format)
+    }
+
+    #[inline(always)]
+    fn should_sample<T>(&self, request: &http::Request<T>) -> bool {
+        I::should_sample(self, request)
+    }
+}
+
+#[derive(Clone)]
+///Context wrapper, replacing client IP extraction of inner context `C` with function `E`
+///
+///Created via [HttpRequestLayer::with_extract_client_ip], all other callbacks are delegated to inner context.
+pub struct WithClientIp<C, E> {
+    context: C,
+    extract: E,
+}
+
+impl<C: LayerContext, E: Fn(&http::request::Parts) -> Option<IpAddr> + Clone + Send + Sync> LayerContext for WithClientIp<C, E> {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = C::INSPECT_HEADERS;
+
+    #[inline(always)]
+    fn on_request<T>(&self, span: &tracing::Span, request: &http::Request<T>) {
+        self.context.on_request(span, request)
+    }
+
+    #[inline(always)]
+    fn on_response_ok<T>(&self, span: &tracing::Span, response: &mut http::Response<T>) {
+        self.context.on_response_ok(span, response)
+    }
+
+    #[inline(always)]
     fn on_response_error(&self, span: &tracing::Span, error: &impl std::error::Error) {
+        self.context.on_response_error(span, error)
+    }
+
+    #[inline(always)]
+    fn extract_client_ip(&self, _: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
+        (self.extract)(parts)
+    }
+
+    #[inline(always)]
+    fn generate_request_id(&self, format: RequestIdFormat) -> RequestId {
+        self.context.generate_request_id(format)
+    }
+
+    #[inline(always)]
+    fn should_sample<T>(&self, request: &http::Request<T>) -> bool {
+        self.context.should_sample(request)
+    }
+}
+
+#[derive(Clone, Default)]
+//Layer's options, which are not part of `LayerContext`
+struct Options {
+    record_forwarded_for_first: bool,
+    record_full_url: bool,
+    redact_query_params: &'static [&'static str],
+    record_fetch_metadata: bool,
+    max_uri_len: Option<usize>,
+    response_request_id_conflict: ResponseRequestIdConflict,
+    omit_response_request_id: bool,
+    request_id_policy: RequestIdPolicy,
+    request_id_format: RequestIdFormat,
+    request_id_validation: RequestIdValidation,
+    correlation_id_header: Option<http::HeaderName>,
+    peer_service_header: Option<http::HeaderName>,
+    retry_header: Option<http::HeaderName>,
+    skip_paths: &'static [&'static str],
+    request_id_headers: &'static [http::HeaderName],
+    request_fingerprint: Option<fingerprint::Components>,
+    inspect_headers: Option<Arc<[http::HeaderName]>>,
+    inspect_header_prefixes: Option<Arc<[&'static str]>>,
+    inspect_headers_deny_list: Option<Arc<[http::HeaderName]>>,
+    header_value_format: headers::ValueFormat,
+    inspect_headers_as_fields: bool,
+    inspect_response_headers: Option<Arc<[http::HeaderName]>>,
+    inspect_cookies: &'static [&'static str],
+    session_cookie: Option<&'static str>,
+    session_id_hash: Option<fn(&str) -> String>,
+    resource_tracker: Option<Arc<dyn resource::ResourceTracker>>,
+    connection_diagnostics: bool,
+    response_header_allowlist: Option<Arc<response_headers::HeaderAllowlist>>,
+    extract_stream_info: Option<ExtractStreamInfo>,
+    extract_connection_id: Option<ExtractConnectionId>,
+    extract_endpoint: Option<ExtractEndpoint>,
+    benign_error_filter: Option<BenignErrorFilter>,
+    error_categorizer: Option<error::ErrorCategorizer>,
+    startup_event: Option<&'static [(&'static str, &'static str)]>,
+    subscriber_panic_guard: bool,
+    max_concurrent_spans: Option<usize>,
+    clock: Option<Arc<dyn clock::Clock>>,
+    propagation_extract: &'static [propagation::PropagationFormat],
+    propagation_inject: &'static [propagation::PropagationFormat],
+    trace_response_header: bool,
+    record_trace_ids: bool,
+    raw_mapped_ipv4: bool,
+    record_ready_wait: bool,
+    client_ip_privacy: IpPrivacy,
+    default_timeout_budget: Option<std::time::Duration>,
+    on_complete: Option<OnComplete>,
+    #[cfg(feature = "log-bridge")]
+    log_bridge: bool,
+    access_log: bool,
+    experiment_header: Option<experiment::ExperimentHeader>,
+    semconv: Option<Arc<dyn semconv::SemanticConvention>>,
+}
+
+impl Options {
+    #[inline(always)]
+    fn now(&self) -> std::time::SystemTime {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => std::time::SystemTime::now(),
+        }
+    }
+
+    #[inline(always)]
+    fn instant(&self) -> std::time::Instant {
+        match &self.clock {
+            Some(clock) => clock.instant(),
+            None => std::time::Instant::now(),
+        }
+    }
+
+    #[inline]
+    //Returns first request id header present in `headers` alongside its name
+    fn request_id<'a>(&self, headers: &'a http::HeaderMap) -> Option<(&'static http::HeaderName, &'a http::HeaderValue)> {
+        let names = match self.request_id_headers {
+            [] => &DEFAULT_REQUEST_ID_HEADERS,
+            names => names,
+        };
+        names.iter().find_map(|name| headers.get(name).map(|value| (name, value)))
+    }
+
+    #[inline]
+    fn semconv(&self) -> &dyn semconv::SemanticConvention {
+        match &self.semconv {
+            Some(semconv) => semconv.as_ref(),
+            None => &semconv::OtelSemconv,
+        }
+    }
+}
+
+///Layer with default context, intended to be used in type annotations
+///
+///Remains stable regardless of generic parameters [HttpRequestLayer] might gain in future.
+pub type DefaultHttpRequestLayer = HttpRequestLayer;
+
+#[derive(Clone)]
+///Tower layer
+///
+///Span maker is [MakeSpan] by default, but any `Fn() -> tracing::Span + Clone` can be used via [with_make_span](#method.with_make_span)
+pub struct HttpRequestLayer<C: LayerContext = Noop, F = MakeSpan> {
+    make_span: F,
+    context: C,
+    options: Arc<Options>,
+    stats: Arc<stats::MiddlewareStats>,
+    connections: Arc<connection::ConnectionTracker>,
+    active_requests: Arc<core::sync::atomic::AtomicU64>,
+    concurrent_spans: Arc<core::sync::atomic::AtomicUsize>,
+}
+
+impl HttpRequestLayer {
+    #[inline]
+    ///Creates new layer with noop context.
+    pub fn new_simple(make_span: MakeSpan) -> Self {
+        Self::new(make_span, Noop)
+    }
+}
+
+impl<C: LayerContext> HttpRequestLayer<C> {
+    #[inline]
+    ///Creates new layer with provided span maker
+    pub fn new(make_span: MakeSpan, context: C) -> Self {
+        Self {
+            make_span,
+            context,
+            options: Arc::new(Options::default()),
+            stats: Arc::new(stats::MiddlewareStats::default()),
+            connections: Arc::new(connection::ConnectionTracker::new(connection::DEFAULT_CAPACITY)),
+            active_requests: Arc::new(core::sync::atomic::AtomicU64::new(0)),
+            concurrent_spans: Arc::new(core::sync::atomic::AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl<C: LayerContext, F: Fn() -> tracing::Span + Clone> HttpRequestLayer<C, F> {
+    #[inline]
+    ///Replaces context
+    pub fn with_context<C2: LayerContext>(self, context: C2) -> HttpRequestLayer<C2, F> {
+        HttpRequestLayer {
+            make_span: self.make_span,
+            context,
+            options: self.options,
+            stats: self.stats,
+            connections: self.connections,
+            active_requests: self.active_requests,
+            concurrent_spans: self.concurrent_spans,
+        }
+    }
+
+    #[inline]
+    ///Replaces client IP extraction of the context with `extract`, which can own its state (e.g. list of trusted proxies loaded from config)
+    ///
+    ///Accepts both plain functions and closures. Other callbacks of the context remain unchanged.
+    ///
+    ///```
+    ///use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+    ///use std::net::IpAddr;
+    ///
+    ///make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+    ///
+    ///let trusted: Vec<IpAddr> = vec!["10.0.0.1".parse().unwrap()];
+    ///let layer = HttpRequestLayer::new_simple(make_my_request_span).with_extract_client_ip(move |parts: &tower_http_tracing::http::request::Parts| {
+    ///    let ip = parts.headers.get(tower_http_tracing::X_FORWARDED_FOR)?.to_str().ok()?.trim().parse().ok()?;
+    ///    trusted.contains(&ip).then_some(ip)
+    ///});
+    ///```
+    pub fn with_extract_client_ip<E: Fn(&http::request::Parts) -> Option<IpAddr> + Clone + Send + Sync>(self, extract: E) -> HttpRequestLayer<WithClientIp<C, E>, F> {
+        let context = WithClientIp {
+            context: self.context.clone(),
+            extract,
+        };
+        self.with_context(context)
+    }
+
+    #[inline]
+    ///Replaces span maker, allowing to use closure capturing its state (e.g. service name resolved at runtime)
+    ///
+    ///```
+    ///use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+    ///
+    ///make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO, service.name = tracing::field::Empty));
+    ///
+    ///let service_name = std::sync::Arc::new(std::env::var("SERVICE_NAME").unwrap_or_default());
+    ///let layer = HttpRequestLayer::new_simple(make_my_request_span).with_make_span(move || {
+    ///    let span = make_my_request_span();
+    ///    span.record("service.name", service_name.as_str());
+    ///    span
+    ///});
+    ///```
+    pub fn with_make_span<F2: Fn() -> tracing::Span + Clone>(self, make_span: F2) -> HttpRequestLayer<C, F2> {
+        HttpRequestLayer {
+            make_span,
+            context: self.context,
+            options: self.options,
+            stats: self.stats,
+            connections: self.connections,
+            active_requests: self.active_requests,
+            concurrent_spans: self.concurrent_spans,
+        }
+    }
+
+    #[inline]
+    ///Starts building set of named profiles, derived from this layer
+    ///
+    ///Each profile starts from this layer's configuration, allowing to override options per profile (e.g. per listener).
+    ///All profiles share the same span maker, context, counters and connection tracking.
+    ///
+    ///```
+    ///use tower_http_tracing::{make_request_spanner, HttpRequestLayer, IpPrivacy};
+    ///
+    ///make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+    ///
+    ///let layers = HttpRequestLayer::new_simple(make_my_request_span).with_record_forwarded_for_first()
+    ///                                                               .variants()
+    ///                                                               .profile("internal", |layer| layer)
+    ///                                                               .profile("public", |layer| layer.with_client_ip_privacy(IpPrivacy::Truncate))
+    ///                                                               .build();
+    ///assert!(layers.contains_key("public"));
+    ///```
+    pub fn variants(self) -> LayerVariants<C, F> {
+        LayerVariants {
+            base: self,
+            profiles: std::collections::HashMap::new(),
+        }
+    }
+
+    #[inline]
+    ///Returns snapshot of middleware's internal counters
+    ///
+    ///Counters are shared between layer and all services created from it (including clones of the layer)
+    pub fn stats(&self) -> MiddlewareStatsSnapshot {
+        self.stats.snapshot()
+    }
+
+    #[inline]
+    ///Returns gauge of currently active requests, following semantics of `http.server.active_requests`
+    ///
+    ///Request is considered active from the moment it is passed to the middleware until its response future completes or is dropped.
+    ///If response's body is [tracked](body/struct.ResponseBodyLayer.html#method.with_track_response_body), request stays active until body ends or is dropped.
+    ///Gauge is shared between layer and all services created from it (including clones of the layer)
+    pub fn active_requests(&self) -> Arc<core::sync::atomic::AtomicU64> {
+        self.active_requests.clone()
+    }
+
+    #[inline]
+    ///Specifies allow-list of response headers, removing any other header from response
+    ///
+    ///Headers set by middleware itself (`X-Request-Id` and propagation headers like `traceparent`) are always allowed.
+    ///Headers are stripped after all callbacks of the context, hence headers added by them are subject to allow-list too.
+    ///Number of removed headers is recorded as `http.response.headers_stripped`,
+    ///and `WARN` event listing removed headers is emitted first time each header is seen (up to 64 distinct headers).
+    ///
+    ///Not enabled by default, as it changes responses.
+    pub fn with_response_header_allowlist(mut self, allowed: &'static [&'static http::HeaderName]) -> Self {
+        Arc::make_mut(&mut self.options).response_header_allowlist = Some(Arc::new(response_headers::HeaderAllowlist::new(allowed)));
+        self
+    }
+
+    #[inline]
+    ///Specifies headers to inspect via `http.headers` attribute, in addition to [LayerContext::INSPECT_HEADERS]
+    ///
+    ///Unlike context's constant, list is owned, so it can be built at runtime (e.g. from configuration file).
+    ///Headers already present in [LayerContext::INSPECT_HEADERS] are inspected only once
+    ///
+    ///Headers are matched via [HeaderMatch], which can be created from [HeaderName](http::HeaderName) to match exactly.
+    ///If any [HeaderMatch::Prefix] is specified, headers are recorded in order of request's headers, otherwise in order of the list.
+    ///
+    ///```rust
+    ///use tower_http_tracing::{http, HeaderMatch, HttpRequestLayer};
+    ///
+    ///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+    ///let layer = HttpRequestLayer::new_simple(make_my_request_span).with_inspect_headers([HeaderMatch::Exact(http::header::ACCEPT), HeaderMatch::Prefix("x-exp-")]);
+    ///```
+    pub fn with_inspect_headers(mut self, headers: impl IntoIterator<Item = impl Into<HeaderMatch>>) -> Self {
+        let mut names = Vec::new();
+        let mut prefixes = Vec::new();
+        for header in headers {
+            match header.into() {
+                HeaderMatch::Exact(name) => names.push(name),
+                HeaderMatch::Prefix(prefix) => prefixes.push(prefix),
+            }
+        }
+        let options = Arc::make_mut(&mut self.options);
+        options.inspect_headers = Some(names.into());
+        options.inspect_header_prefixes = Some(prefixes.into());
+        self
+    }
+
+    #[inline]
+    ///Enables inspection of all request's headers via `http.headers` attribute, except headers in `deny` list
+    ///
+    ///`Authorization`, `Cookie` and `Proxy-Authorization` are always denied.
+    ///Supersedes [LayerContext::INSPECT_HEADERS] and [with_inspect_headers](#method.with_inspect_headers), intended for debugging as it can expose sensitive data
+    pub fn with_inspect_headers_all(mut self, deny: &[http::HeaderName]) -> Self {
+        Arc::make_mut(&mut self.options).inspect_headers_deny_list = Some(deny.into());
+        self
+    }
+
+    #[inline]
+    ///Specifies function to redact values of headers inspected via `http.headers` attribute
+    ///
+    ///Function is called for every value of inspected header, so each value of multi-valued header is redacted independently.
+    ///
+    ///## Usage
+    ///
+    ///```rust
+    ///use tower_http_tracing::{http, HeaderRedaction, HttpRequestLayer};
+    ///
+    ///fn redact(name: &http::HeaderName, value: &http::HeaderValue) -> HeaderRedaction {
+    ///    if name != http::header::AUTHORIZATION {
+    ///        return HeaderRedaction::Keep;
+    ///    }
+    ///    //Keep auth scheme only
+    ///    match value.as_bytes().iter().position(|byt| *byt == b' ') {
+    ///        Some(idx) => HeaderRedaction::Prefix(idx + 1),
+    ///        None => HeaderRedaction::Replace,
+    ///    }
+    ///}
+    ///
+    ///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+    ///let layer = HttpRequestLayer::new_simple(make_my_request_span).with_inspect_headers([http::header::AUTHORIZATION])
+    ///                                                              .with_header_redactor(redact);
+    ///```
+    pub fn with_header_redactor(mut self, redactor: HeaderRedactor) -> Self {
+        Arc::make_mut(&mut self.options).header_value_format.redactor = Some(redactor);
+        self
+    }
+
+    #[inline]
+    ///Enables recording of each inspected header as its own `http.request.header.<name>` field instead of single `http.headers` attribute
+    ///
+    ///Fields must be declared via `headers = [...]` of [make_request_spanner](macro.make_request_spanner.html), headers without declared field are skipped.
+    ///Multiple values of the same header are joined by comma.
+    ///
+    ///## Usage
+    ///
+    ///```rust
+    ///use tower_http_tracing::{http, HttpRequestLayer};
+    ///
+    ///tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO, headers = [ACCEPT, "x-tenant"]));
+    ///let layer = HttpRequestLayer::new_simple(make_my_request_span).with_inspect_headers([http::header::ACCEPT, http::HeaderName::from_static("x-tenant")])
+    ///                                                              .with_inspect_headers_as_fields();
+    ///```
+    pub fn with_inspect_headers_as_fields(mut self) -> Self {
+        Arc::make_mut(&mut self.options).inspect_headers_as_fields = true;
+        self
+    }
+
+    #[inline]
+    ///Specifies response's headers to inspect via `http.response.headers` attribute
+    ///
+    ///Headers are recorded before response [allow-list](#method.with_response_header_allowlist) is applied, including headers inserted by middleware itself (e.g. `X-Request-Id`).
+    ///Values are subject to the same [redaction](#method.with_header_redactor) and [length limit](#method.with_max_header_value_len) as request's headers
+    pub fn with_inspect_response_headers(mut self, headers: &[http::HeaderName]) -> Self {
+        Arc::make_mut(&mut self.options).inspect_response_headers = Some(headers.into());
+        self
+    }
+
+    #[inline]
+    ///Specifies names of cookies to inspect via `http.request.cookies` attribute
+    ///
+    ///Only specified cookies are recorded, so that the rest of `Cookie` header (e.g. session tokens) never leaks into span.
+    ///If cookie is sent multiple times, only its first occurrence is recorded. Malformed pairs are skipped
+    pub fn with_inspect_cookies(mut self, cookies: &'static [&'static str]) -> Self {
+        Arc::make_mut(&mut self.options).inspect_cookies = cookies;
+        self
+    }
+
+    #[inline]
+    ///Specifies name of cookie holding session's id, which is recorded as `session.id`
+    ///
+    ///If cookie is sent multiple times, only its first occurrence is recorded.
+    ///
+    ///Not set by default
+    pub fn with_session_cookie(mut self, name: &'static str) -> Self {
+        Arc::make_mut(&mut self.options).session_cookie = Some(name);
+        self
+    }
+
+    #[inline]
+    ///Specifies function to transform session's id before recording it as `session.id`, typically keyed hash, so that raw session's id never leaks into span
+    ///
+    ///Has no effect unless [session cookie](#method.with_session_cookie) is specified
+    pub fn with_session_id_hash(mut self, hash: fn(&str) -> String) -> Self {
+        Arc::make_mut(&mut self.options).session_id_hash = Some(hash);
+        self
+    }
+
+    #[inline]
+    ///Limits length of each value of headers inspected via `http.headers` attribute
+    ///
+    ///Length is in bytes, but value is never cut in the middle of UTF-8 character. Truncated value is followed by `...`
+    ///
+    ///Unlimited by default
+    pub fn with_max_header_value_len(mut self, max_len: usize) -> Self {
+        Arc::make_mut(&mut self.options).header_value_format.max_len = Some(max_len);
+        self
+    }
+
+    #[inline]
+    ///Specifies how to record inspected headers with multiple values
+    ///
+    ///Values dropped by [redactor](#method.with_header_redactor) are not taken into account.
+    ///Defaults to [HeaderValuesStrategy::Join], which is not suitable for headers like `Set-Cookie`, whose values may contain comma themselves
+    pub fn with_header_values_strategy(mut self, strategy: HeaderValuesStrategy) -> Self {
+        Arc::make_mut(&mut self.options).header_value_format.strategy = strategy;
+        self
+    }
+
+    #[inline]
+    ///Enables rendering of `http.headers` and `http.response.headers` attributes as JSON object instead of debug map
+    ///
+    ///Header with single value is rendered as string, while header with multiple values as array of strings (e.g. `{"accept":"text/plain","x-tenant":["acme","globex"]}`).
+    ///Quotes, backslashes and control characters are escaped, so that value is always valid JSON
+    pub fn with_inspect_headers_json(mut self) -> Self {
+        Arc::make_mut(&mut self.options).header_value_format.json = true;
+        self
+    }
+
+    #[inline]
+    ///Enables recording of browser's fetch metadata headers (`Sec-Fetch-Site`, `Sec-Fetch-Mode`, `Sec-Fetch-Dest` and `Sec-Fetch-User`) as `http.request.header.sec-fetch-*`
+    ///
+    ///Each header is recorded as its own field, as long as it is present in request.
+    ///Values outside of the vocabulary defined by specification are recorded as `other` to bound cardinality.
+    pub fn with_record_fetch_metadata(mut self) -> Self {
+        Arc::make_mut(&mut self.options).record_fetch_metadata = true;
+        self
+    }
+
+    #[inline]
+    ///Specifies limit of URI's path and query length, defaults to [DEFAULT_MAX_URI_LEN]
+    ///
+    ///When URI exceeds limit, `url.path` is truncated to the first `max` bytes, `url.query` is skipped entirely,
+    ///`url.truncated` is set to `true` and original length is recorded as `url.length`
+    pub fn with_max_uri_len(mut self, max: usize) -> Self {
+        Arc::make_mut(&mut self.options).max_uri_len = Some(max);
+        self
+    }
+
+    #[inline]
+    ///Specifies whether to record absolute URL of request as `url.full`
+    ///
+    ///URL may contain sensitive information in its query, hence it is disabled by default
+    pub fn with_record_full_url(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).record_full_url = enabled;
+        self
+    }
+
+    #[inline]
+    ///Specifies names of query parameters (case-insensitive), whose values are replaced with `REDACTED` in `url.query` and `url.full`
+    ///
+    ///Order of parameters is preserved, e.g. `user=1&token=secret` is recorded as `user=1&token=REDACTED`
+    pub fn with_redact_query_params(mut self, params: &'static [&'static str]) -> Self {
+        Arc::make_mut(&mut self.options).redact_query_params = params;
+        self
+    }
+
+    #[inline]
+    ///Enables recording of the first `X-Forwarded-For` entry as `http.request.header.x-forwarded-for.first`
+    ///
+    ///Value is recorded as it is, without parsing it as IP address, and capped at 64 bytes.
+    ///Note that this header can be spoofed by client and has no relation to `client.address`, which is always determined by [LayerContext::extract_client_ip]
+    pub fn with_record_forwarded_for_first(mut self) -> Self {
+        Arc::make_mut(&mut self.options).record_forwarded_for_first = true;
+        self
+    }
+
+    #[inline]
+    ///Specifies function to extract stream id of the request, recorded as `network.stream.id`
+    ///
+    ///This is useful when server exposes protocol details (e.g. HTTP/2 stream id) via request's extensions.
+    ///Nothing is recorded if function returns `None`
+    pub fn with_extract_stream_info(mut self, extract: ExtractStreamInfo) -> Self {
+        Arc::make_mut(&mut self.options).extract_stream_info = Some(extract);
+        self
+    }
+
+    #[inline]
+    ///Enables recording of connection reuse hints from response headers:
+    ///
+    ///- `http.response.connection_close` - Set to `true` if response contains `Connection: close`
+    ///- `http.response.alt_svc.present` - Set to `true` if response contains `Alt-Svc`
+    pub fn with_connection_diagnostics(mut self) -> Self {
+        Arc::make_mut(&mut self.options).connection_diagnostics = true;
+        self
+    }
+
+    #[inline]
+    ///Specifies function to extract connection id of the request, recorded as `network.connection.id`
+    ///
+    ///Layer additionally tracks number of requests seen per connection, recorded as `network.connection.requests_seen` (i.e. `1` for fresh connection).
+    ///Tracking is shared between all services created from layer and limited to 1024 most recently used connections by default, which can be changed via [with_connection_tracking_capacity](#method.with_connection_tracking_capacity).
+    ///Nothing is recorded if function returns `None`
+    pub fn with_extract_connection_id(mut self, extract: ExtractConnectionId) -> Self {
+        Arc::make_mut(&mut self.options).extract_connection_id = Some(extract);
+        self
+    }
+
+    #[inline]
+    ///Specifies function to extract [endpoint](struct.Endpoint.html) that accepted request, recorded as `server.endpoint`
+    ///
+    ///Nothing is recorded if function returns `None`
+    pub fn with_extract_endpoint(mut self, extract: ExtractEndpoint) -> Self {
+        Arc::make_mut(&mut self.options).extract_endpoint = Some(extract);
+        self
+    }
+
+    #[inline]
+    ///Specifies max number of connections tracked to determine `network.connection.requests_seen`
+    ///
+    ///Least recently used connection is forgotten when capacity is exceeded. Resets already tracked connections.
+    pub fn with_connection_tracking_capacity(mut self, capacity: usize) -> Self {
+        self.connections = Arc::new(connection::ConnectionTracker::new(capacity));
+        self
+    }
+
+    #[inline]
+    ///Specifies filter to identify benign errors, such as client disconnecting mid-response
+    ///
+    ///When filter returns `true`, error is recorded with status code `499` (or `CANCELLED` for gRPC) and `error.type` set to `client_disconnect`, omitting `error.message`.
+    ///Request is completed with [ResponseStatus::ClientDisconnect], hence its finish event is emitted at `INFO` level.
+    ///
+    ///See [is_client_disconnect] for ready to use filter.
+    pub fn with_benign_error_filter(mut self, filter: BenignErrorFilter) -> Self {
+        Arc::make_mut(&mut self.options).benign_error_filter = Some(filter);
+        self
+    }
+
+    #[inline]
+    ///Specifies categorizer of errors, which determines `error.category`
+    ///
+    ///Not used for benign errors.
+    pub fn with_error_categorizer(mut self, categorizer: error::ErrorCategorizer) -> Self {
+        Arc::make_mut(&mut self.options).error_categorizer = Some(categorizer);
+        self
+    }
+
+    #[inline]
+    ///Enables startup event, emitted once per service (shared by its clones) before span of the first request is created
+    ///
+    ///Event is emitted with `INFO` level and [SERVICE](targets/constant.SERVICE.html) target, carrying `metadata` (e.g. `service.version`) as `service.metadata` field in format `key=value`, separated by space.
+    ///Additionally name and version of this crate are recorded as `library.name` and `library.version`.
+    ///
+    ///This allows to make service level information available in traces, without recording it on every span.
+    pub fn with_startup_event(mut self, metadata: &'static [(&'static str, &'static str)]) -> Self {
+        Arc::make_mut(&mut self.options).startup_event = Some(metadata);
+        self
+    }
+
+    #[inline]
+    ///Enables guard against panics of `tracing` subscriber while middleware creates span and records its fields
+    ///
+    ///When subscriber panics on creation of span or recording of request's fields, request proceeds without span.
+    ///When subscriber panics while recording response's fields, remaining fields are skipped.
+    ///Every caught panic is counted in [stats](#method.stats) as `subscriber_panics`.
+    ///
+    ///Not enabled by default, as it can mask bugs of subscriber.
+    pub fn with_subscriber_panic_guard(mut self) -> Self {
+        Arc::make_mut(&mut self.options).subscriber_panic_guard = true;
+        self
+    }
+
+    #[inline]
+    ///Specifies paths of requests, which bypass middleware entirely (e.g. health checks)
+    ///
+    ///Path must match exactly, query is not taken into account.
+    ///Requests with such path are passed to inner service as it is: no span is created, no fields are recorded, request's id is neither generated nor echoed in response.
+    ///As result, [RequestInfo] and [SpanHandle] are not inserted into request's extensions.
+    ///Every skipped request is counted in [stats](#method.stats) as `requests_skipped`
+    pub fn with_skip_paths(mut self, paths: &'static [&'static str]) -> Self {
+        Arc::make_mut(&mut self.options).skip_paths = paths;
+        self
+    }
+
+    #[inline]
+    ///Limits number of request spans that can exist concurrently, protecting tracing pipeline during traffic spikes
+    ///
+    ///Once limit is reached, new requests are shed: they get disabled span, while request id is still handled as usual.
+    ///Slot is released when response future completes or is dropped.
+    ///Every shed request is counted in [stats](#method.stats) as `spans_shed` and marked via [RequestSummary::shed]
+    ///
+    ///Limit is shared between layer and all services created from it (including clones of the layer)
+    pub fn with_max_concurrent_spans(mut self, max: usize) -> Self {
+        Arc::make_mut(&mut self.options).max_concurrent_spans = Some(max);
+        self
+    }
+
+    #[inline]
+    ///Specifies clock to use whenever current time is necessary
+    ///
+    ///Defaults to [SystemClock](clock/struct.SystemClock.html)
+    pub fn with_clock(mut self, clock: impl clock::Clock + 'static) -> Self {
+        Arc::make_mut(&mut self.options).clock = Some(Arc::new(clock));
+        self
+    }
+
+    #[inline]
+    ///Specifies formats to extract trace context from request, tried in order until first success
+    ///
+    ///Extracted context is available via [RequestInfo::trace_context]
+    pub fn with_propagation_extract(mut self, formats: &'static [propagation::PropagationFormat]) -> Self {
+        Arc::make_mut(&mut self.options).propagation_extract = formats;
+        self
     }
-}
 
-#[derive(Copy, Clone)]
-///Default context that does nothing by default
-pub struct Noop;
-impl LayerContext for Noop {
-    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
-}
+    #[inline]
+    ///Specifies formats to inject trace context, extracted from request, into response.
+    ///
+    ///All formats are injected.
+    pub fn with_propagation_inject(mut self, formats: &'static [propagation::PropagationFormat]) -> Self {
+        Arc::make_mut(&mut self.options).propagation_inject = formats;
+        self
+    }
 
-impl<I: LayerContext> LayerContext for Box<I> {
-    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = I::INSPECT_HEADERS;
+    #[inline]
+    ///Enables injection of request span's own context into response as W3C `traceresponse` header
+    ///
+    ///Unlike `traceparent`, it refers to the span created by this layer rather than inherited parent, which allows clients to locate trace of the request.
+    ///Context is only available via `opentelemetry` or `datadog` integration, otherwise nothing is injected.
+    pub fn with_trace_response_header(mut self) -> Self {
+        Arc::make_mut(&mut self.options).trace_response_header = true;
+        self
+    }
 
-    #[inline(always)]
-    fn on_request<T>(&self, span: &tracing::Span, request: &http::Request<T>) {
-        I::on_request(self, span, request)
+    #[inline]
+    ///Enables recording of span's own trace context as plain `trace_id` and `span_id` fields, allowing to correlate logs without access to tracing integration
+    ///
+    ///Context is only available via `opentelemetry` or `datadog` integration, otherwise request's id is recorded as `trace_id`.
+    pub fn with_record_trace_ids(mut self) -> Self {
+        Arc::make_mut(&mut self.options).record_trace_ids = true;
+        self
     }
 
-    #[inline(always)]
-    fn on_response_ok<T>(&self, span: &tracing::Span, response: &mut http::Response<T>) {
-        I::on_response_ok(self, span, response)
+    #[inline]
+    ///Specifies whether to normalize IPv4-mapped IPv6 client addresses (e.g. `::ffff:203.0.113.7`) into IPv4
+    ///
+    ///Normalization is applied before recording `client.address` and storing [RequestInfo::client_ip]
+    ///
+    ///Defaults to `true`
+    pub fn with_normalize_mapped_ipv4(mut self, normalize: bool) -> Self {
+        Arc::make_mut(&mut self.options).raw_mapped_ipv4 = !normalize;
+        self
     }
 
-    #[inline(always)]
-    fn on_response_error(&self, span: &tracing::Span, error: &impl std::error::Error) {
-        I::on_response_error(self, span, error)
+    #[inline]
+    ///Enables recording of time spent waiting for inner service readiness as `http.server.ready_wait_ms`
+    ///
+    ///Wait starts when `poll_ready` returns `Pending` for the first time and ends with the following `call`, which records it on its request's span.
+    ///
+    ///Note that this is approximate as the wait is tracked per service instance: when service is cloned each clone tracks its own wait, and with concurrent callers wait is attributed to whichever request is dispatched next.
+    pub fn with_record_ready_wait(mut self) -> Self {
+        Arc::make_mut(&mut self.options).record_ready_wait = true;
+        self
     }
 
-    #[inline(always)]
-    fn extract_client_ip(&self, span: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
-        I::extract_client_ip(self, span, parts)
+    #[inline]
+    ///Specifies privacy mode for client's IP address
+    ///
+    ///Transformation is applied before recording `client.address` and storing [RequestInfo::client_ip]
+    ///
+    ///Defaults to [IpPrivacy::Raw]
+    pub fn with_client_ip_privacy(mut self, privacy: IpPrivacy) -> Self {
+        Arc::make_mut(&mut self.options).client_ip_privacy = privacy;
+        self
     }
-}
 
-impl<I: LayerContext> LayerContext for std::sync::Arc<I> {
-    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = I::INSPECT_HEADERS;
+    #[inline]
+    ///Specifies semantic convention to use when recording span fields
+    ///
+    ///Defaults to [OtelSemconv](semconv/struct.OtelSemconv.html).
+    ///Note that span must declare fields of the selected [convention](macro.make_request_spanner.html#semantic-conventions)
+    pub fn with_semantic_convention(mut self, semconv: impl semconv::SemanticConvention + 'static) -> Self {
+        Arc::make_mut(&mut self.options).semconv = Some(Arc::new(semconv));
+        self
+    }
 
-    #[inline(always)]
-    fn on_request<T>(&self, span: &tracing::Span, request: &http::Request<T>) {
-        I::on_request(self, span, request)
+    #[inline]
+    ///Enables recording of experiment buckets from `header` with `name=value;name=value` format
+    ///
+    ///Only experiments listed in `names` are recorded as `experiment.<name>` fields, which must be declared by user when defining [span](macro.make_request_spanner.html#experiment-fields).
+    ///Malformed pairs are skipped, values are capped at 32 bytes and at most 8 experiments are recorded per request.
+    pub fn with_experiment_header(mut self, header: http::HeaderName, names: &'static [&'static str]) -> Self {
+        Arc::make_mut(&mut self.options).experiment_header = Some(experiment::ExperimentHeader::new(header, names));
+        self
     }
 
-    #[inline(always)]
-    fn on_response_ok<T>(&self, span: &tracing::Span, response: &mut http::Response<T>) {
-        I::on_response_ok(self, span, response)
+    #[inline]
+    ///Specifies default time budget for non-gRPC requests, used to determine [RequestInfo::deadline]
+    ///
+    ///gRPC requests always use `grpc-timeout` header, if present.
+    pub fn with_default_timeout_budget(mut self, budget: std::time::Duration) -> Self {
+        Arc::make_mut(&mut self.options).default_timeout_budget = Some(budget);
+        self
     }
 
-    #[inline(always)]
-    fn on_response_error(&self, span: &tracing::Span, error: &impl std::error::Error) {
-        I::on_response_error(self, span, error)
+    #[inline]
+    ///Specifies callback to be invoked exactly once, when request completes with response, error or is cancelled.
+    ///
+    ///If response's body is [tracked](body/struct.ResponseBodyLayer.html#method.with_track_response_body), callback is invoked once body ends or is dropped instead.
+    ///
+    ///Callback is provided with request's span and [RequestSummary]
+    pub fn with_on_complete(mut self, on_complete: OnComplete) -> Self {
+        Arc::make_mut(&mut self.options).on_complete = Some(on_complete);
+        self
     }
 
-    #[inline(always)]
-    fn extract_client_ip(&self, span: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
-        I::extract_client_ip(self, span, parts)
+    #[inline]
+    ///Enables access log event, emitted once request completes within its span
+    ///
+    ///Event uses target [ACCESS](targets/constant.ACCESS.html), which allows to disable access log while keeping spans (e.g. via `tower_http_tracing::access=off` directive).
+    ///Event's message is single line [summary](struct.RequestSummary.html) of the request, while level is mapped from final status: `ERROR` for server errors, `WARN` for client errors and cancellation, `INFO` otherwise.
+    ///
+    ///Disabled by default
+    pub fn with_access_log(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).access_log = enabled;
+        self
     }
-}
 
-#[derive(Clone)]
-///Tower layer
-pub struct HttpRequestLayer<C: LayerContext = Noop> {
-    make_span: MakeSpan,
-    context: C,
-}
+    #[cfg(feature = "log-bridge")]
+    #[inline]
+    ///Enables mirroring of completed requests into `log` crate, for services that do not use tracing subscriber
+    ///
+    ///Once request completes, record with single line [summary](struct.RequestSummary.html) is logged using target [ACCESS](targets/constant.ACCESS.html).
+    ///Level is mapped from final status: `Error` for server errors, `Warn` for client errors and cancellation, `Info` otherwise.
+    ///Summary is formatted only if level is enabled by logger.
+    pub fn with_log_bridge(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).log_bridge = enabled;
+        self
+    }
 
-impl HttpRequestLayer {
     #[inline]
-    ///Creates new layer with noop context.
-    pub fn new_simple(make_span: MakeSpan) -> Self {
-        Self {
-            make_span,
-            context: Noop
-        }
+    ///Specifies [tracker](resource/index.html) of resources consumed by request, recorded as `process.request.allocated_bytes`
+    ///
+    ///Tracker is started when request is passed to the middleware and finished when response future completes or is dropped
+    pub fn with_resource_tracker(mut self, tracker: impl resource::ResourceTracker + 'static) -> Self {
+        Arc::make_mut(&mut self.options).resource_tracker = Some(Arc::new(tracker));
+        self
+    }
+
+    #[inline]
+    ///Enables recording of request's [fingerprint](fingerprint/index.html) as `http.request.fingerprint`, using all components
+    pub fn with_request_fingerprint(self) -> Self {
+        self.with_request_fingerprint_components(fingerprint::Components::ALL)
+    }
+
+    #[inline]
+    ///Enables recording of request's [fingerprint](fingerprint/index.html) as `http.request.fingerprint`, using specified `components`
+    pub fn with_request_fingerprint_components(mut self, components: fingerprint::Components) -> Self {
+        Arc::make_mut(&mut self.options).request_fingerprint = Some(components);
+        self
+    }
+
+    #[inline]
+    ///Specifies headers to inherit request's id from, tried in order, before falling back to [policy](#method.with_request_id_policy)
+    ///
+    ///Defaults to `X-Request-Id` only. Header that matched is available as [RequestInfo::request_id_source].
+    ///Regardless of matched header, request's id is returned to the client via `X-Request-Id`
+    ///
+    ///```
+    ///use tower_http_tracing::{make_request_spanner, HttpRequestLayer, REQUEST_ID};
+    ///use tower_http_tracing::http::HeaderName;
+    ///
+    ///make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+    ///
+    ///static REQUEST_ID_HEADERS: [HeaderName; 3] = [REQUEST_ID, HeaderName::from_static("x-correlation-id"), HeaderName::from_static("x-amzn-trace-id")];
+    ///let layer = HttpRequestLayer::new_simple(make_my_request_span).with_request_id_headers(&REQUEST_ID_HEADERS);
+    ///```
+    pub fn with_request_id_headers(mut self, headers: &'static [http::HeaderName]) -> Self {
+        Arc::make_mut(&mut self.options).request_id_headers = headers;
+        self
+    }
+
+    #[inline]
+    ///Specifies how to determine request's id
+    ///
+    ///Defaults to [RequestIdPolicy::Generate]
+    pub fn with_request_id_policy(mut self, policy: RequestIdPolicy) -> Self {
+        Arc::make_mut(&mut self.options).request_id_policy = policy;
+        self
+    }
+
+    #[inline]
+    ///Specifies header of end-to-end correlation id, which is recorded as `http.correlation_id` in addition to per-hop request's id
+    ///
+    ///Correlation id is validated in the same way as request's [id](#method.with_request_id_validation), available via [RequestInfo::correlation_id] and echoed in response, unless it is already set by inner service or echoing is [disabled](#method.with_response_request_id).
+    ///
+    ///Not set by default
+    pub fn with_correlation_id_header(mut self, header: http::HeaderName) -> Self {
+        Arc::make_mut(&mut self.options).correlation_id_header = Some(header);
+        self
+    }
+
+    #[inline]
+    ///Specifies header carrying name of the calling service (e.g. `x-client-service`), which is recorded as `peer.service`
+    ///
+    ///Value is available via [RequestInfo::peer_service]. Header is ignored if its value is not valid UTF-8
+    ///
+    ///Not set by default
+    pub fn with_peer_service_header(mut self, header: http::HeaderName) -> Self {
+        Arc::make_mut(&mut self.options).peer_service_header = Some(header);
+        self
+    }
+
+    #[inline]
+    ///Specifies header carrying number of retry attempt (e.g. `x-retry-attempt`), which is recorded as `http.request.resend_count`
+    ///
+    ///Additionally enables recording of `Idempotency-Key` presence as `http.request.idempotency_key.present`.
+    ///Retry header is ignored if its value is not valid `u32`
+    ///
+    ///Not set by default
+    pub fn with_retry_header(mut self, header: http::HeaderName) -> Self {
+        Arc::make_mut(&mut self.options).retry_header = Some(header);
+        self
+    }
+
+    #[inline]
+    ///Specifies how to validate request's id supplied by client
+    ///
+    ///Defaults to [RequestIdValidation::Verbatim]
+    pub fn with_request_id_validation(mut self, validation: RequestIdValidation) -> Self {
+        Arc::make_mut(&mut self.options).request_id_validation = validation;
+        self
+    }
+
+    #[inline]
+    ///Specifies format of request's id generated by middleware
+    ///
+    ///Defaults to [RequestIdFormat::UuidV4Hyphenated]
+    pub fn with_request_id_generator(mut self, format: RequestIdFormat) -> Self {
+        Arc::make_mut(&mut self.options).request_id_format = format;
+        self
+    }
+
+    #[inline]
+    ///Specifies whether to set `X-Request-Id` in response
+    ///
+    ///When disabled, request's id is still recorded in span and available via [RequestInfo], while response is left as returned by inner service.
+    ///
+    ///Defaults to `true`
+    pub fn with_response_request_id(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).omit_response_request_id = !enabled;
+        self
+    }
+
+    #[inline]
+    ///Specifies how to handle `X-Request-Id` already set by inner service in response
+    ///
+    ///Defaults to [ResponseRequestIdConflict::Overwrite]
+    pub fn with_response_request_id_conflict(mut self, policy: ResponseRequestIdConflict) -> Self {
+        Arc::make_mut(&mut self.options).response_request_id_conflict = policy;
+        self
     }
 }
 
-impl<C: LayerContext> HttpRequestLayer<C> {
+///Builder of named [HttpRequestLayer] profiles, created via [HttpRequestLayer::variants]
+pub struct LayerVariants<C: LayerContext, F = MakeSpan> {
+    base: HttpRequestLayer<C, F>,
+    profiles: std::collections::HashMap<&'static str, HttpRequestLayer<C, F>>,
+}
+
+impl<C: LayerContext, F: Fn() -> tracing::Span + Clone> LayerVariants<C, F> {
     #[inline]
-    ///Creates new layer with provided span maker
-    pub fn new(make_span: MakeSpan, context: C) -> Self {
-        Self {
-            make_span,
-            context,
-        }
+    ///Adds profile `name`, configured by applying `configure` to the base layer
+    ///
+    ///Replaces profile with the same name, if any.
+    pub fn profile(mut self, name: &'static str, configure: impl FnOnce(HttpRequestLayer<C, F>) -> HttpRequestLayer<C, F>) -> Self {
+        let layer = configure(self.base.clone());
+        self.profiles.insert(name, layer);
+        self
     }
 
     #[inline]
-    ///Replaces context
-    pub fn with_context<C2: LayerContext>(self, context: C2) -> HttpRequestLayer<C2> {
-        HttpRequestLayer {
-            make_span: self.make_span,
-            context
+    ///Returns configured profiles
+    pub fn build(self) -> std::collections::HashMap<&'static str, HttpRequestLayer<C, F>> {
+        self.profiles
+    }
+}
+
+impl<C: LayerContext, F> HttpRequestLayer<C, F> {
+    //Records request's information into newly created `span`, populating rest of `info`
+    fn record_request(&self, span: &tracing::Span, parts: &http::request::Parts, info: &mut RequestInfo, ready_wait_since: Option<std::time::Instant>) {
+        RequestSpan::record(&self.context, &self.options, span, parts, info);
+        if let Some(since) = ready_wait_since {
+            let wait = self.options.instant().saturating_duration_since(since);
+            span.record("http.server.ready_wait_ms", wait.as_millis() as u64);
+        }
+        if let Some(stream_id) = self.options.extract_stream_info.and_then(|extract| extract(parts)) {
+            span.record("network.stream.id", stream_id);
+        }
+        if let Some(connection_id) = self.options.extract_connection_id.and_then(|extract| extract(parts)) {
+            span.record("network.connection.id", connection_id);
+            span.record("network.connection.requests_seen", self.connections.track(connection_id));
+        }
+        if let Some(components) = self.options.request_fingerprint {
+            span.record("http.request.fingerprint", tracing::field::display(fingerprint::compute(components, parts)));
+        }
+        let extra_header_list = self.options.inspect_headers.as_deref().unwrap_or_default();
+        let prefix_list = self.options.inspect_header_prefixes.as_deref().unwrap_or_default();
+        if let Some(deny_list) = self.options.inspect_headers_deny_list.as_deref() {
+            let inspect = headers::InspectAllHeaders {
+                deny_list,
+                headers: &parts.headers,
+                format: self.options.header_value_format,
+            };
+            if self.options.inspect_headers_as_fields {
+                inspect.record_fields(span);
+            } else {
+                span.record("http.headers", tracing::field::debug(inspect));
+            }
+        } else if !C::INSPECT_HEADERS.is_empty() || !extra_header_list.is_empty() || !prefix_list.is_empty() {
+            let inspect = headers::InspectHeaders {
+                header_list: C::INSPECT_HEADERS,
+                extra_header_list,
+                prefix_list,
+                headers: &parts.headers,
+                format: self.options.header_value_format,
+            };
+            if self.options.inspect_headers_as_fields {
+                inspect.record_fields(span);
+            } else {
+                span.record("http.headers", tracing::field::debug(inspect));
+            }
+        }
+        if !self.options.inspect_cookies.is_empty() {
+            let inspect = headers::InspectCookies {
+                cookie_list: self.options.inspect_cookies,
+                headers: &parts.headers,
+            };
+            if inspect.is_present() {
+                span.record("http.request.cookies", tracing::field::debug(inspect));
+            }
+        }
+        if let Some(name) = self.options.session_cookie {
+            let cookies = headers::InspectCookies {
+                cookie_list: &[],
+                headers: &parts.headers,
+            };
+            if let Some(session_id) = cookies.get(name) {
+                match self.options.session_id_hash {
+                    Some(hash) => span.record("session.id", hash(session_id)),
+                    None => span.record("session.id", session_id),
+                };
+            }
+        }
+        if self.options.record_forwarded_for_first {
+            if let Some(first) = parts.headers.get(X_FORWARDED_FOR).and_then(|value| headers::forwarded_for_first(value.as_bytes())) {
+                span.record("http.request.header.x-forwarded-for.first", first);
+            }
+        }
+        if parts.headers.get(http::header::EXPECT).is_some_and(|expect| expect.as_bytes().eq_ignore_ascii_case(b"100-continue")) {
+            span.record("http.request.expect_continue", true);
+        }
+        if self.options.record_fetch_metadata {
+            fetch_metadata::record(span, &parts.headers);
+        }
+        if let Some(experiment_header) = self.options.experiment_header.as_ref() {
+            experiment_header.record(span, &parts.headers);
         }
     }
 }
 
-impl<S, C: LayerContext> tower_layer::Layer<S> for HttpRequestLayer<C> {
-    type Service = HttpRequestService<S, C>;
+impl<S, C: LayerContext, F: Fn() -> tracing::Span + Clone> tower_layer::Layer<S> for HttpRequestLayer<C, F> {
+    type Service = HttpRequestService<S, C, F>;
     #[inline(always)]
     fn layer(&self, inner: S) -> Self::Service {
         HttpRequestService {
             layer: self.clone(),
             inner,
+            ready_wait_since: None,
+            startup: Arc::new(std::sync::Once::new()),
+        }
+    }
+}
+
+struct StartupMetadata(&'static [(&'static str, &'static str)]);
+
+impl fmt::Display for StartupMetadata {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, (key, value)) in self.0.iter().enumerate() {
+            if idx > 0 {
+                fmt.write_str(" ")?;
+            }
+            fmt.write_fmt(format_args!("{key}={value}"))?;
         }
+        Ok(())
     }
 }
 
 ///Tower service to annotate requests with span
-pub struct HttpRequestService<S, C: LayerContext> {
-    layer: HttpRequestLayer<C>,
-    inner: S
+pub struct HttpRequestService<S, C: LayerContext, F = MakeSpan> {
+    layer: HttpRequestLayer<C, F>,
+    inner: S,
+    ready_wait_since: Option<std::time::Instant>,
+    startup: Arc<std::sync::Once>,
+}
+
+impl<S: Clone, C: LayerContext, F: Clone> Clone for HttpRequestService<S, C, F> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            layer: self.layer.clone(),
+            inner: self.inner.clone(),
+            ready_wait_since: None,
+            startup: self.startup.clone(),
+        }
+    }
 }
 
-impl<C: LayerContext, ReqBody, ResBody, S: tower_service::Service<http::Request<ReqBody>, Response = http::Response<ResBody>>> tower_service::Service<http::Request<ReqBody>> for HttpRequestService<S, C> where S::Error: std::error::Error {
+impl<C: LayerContext, F: Fn() -> tracing::Span, ReqBody, ResBody, S: tower_service::Service<http::Request<ReqBody>, Response = http::Response<ResBody>>> tower_service::Service<http::Request<ReqBody>> for HttpRequestService<S, C, F> where S::Error: std::error::Error + 'static {
     type Response = S::Response;
     type Error = S::Error;
     type Future = ResponseFut<S::Future, C>;
 
     #[inline(always)]
     fn poll_ready(&mut self, ctx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
-        self.inner.poll_ready(ctx)
+        let result = self.inner.poll_ready(ctx);
+        if result.is_pending() && self.ready_wait_since.is_none() && self.layer.options.record_ready_wait {
+            self.ready_wait_since = Some(self.layer.options.instant());
+        }
+        result
     }
 
     fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        if let Some(metadata) = self.layer.options.startup_event {
+            self.startup.call_once(|| tracing::event!(
+                target: targets::SERVICE,
+                tracing::Level::INFO,
+                service.metadata = %StartupMetadata(metadata),
+                library.name = env!("CARGO_PKG_NAME"),
+                library.version = env!("CARGO_PKG_VERSION"),
+                "Service started"
+            ));
+        }
+        if self.layer.options.skip_paths.contains(&req.uri().path()) {
+            self.layer.stats.requests_skipped.inc();
+            self.ready_wait_since = None;
+            return ResponseFut {
+                inner: self.inner.call(req),
+                state: None,
+            };
+        }
+        let active_request = stats::ActiveRequestGuard::new(&self.layer.active_requests);
+        let resource = self.layer.options.resource_tracker.as_ref().map(|tracker| tracker.start());
+        let stats = &self.layer.stats;
+        let layer = &self.layer;
+        let sampled = layer.context.should_sample(&req);
+        if !sampled {
+            stats.spans_unsampled.inc();
+        }
+        let (span_permit, shed) = match layer.options.max_concurrent_spans {
+            _ if !sampled => (None, false),
+            Some(max) => match stats::SpanPermit::try_acquire(&layer.concurrent_spans, max) {
+                Some(permit) => (Some(permit), false),
+                None => {
+                    stats.spans_shed.inc();
+                    (None, true)
+                }
+            },
+            None => (None, false),
+        };
+        let span = if shed || !sampled {
+            tracing::Span::none()
+        } else {
+            subscriber_guard(&layer.options, stats, || {
+                let span = (layer.make_span)();
+                stats.spans_created.inc();
+                if span.is_disabled() {
+                    stats.spans_disabled.inc();
+                }
+                //Parent context must be assigned before span is entered for the first time
+                #[cfg(feature = "opentelemetry")]
+                opentelemetry::on_request(&span, &req);
+                #[cfg(feature = "datadog")]
+                datadog::on_request(&span, &req);
+                span
+            }).unwrap_or_else(tracing::Span::none)
+        };
+
         let (parts, body) = req.into_parts();
-        let RequestSpan { span, info } = RequestSpan::new(&self.layer.context, (self.layer.make_span)(), &parts);
+        let ready_wait_since = self.ready_wait_since.take();
+        //Request id must be determined regardless of span to be propagated, and only once to stay the same if recording fails
+        let mut info = RequestInfo::resolve(&layer.context, &layer.options, &parts);
+        let span = match sampled {
+            true => match subscriber_guard(&layer.options, stats, || layer.record_request(&span, &parts, &mut info, ready_wait_since)) {
+                Some(()) => span,
+                None => tracing::Span::none(),
+            },
+            false => span,
+        };
+        info.sampled = sampled;
+        match info.request_id_origin {
+            RequestIdOrigin::Generated => stats.ids_generated.inc(),
+            RequestIdOrigin::Inherited | RequestIdOrigin::DerivedFromTrace => stats.ids_inherited.inc(),
+        }
+        if info.incoming_request_id.as_ref().unwrap_or(&info.request_id).truncated() {
+            stats.truncations.inc();
+        }
+        if layer.options.record_trace_ids {
+            subscriber_guard(&layer.options, stats, || record_trace_ids(&span, &info.request_id));
+        }
 
         let mut req = http::Request::from_parts(parts, body);
         self.layer.context.on_request(&span, &req);
-        #[cfg(feature = "opentelemetry")]
-        opentelemetry::on_request(&span, &req);
-        #[cfg(feature = "datadog")]
-        datadog::on_request(&span, &req);
 
         let _entered = span.enter();
-        if !C::INSPECT_HEADERS.is_empty() {
-            span.record("http.headers", tracing::field::debug(headers::InspectHeaders {
-                header_list: C::INSPECT_HEADERS,
-                headers: req.headers()
-            }));
-        }
         let request_id = info.request_id.clone();
+        let correlation_id = info.correlation_id.clone();
         let protocol = info.protocol;
+        let trace_context = info.trace_context;
+        let started = info.started;
+        #[cfg(feature = "log-bridge")]
+        let log_bridge = self.layer.options.log_bridge;
+        #[cfg(not(feature = "log-bridge"))]
+        let log_bridge = false;
+        let access_log = self.layer.options.access_log;
+        let completion = (self.layer.options.on_complete.is_some() || log_bridge || access_log).then(|| Completion {
+            on_complete: self.layer.options.on_complete,
+            #[cfg(feature = "log-bridge")]
+            log_bridge,
+            access_log,
+            started,
+            summary: RequestSummary {
+                method: req.method().clone(),
+                uri: req.uri().clone(),
+                path: req.uri().path().to_owned(),
+                route: None,
+                protocol,
+                request_id: request_id.clone(),
+                client_ip: info.client_ip,
+                status: ResponseStatus::Cancelled,
+                duration: std::time::Duration::ZERO,
+                request_body_size: headers::content_length(req.headers()),
+                response_body_size: None,
+                shed,
+            }
+        });
         req.extensions_mut().insert(info);
+        req.extensions_mut().insert(SpanHandle {
+            span: span.clone(),
+        });
 
         let inner = self.inner.call(req);
 
         drop(_entered);
         ResponseFut {
             inner,
-            context: self.layer.context.clone(),
-            span,
-            protocol,
-            request_id
+            state: Some(ResponseState {
+                context: self.layer.context.clone(),
+                options: self.layer.options.clone(),
+                stats: self.layer.stats.clone(),
+                span,
+                protocol,
+                request_id,
+                correlation_id,
+                trace_context,
+                started,
+                completion,
+                active_request: Some(active_request),
+                span_permit,
+                resource,
+            }),
         }
     }
 }
@@ -545,40 +3414,166 @@ impl<C: LayerContext, ReqBody, ResBody, S: tower_service::Service<http::Request<
 ///Middleware's response future
 pub struct ResponseFut<F, C> {
     inner: F,
+    //Absent when request bypasses middleware
+    state: Option<ResponseState<C>>,
+}
+
+//Request's tracing state, kept until response is ready
+struct ResponseState<C> {
     context: C,
+    options: Arc<Options>,
+    stats: Arc<stats::MiddlewareStats>,
     span: tracing::Span,
     protocol: Protocol,
     request_id: RequestId,
+    correlation_id: Option<RequestId>,
+    trace_context: Option<propagation::TraceContext>,
+    started: std::time::Instant,
+    completion: Option<Completion>,
+    active_request: Option<stats::ActiveRequestGuard>,
+    span_permit: Option<stats::SpanPermit>,
+    resource: Option<resource::ResourceSnapshot>,
+}
+
+#[inline(always)]
+//Runs `record`, catching panic of subscriber if guard is enabled
+fn subscriber_guard<R>(options: &Options, stats: &stats::MiddlewareStats, record: impl FnOnce() -> R) -> Option<R> {
+    if !options.subscriber_panic_guard {
+        return Some(record());
+    }
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(record)) {
+        Ok(result) => Some(result),
+        Err(_) => {
+            stats.subscriber_panics.inc();
+            None
+        }
+    }
+}
+
+#[allow(unused)]
+#[inline(always)]
+//Returns context of the span itself, as assigned by tracing integration
+fn span_trace_context(span: &tracing::Span) -> Option<propagation::TraceContext> {
+    #[cfg(feature = "opentelemetry")]
+    if let Some(context) = opentelemetry::span_trace_context(span) {
+        return Some(context);
+    }
+    #[cfg(feature = "datadog")]
+    if let Some(context) = datadog::span_trace_context(span) {
+        return Some(context);
+    }
+    None
+}
+
+//Records span's own trace context, falling back to request's id in place of trace id
+fn record_trace_ids(span: &tracing::Span, request_id: &RequestId) {
+    match span_trace_context(span) {
+        Some(context) => {
+            span.record("trace_id", tracing::field::display(format_args!("{:032x}", context.trace_id)));
+            span.record("span_id", tracing::field::display(format_args!("{:016x}", context.span_id)));
+        },
+        None => semconv::record_request_id(span, "trace_id", request_id),
+    }
 }
 
-impl<C: LayerContext, ResBody, E: std::error::Error, F: Future<Output = Result<http::Response<ResBody>, E>>> Future for ResponseFut<F, C> {
+#[inline(always)]
+fn finish_resource(span: &tracing::Span, options: &Options, resource: &mut Option<resource::ResourceSnapshot>) {
+    if let (Some(tracker), Some(snapshot)) = (options.resource_tracker.as_ref(), resource.take()) {
+        span.record("process.request.allocated_bytes", tracker.finish(snapshot));
+    }
+}
+
+impl<F, C> Drop for ResponseFut<F, C> {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.as_mut() {
+            finish_resource(&state.span, &state.options, &mut state.resource);
+            if let Some(completion) = state.completion.take() {
+                let _entered = state.span.enter();
+                completion.complete(&state.span, ResponseStatus::Cancelled, state.options.instant());
+            }
+        }
+    }
+}
+
+impl<C: LayerContext, ResBody, E: std::error::Error + 'static, F: Future<Output = Result<http::Response<ResBody>, E>>> Future for ResponseFut<F, C> {
     type Output = F::Output;
 
     fn poll(self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
-        let (fut, context, span, protocol, request_id) = unsafe {
+        let (fut, state) = unsafe {
             let this = self.get_unchecked_mut();
-            (
-                Pin::new_unchecked(&mut this.inner),
-                &this.context,
-                &this.span,
-                this.protocol,
-                &this.request_id,
-            )
+            (Pin::new_unchecked(&mut this.inner), &mut this.state)
+        };
+        let state = match state {
+            Some(state) => state,
+            None => return Future::poll(fut, ctx),
         };
+        let ResponseState { ref context, ref options, ref stats, ref span, protocol, ref request_id, ref correlation_id, ref trace_context, started, ref mut completion, ref mut active_request, ref mut span_permit, ref mut resource } = *state;
         let _entered = span.enter();
         match Future::poll(fut, ctx) {
             task::Poll::Ready(Ok(mut resp)) => {
-                if let Ok(request_id) = http::HeaderValue::from_bytes(request_id.as_bytes()) {
-                    resp.headers_mut().insert(REQUEST_ID, request_id);
-                }
+                //gRPC status is resolved from trailers by body layer, unless it is sent in headers
                 let status = match protocol {
-                    Protocol::Http => resp.status().as_u16(),
-                    Protocol::Grpc => match resp.headers().get("grpc-status") {
-                        Some(status) => grpc::parse_grpc_status(status.as_bytes()),
-                        None => 2,
-                    }
+                    Protocol::Http => Some(resp.status().as_u16()),
+                    Protocol::Grpc | Protocol::GrpcWeb => grpc::find_grpc_status(protocol, resp.headers(), None),
                 };
-                span.record("http.response.status_code", status);
+                subscriber_guard(options, stats, || {
+                    if let Some(route) = resp.extensions().get::<MatchedRoute>() {
+                        span.record("http.route", route.0.as_str());
+                    }
+                    if let Some(user) = resp.extensions().get::<AuthenticatedUser>() {
+                        span.record("enduser.id", user.0.as_str());
+                    }
+                    if !options.omit_response_request_id {
+                        options.response_request_id_conflict.apply(span, stats, resp.headers_mut(), request_id);
+                        if let (Some(name), Some(correlation_id)) = (options.correlation_id_header.as_ref(), correlation_id) {
+                            if !resp.headers().contains_key(name) {
+                                match correlation_id.to_header_value() {
+                                    Some(correlation_id) => {
+                                        resp.headers_mut().insert(name.clone(), correlation_id);
+                                    },
+                                    None => stats.header_insert_failures.inc(),
+                                }
+                            }
+                        }
+                    }
+                    if let Some(status) = status {
+                        options.semconv().record_status_code(span, status);
+                    }
+                    if let Some(size) = headers::content_length(resp.headers()) {
+                        span.record("http.response.body.size", size);
+                    }
+                    if let Some(trace_context) = trace_context {
+                        propagation::PropagationFormat::inject_all(options.propagation_inject, resp.headers_mut(), trace_context);
+                    }
+                    if options.trace_response_header {
+                        if let Some(span_context) = span_trace_context(span) {
+                            propagation::inject_trace_response(resp.headers_mut(), &span_context);
+                        }
+                    }
+                    if let Protocol::Http = protocol {
+                        rate_limit::record(span, resp.status(), resp.headers(), || options.now());
+                    }
+                    if options.connection_diagnostics {
+                        if headers::contains_token(resp.headers().get_all(http::header::CONNECTION), "close") {
+                            span.record("http.response.connection_close", true);
+                        }
+                        if resp.headers().contains_key(http::header::ALT_SVC) {
+                            span.record("http.response.alt_svc.present", true);
+                        }
+                    }
+                    if let Some(extra_header_list) = options.inspect_response_headers.as_deref() {
+                        let inspect = headers::InspectHeaders {
+                            header_list: &[],
+                            extra_header_list,
+                            prefix_list: &[],
+                            headers: resp.headers(),
+                            format: options.header_value_format,
+                        };
+                        if inspect.is_present() {
+                            span.record("http.response.headers", tracing::field::debug(inspect));
+                        }
+                    }
+                });
 
                 context.on_response_ok(&span, &mut resp);
                 #[cfg(feature = "opentelemetry")]
@@ -586,16 +3581,72 @@ impl<C: LayerContext, ResBody, E: std::error::Error, F: Future<Output = Result<h
                 #[cfg(feature = "datadog")]
                 datadog::on_response_ok(&span, &mut resp);
 
+                if let Some(allowlist) = options.response_header_allowlist.as_ref() {
+                    let (stripped, first_seen) = allowlist.strip(resp.headers_mut(), options.correlation_id_header.as_ref());
+                    if stripped > 0 {
+                        subscriber_guard(options, stats, || {
+                            span.record("http.response.headers_stripped", stripped as u64);
+                            if !first_seen.is_empty() {
+                                tracing::warn!(target: targets::SPAN, headers = %response_headers::HeaderNames(&first_seen), "Stripped response headers that are not allow-listed");
+                            }
+                        });
+                    }
+                }
+
+                finish_resource(span, options, resource);
+                let now = options.instant();
+                span.record("http.server.request.duration", now.saturating_duration_since(started).as_secs_f64());
+
+                let completion = completion.take().map(|mut completion| {
+                    completion.summary.route = resp.extensions().get::<MatchedRoute>().map(|route| route.0.clone());
+                    completion.summary.response_body_size = headers::content_length(resp.headers());
+                    completion
+                });
+                match resp.extensions_mut().remove::<DeferredCompletion>() {
+                    Some(deferred) => deferred.defer(PendingCompletion {
+                        completion,
+                        span: span.clone(),
+                        protocol,
+                        status,
+                        options: options.clone(),
+                        active_request: active_request.take(),
+                    }),
+                    None => {
+                        //Without body layer trailers cannot be inspected
+                        let status = status.unwrap_or_else(|| {
+                            subscriber_guard(options, stats, || options.semconv().record_status_code(span, grpc::UNKNOWN));
+                            grpc::UNKNOWN
+                        });
+                        if let Some(completion) = completion {
+                            completion.complete(span, ResponseStatus::Code(status), now);
+                        }
+                    },
+                }
+
+                *active_request = None;
+                *span_permit = None;
                 task::Poll::Ready(Ok(resp))
             }
             task::Poll::Ready(Err(error)) => {
-                let status = match protocol {
-                    Protocol::Http => 500u16,
-                    Protocol::Grpc => 13,
+                let is_benign = options.benign_error_filter.is_some_and(|filter| filter(&error));
+                let status = match (protocol, is_benign) {
+                    (Protocol::Http, true) => 499u16,
+                    (Protocol::Grpc | Protocol::GrpcWeb, true) => grpc::CANCELLED,
+                    (Protocol::Http, false) => 500u16,
+                    (Protocol::Grpc | Protocol::GrpcWeb, false) => grpc::INTERNAL,
                 };
-                span.record("http.response.status_code", status);
-                span.record("error.type", core::any::type_name::<E>());
-                span.record("error.message", tracing::field::display(&error));
+                subscriber_guard(options, stats, || {
+                    options.semconv().record_status_code(span, status);
+                    if is_benign {
+                        span.record("error.type", "client_disconnect");
+                    } else {
+                        span.record("error.type", core::any::type_name::<E>());
+                        if let Some(category) = options.error_categorizer.as_ref().and_then(|categorizer| categorizer.categorize(&error)) {
+                            span.record("error.category", category);
+                        }
+                        span.record("error.message", tracing::field::display(&error));
+                    }
+                });
 
                 context.on_response_error(&span, &error);
                 #[cfg(feature = "opentelemetry")]
@@ -603,6 +3654,20 @@ impl<C: LayerContext, ResBody, E: std::error::Error, F: Future<Output = Result<h
                 #[cfg(feature = "datadog")]
                 datadog::on_response_error(&span, &error);
 
+                finish_resource(span, options, resource);
+                let now = options.instant();
+                span.record("http.server.request.duration", now.saturating_duration_since(started).as_secs_f64());
+
+                if let Some(completion) = completion.take() {
+                    let status = match is_benign {
+                        true => ResponseStatus::ClientDisconnect(status),
+                        false => ResponseStatus::Error(status),
+                    };
+                    completion.complete(span, status, now);
+                }
+
+                *active_request = None;
+                *span_permit = None;
                 task::Poll::Ready(Err(error))
             },
             task::Poll::Pending => task::Poll::Pending
@@ -0,0 +1,16 @@
+//Mirrors access log of completed requests into `log` crate
+
+use crate::{targets, RequestSummary};
+
+#[inline]
+pub fn log(summary: &RequestSummary) {
+    let level = match summary.level() {
+        tracing::Level::ERROR => log::Level::Error,
+        tracing::Level::WARN => log::Level::Warn,
+        _ => log::Level::Info,
+    };
+    //Summary is only formatted if record is going to be logged
+    if log::log_enabled!(target: targets::ACCESS, level) {
+        log::log!(target: targets::ACCESS, level, "{summary}");
+    }
+}
@@ -59,7 +59,7 @@ pub fn on_request<T>(span: &tracing::Span, request: &http::Request<T>) {
     let context = propagator.extract(&HeaderMapExtractor(request.headers()));
 
     if let Err(error) = span.set_parent(context) {
-        tracing::warn!("Unable to propagate parent context: {error}");
+        tracing::warn!(target: crate::targets::ERROR, "Unable to propagate parent context: {error}");
     }
 }
 
@@ -75,6 +75,23 @@ pub fn on_response_ok<T>(span: &tracing::Span, response: &mut http::Response<T>)
     propagator.inject_context(&context, &mut HeaderMapInjector(response.headers_mut()));
 }
 
+///Returns context of the `span` itself, if it is valid
+pub fn span_trace_context(span: &tracing::Span) -> Option<crate::propagation::TraceContext> {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use opentelemetry::trace::TraceContextExt;
+
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    if !span_context.is_valid() {
+        return None;
+    }
+    Some(crate::propagation::TraceContext {
+        trace_id: u128::from_be_bytes(span_context.trace_id().to_bytes()),
+        span_id: u64::from_be_bytes(span_context.span_id().to_bytes()),
+        sampled: span_context.is_sampled(),
+    })
+}
+
 #[inline(always)]
 ///Propagates error into `span` context
 pub fn on_response_error(span: &tracing::Span, error: &impl std::error::Error) {
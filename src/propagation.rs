@@ -0,0 +1,248 @@
+//! Trace context propagation
+//!
+//! Provides format agnostic representation of trace context, allowing to extract it in one format and inject in another.
+
+use core::fmt;
+
+///W3C `traceparent` header name
+pub const TRACEPARENT: http::HeaderName = http::HeaderName::from_static("traceparent");
+///W3C `traceresponse` header name
+///
+///<https://w3c.github.io/trace-context/#traceresponse-header>
+pub const TRACERESPONSE: http::HeaderName = http::HeaderName::from_static("traceresponse");
+///Datadog trace id header name
+pub const DATADOG_TRACE_ID: http::HeaderName = http::HeaderName::from_static("x-datadog-trace-id");
+///Datadog parent id header name
+pub const DATADOG_PARENT_ID: http::HeaderName = http::HeaderName::from_static("x-datadog-parent-id");
+///Datadog sampling priority header name
+pub const DATADOG_SAMPLING_PRIORITY: http::HeaderName = http::HeaderName::from_static("x-datadog-sampling-priority");
+///Datadog tags header name
+pub const DATADOG_TAGS: http::HeaderName = http::HeaderName::from_static("x-datadog-tags");
+
+const W3C_VERSION: u8 = 0;
+const W3C_SAMPLED_FLAG: u8 = 0x01;
+const DATADOG_TRACE_ID_HIGH_TAG: &str = "_dd.p.tid=";
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Trace context, shared between all propagation formats
+pub struct TraceContext {
+    ///Trace id
+    pub trace_id: u128,
+    ///Id of the span within trace
+    pub span_id: u64,
+    ///Whether trace is sampled
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    #[inline(always)]
+    ///Returns whether context is valid (i.e. both ids are non-zero)
+    pub const fn is_valid(&self) -> bool {
+        self.trace_id != 0 && self.span_id != 0
+    }
+}
+
+///Formatter of W3C `traceparent` value, shared by `traceparent` and `traceresponse` headers
+pub struct W3cTraceparent<'a>(pub &'a TraceContext);
+
+impl fmt::Display for W3cTraceparent<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let TraceContext { trace_id, span_id, sampled } = self.0;
+        let flags = if *sampled { W3C_SAMPLED_FLAG } else { 0 };
+        fmt.write_fmt(format_args!("{W3C_VERSION:02x}-{trace_id:032x}-{span_id:016x}-{flags:02x}"))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Propagation format
+pub enum PropagationFormat {
+    ///W3C `traceparent` header
+    ///
+    ///<https://www.w3.org/TR/trace-context/#traceparent-header>
+    W3c,
+    ///Datadog's native `x-datadog-*` headers
+    ///
+    ///<https://docs.datadoghq.com/tracing/trace_collection/trace_context_propagation/?tab=java#datadog-format>
+    Datadog,
+}
+
+#[inline(always)]
+fn parse_hex<const LEN: usize>(value: &str) -> Option<u128> {
+    if value.len() != LEN || !value.bytes().all(|byt| byt.is_ascii_hexdigit()) {
+        return None;
+    }
+    u128::from_str_radix(value, 16).ok()
+}
+
+//Parses value in format of `traceparent`, without validating ids
+pub(crate) fn parse_w3c(value: &str) -> Option<TraceContext> {
+    let mut parts = value.trim().split('-');
+    let version = parse_hex::<2>(parts.next()?)?;
+    let trace_id = parse_hex::<32>(parts.next()?)?;
+    let span_id = parse_hex::<16>(parts.next()?)?;
+    let flags = parse_hex::<2>(parts.next()?)?;
+    //Version 00 cannot have extra fields
+    if version != W3C_VERSION as u128 || parts.next().is_some() {
+        return None;
+    }
+
+    Some(TraceContext {
+        trace_id,
+        span_id: span_id as u64,
+        sampled: flags as u8 & W3C_SAMPLED_FLAG == W3C_SAMPLED_FLAG,
+    })
+}
+
+///Carrier of propagated context, allowing to use propagation outside of HTTP (e.g. with headers of message queue)
+///
+///Keys are always lowercase names of headers (e.g. [TRACEPARENT])
+pub trait Carrier {
+    ///Returns value of `key`, if present and valid string
+    fn get(&self, key: &str) -> Option<&str>;
+    ///Sets `value` of `key`, replacing existing one
+    fn set(&mut self, key: &str, value: String);
+}
+
+impl Carrier for http::HeaderMap {
+    #[inline]
+    fn get(&self, key: &str) -> Option<&str> {
+        http::HeaderMap::get(self, key).and_then(|value| value.to_str().ok())
+    }
+
+    #[inline]
+    fn set(&mut self, key: &str, value: String) {
+        //Values produced by propagation are always valid
+        if let (Ok(key), Ok(value)) = (http::HeaderName::from_bytes(key.as_bytes()), http::HeaderValue::try_from(value)) {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<S: core::hash::BuildHasher> Carrier for std::collections::HashMap<String, String, S> {
+    #[inline]
+    fn get(&self, key: &str) -> Option<&str> {
+        std::collections::HashMap::get(self, key).map(String::as_str)
+    }
+
+    #[inline]
+    fn set(&mut self, key: &str, value: String) {
+        self.insert(key.to_owned(), value);
+    }
+}
+
+///Injects `context` into `carrier` as `traceresponse`, doing nothing if context is not valid
+pub fn inject_trace_response_into<C: Carrier + ?Sized>(carrier: &mut C, context: &TraceContext) {
+    if context.is_valid() {
+        carrier.set(TRACERESPONSE.as_str(), W3cTraceparent(context).to_string());
+    }
+}
+
+///Extracts context from `traceresponse` within `carrier`, returning `None` if it is absent or invalid
+pub fn extract_trace_response_from<C: Carrier + ?Sized>(carrier: &C) -> Option<TraceContext> {
+    parse_w3c(carrier.get(TRACERESPONSE.as_str())?).filter(TraceContext::is_valid)
+}
+
+#[inline]
+///Injects `context` into `headers` as `traceresponse`, doing nothing if context is not valid
+pub fn inject_trace_response(headers: &mut http::HeaderMap, context: &TraceContext) {
+    inject_trace_response_into(headers, context)
+}
+
+#[inline]
+///Extracts context from `traceresponse` within `headers`, returning `None` if it is absent or invalid
+pub fn extract_trace_response(headers: &http::HeaderMap) -> Option<TraceContext> {
+    extract_trace_response_from(headers)
+}
+
+///Records context from `traceresponse` of downstream response into `span`, declared via [make_child_spanner](../macro.make_child_spanner.html)
+///
+///Context is recorded as `traceresponse.trace_id` and `traceresponse.span_id`, allowing to link downstream trace that served the call
+pub fn record_trace_response(span: &tracing::Span, headers: &http::HeaderMap) {
+    if let Some(TraceContext { trace_id, span_id, .. }) = extract_trace_response(headers) {
+        span.record("traceresponse.trace_id", tracing::field::display(format_args!("{trace_id:032x}")));
+        span.record("traceresponse.span_id", tracing::field::display(format_args!("{span_id:016x}")));
+    }
+}
+
+impl PropagationFormat {
+    ///Extracts context from `carrier`, returning `None` if it is absent or invalid
+    pub fn extract_from<C: Carrier + ?Sized>(self, carrier: &C) -> Option<TraceContext> {
+        let context = match self {
+            Self::W3c => parse_w3c(carrier.get(TRACEPARENT.as_str())?)?,
+            Self::Datadog => {
+                let trace_id_low: u64 = carrier.get(DATADOG_TRACE_ID.as_str())?.trim().parse().ok()?;
+                let span_id: u64 = carrier.get(DATADOG_PARENT_ID.as_str())?.trim().parse().ok()?;
+                let sampled = match carrier.get(DATADOG_SAMPLING_PRIORITY.as_str()) {
+                    Some(priority) => priority.trim().parse::<i8>().map(|priority| priority > 0).unwrap_or(false),
+                    //Absence of decision means it is up to us
+                    None => true,
+                };
+                let trace_id_high = carrier.get(DATADOG_TAGS.as_str())
+                                           .and_then(|tags| tags.split(',').find_map(|tag| tag.trim().strip_prefix(DATADOG_TRACE_ID_HIGH_TAG)))
+                                           .and_then(parse_hex::<16>)
+                                           .unwrap_or(0);
+
+                TraceContext {
+                    trace_id: trace_id_high << 64 | trace_id_low as u128,
+                    span_id,
+                    sampled,
+                }
+            },
+        };
+
+        if context.is_valid() {
+            Some(context)
+        } else {
+            None
+        }
+    }
+
+    ///Injects `context` into `carrier`, doing nothing if context is not valid
+    pub fn inject_into<C: Carrier + ?Sized>(self, carrier: &mut C, context: &TraceContext) {
+        if !context.is_valid() {
+            return;
+        }
+
+        let TraceContext { trace_id, span_id, sampled } = context;
+        match self {
+            Self::W3c => {
+                carrier.set(TRACEPARENT.as_str(), W3cTraceparent(context).to_string());
+            },
+            Self::Datadog => {
+                let trace_id_low = *trace_id as u64;
+                let trace_id_high = (*trace_id >> 64) as u64;
+                carrier.set(DATADOG_TRACE_ID.as_str(), trace_id_low.to_string());
+                carrier.set(DATADOG_PARENT_ID.as_str(), span_id.to_string());
+                carrier.set(DATADOG_SAMPLING_PRIORITY.as_str(), (if *sampled { "1" } else { "0" }).to_owned());
+                if trace_id_high != 0 {
+                    carrier.set(DATADOG_TAGS.as_str(), format!("{DATADOG_TRACE_ID_HIGH_TAG}{trace_id_high:016x}"));
+                }
+            },
+        }
+    }
+
+    #[inline]
+    ///Extracts context from `headers`, returning `None` if it is absent or invalid
+    pub fn extract(self, headers: &http::HeaderMap) -> Option<TraceContext> {
+        self.extract_from(headers)
+    }
+
+    #[inline]
+    ///Injects `context` into `headers`, doing nothing if context is not valid
+    pub fn inject(self, headers: &mut http::HeaderMap, context: &TraceContext) {
+        self.inject_into(headers, context)
+    }
+
+    ///Extracts context from `headers` using first format in `formats` that succeeds
+    pub fn extract_first(formats: &[Self], headers: &http::HeaderMap) -> Option<TraceContext> {
+        formats.iter().find_map(|format| format.extract(headers))
+    }
+
+    ///Injects `context` into `headers` using all `formats`
+    pub fn inject_all(formats: &[Self], headers: &mut http::HeaderMap, context: &TraceContext) {
+        for format in formats {
+            format.inject(headers, context);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::SystemTime;
+
+///`RateLimit-Limit` header name
+pub const RATELIMIT_LIMIT: http::HeaderName = http::HeaderName::from_static("ratelimit-limit");
+///`RateLimit-Remaining` header name
+pub const RATELIMIT_REMAINING: http::HeaderName = http::HeaderName::from_static("ratelimit-remaining");
+///`X-RateLimit-Limit` header name, legacy variant of `RateLimit-Limit`
+pub const X_RATELIMIT_LIMIT: http::HeaderName = http::HeaderName::from_static("x-ratelimit-limit");
+///`X-RateLimit-Remaining` header name, legacy variant of `RateLimit-Remaining`
+pub const X_RATELIMIT_REMAINING: http::HeaderName = http::HeaderName::from_static("x-ratelimit-remaining");
+
+//Takes leading integer of the value, ignoring parameters that follow
+fn parse_leading_int(value: &http::HeaderValue) -> Option<u64> {
+    let value = value.as_bytes();
+    let end = value.iter().position(|byt| matches!(byt, b',' | b';')).unwrap_or(value.len());
+    core::str::from_utf8(&value[..end]).ok()?.trim().parse().ok()
+}
+
+//Prefers standard header, falling back to its legacy `X-` variant
+fn get_either(headers: &http::HeaderMap, name: http::HeaderName, legacy: http::HeaderName) -> Option<&http::HeaderValue> {
+    headers.get(name).or_else(|| headers.get(legacy))
+}
+
+///Records rate limiting information, if `status` indicates request was throttled
+pub fn record(span: &tracing::Span, status: http::StatusCode, headers: &http::HeaderMap, now: impl FnOnce() -> SystemTime) {
+    let retry_after = headers.get(http::header::RETRY_AFTER);
+    match status {
+        http::StatusCode::TOO_MANY_REQUESTS => (),
+        http::StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => (),
+        _ => return,
+    }
+
+    if let Some(limit) = get_either(headers, RATELIMIT_LIMIT, X_RATELIMIT_LIMIT).and_then(parse_leading_int) {
+        span.record("http.response.rate_limit.limit", limit);
+    }
+    let remaining = get_either(headers, RATELIMIT_REMAINING, X_RATELIMIT_REMAINING).and_then(parse_leading_int);
+    if let Some(remaining) = remaining {
+        span.record("http.response.rate_limit.remaining", remaining);
+    }
+    let retry_after = retry_after.and_then(|value| crate::time::parse_retry_after(value, now())).map(|retry_after| retry_after.as_secs());
+    if let Some(retry_after) = retry_after {
+        span.record("http.response.rate_limit.retry_after_seconds", retry_after);
+        span.record("http.response.retry_after", retry_after);
+    }
+    if let Some(remaining) = remaining {
+        span.record("http.response.ratelimit_remaining", remaining);
+    }
+}
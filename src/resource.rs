@@ -0,0 +1,75 @@
+//! Resource tracking
+//!
+//! Allows to record amount of resource (e.g. allocated memory) consumed by request as `process.request.allocated_bytes`.
+//!
+//! Tracker is started when request is passed to the middleware and finished when response future completes or is dropped.
+
+use core::cell::Cell;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Opaque snapshot of tracker's state at the start of the request
+pub struct ResourceSnapshot(pub u64);
+
+///Resource tracker interface
+pub trait ResourceTracker: Send + Sync {
+    ///Takes snapshot at the start of the request
+    fn start(&self) -> ResourceSnapshot;
+    ///Returns amount of resource consumed since `snapshot`
+    fn finish(&self, snapshot: ResourceSnapshot) -> u64;
+}
+
+impl<T: ResourceTracker> ResourceTracker for std::sync::Arc<T> {
+    #[inline(always)]
+    fn start(&self) -> ResourceSnapshot {
+        T::start(self)
+    }
+
+    #[inline(always)]
+    fn finish(&self, snapshot: ResourceSnapshot) -> u64 {
+        T::finish(self, snapshot)
+    }
+}
+
+thread_local! {
+    static ALLOCATED: Cell<u64> = const { Cell::new(0) };
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+///Reference tracker backed by thread-local counter of allocated bytes
+///
+///Counter must be maintained by tracking allocator via [ThreadLocalTracker::add].
+///
+///## Caveats
+///
+///Counter is per thread, hence result is only accurate when request is processed on single thread from start to finish
+///(e.g. current thread runtime) and no other request is processed concurrently on the same thread.
+///With multi-threaded runtime task can migrate between threads, producing meaningless delta, which is saturated at zero.
+pub struct ThreadLocalTracker;
+
+impl ThreadLocalTracker {
+    #[inline(always)]
+    ///Adds `bytes` to current thread's counter
+    ///
+    ///Does not allocate, hence can be called from within `GlobalAlloc` implementation
+    pub fn add(bytes: u64) {
+        ALLOCATED.with(|allocated| allocated.set(allocated.get().saturating_add(bytes)));
+    }
+
+    #[inline(always)]
+    ///Returns current thread's counter
+    pub fn get() -> u64 {
+        ALLOCATED.with(Cell::get)
+    }
+}
+
+impl ResourceTracker for ThreadLocalTracker {
+    #[inline(always)]
+    fn start(&self) -> ResourceSnapshot {
+        ResourceSnapshot(Self::get())
+    }
+
+    #[inline(always)]
+    fn finish(&self, snapshot: ResourceSnapshot) -> u64 {
+        Self::get().saturating_sub(snapshot.0)
+    }
+}
@@ -0,0 +1,81 @@
+//Enforcement of response headers allow-list
+
+use core::fmt;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use crate::propagation;
+
+///Max number of distinct header names, for which warning is emitted
+pub const MAX_WARNED: usize = 64;
+
+//Headers set by the middleware and its integrations
+const ALWAYS_ALLOWED: [http::HeaderName; 8] = [
+    crate::REQUEST_ID,
+    propagation::TRACEPARENT,
+    http::HeaderName::from_static("tracestate"),
+    propagation::TRACERESPONSE,
+    propagation::DATADOG_TRACE_ID,
+    propagation::DATADOG_PARENT_ID,
+    propagation::DATADOG_SAMPLING_PRIORITY,
+    propagation::DATADOG_TAGS,
+];
+
+pub struct HeaderAllowlist {
+    allowed: &'static [&'static http::HeaderName],
+    //Names, for which warning is already emitted
+    warned: Mutex<HashSet<http::HeaderName>>,
+}
+
+impl HeaderAllowlist {
+    pub fn new(allowed: &'static [&'static http::HeaderName]) -> Self {
+        Self {
+            allowed,
+            warned: Mutex::new(HashSet::new()),
+        }
+    }
+
+    #[inline]
+    fn is_allowed(&self, name: &http::HeaderName, correlation_id: Option<&http::HeaderName>) -> bool {
+        ALWAYS_ALLOWED.contains(name) || correlation_id == Some(name) || self.allowed.contains(&name)
+    }
+
+    ///Removes headers that are not allowed, returning number of removed values and names that are seen for the first time
+    ///
+    ///Header of correlation id is always allowed, if configured
+    pub fn strip(&self, headers: &mut http::HeaderMap, correlation_id: Option<&http::HeaderName>) -> (usize, Vec<http::HeaderName>) {
+        let mut stripped = 0;
+        let mut first_seen = Vec::new();
+        if headers.keys().all(|name| self.is_allowed(name, correlation_id)) {
+            return (stripped, first_seen);
+        }
+
+        let names: Vec<_> = headers.keys().filter(|name| !self.is_allowed(name, correlation_id)).cloned().collect();
+        let mut warned = self.warned.lock().unwrap_or_else(|error| error.into_inner());
+        for name in names {
+            if let http::header::Entry::Occupied(entry) = headers.entry(&name) {
+                stripped += entry.remove_entry_mult().1.count();
+            }
+            if warned.len() < MAX_WARNED && !warned.contains(&name) {
+                warned.insert(name.clone());
+                first_seen.push(name);
+            }
+        }
+        (stripped, first_seen)
+    }
+}
+
+///Formats list of header names, separated by comma
+pub struct HeaderNames<'a>(pub &'a [http::HeaderName]);
+
+impl fmt::Display for HeaderNames<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, name) in self.0.iter().enumerate() {
+            if idx > 0 {
+                fmt.write_str(",")?;
+            }
+            fmt.write_str(name.as_str())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+//! Semantic conventions
+//!
+//! Semantic convention determines names (and sometimes format) of span fields recorded by middleware.
+//! Span must declare fields of the selected convention, see [make_request_spanner](../macro.make_request_spanner.html#semantic-conventions)
+
+use crate::{Protocol, RequestId};
+
+///Semantic convention interface
+pub trait SemanticConvention: Send + Sync {
+    ///Records request's method
+    fn record_method(&self, span: &tracing::Span, method: &http::Method);
+    ///Records request's path
+    fn record_path(&self, span: &tracing::Span, path: &str);
+    ///Records request's query
+    fn record_query(&self, span: &tracing::Span, query: &str);
+    ///Records request's scheme
+    fn record_scheme(&self, span: &tracing::Span, scheme: &str);
+    ///Records request's id
+    fn record_request_id(&self, span: &tracing::Span, request_id: &RequestId);
+    ///Records client's user agent
+    fn record_user_agent(&self, span: &tracing::Span, user_agent: &str);
+    ///Records client's address
+    fn record_client_address(&self, span: &tracing::Span, address: &dyn tracing::Value);
+    ///Records protocol and its version
+    fn record_protocol(&self, span: &tracing::Span, protocol: Protocol, version: http::Version);
+    ///Records response's status code, semantics of which depend on protocol
+    fn record_status_code(&self, span: &tracing::Span, status: u16);
+}
+
+#[inline(always)]
+pub(crate) fn record_request_id(span: &tracing::Span, field: &str, request_id: &RequestId) {
+    if let Some(request_id) = request_id.as_str() {
+        span.record(field, &request_id);
+    } else {
+        span.record(field, tracing::field::display(request_id.hex()));
+    }
+}
+
+#[inline(always)]
+//Returns whether method is known to semantic conventions, i.e. defined by RFC 9110 or RFC 5789
+fn is_known_method(method: &http::Method) -> bool {
+    matches!(*method, http::Method::GET | http::Method::HEAD | http::Method::POST | http::Method::PUT | http::Method::DELETE | http::Method::CONNECT | http::Method::OPTIONS | http::Method::TRACE | http::Method::PATCH)
+}
+
+#[inline(always)]
+//Returns HTTP version as string value, defined by semantic conventions
+fn version_str(version: http::Version) -> &'static str {
+    match version {
+        http::Version::HTTP_09 => "0.9",
+        http::Version::HTTP_10 => "1.0",
+        http::Version::HTTP_11 => "1.1",
+        http::Version::HTTP_2 => "2",
+        http::Version::HTTP_3 => "3",
+        //Invalid version so just set 0
+        _ => "0",
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+///[OpenTelemetry](https://opentelemetry.io/docs/specs/semconv/http/http-spans/#http-server) semantic convention, used by default
+pub struct OtelSemconv;
+
+impl SemanticConvention for OtelSemconv {
+    #[inline(always)]
+    fn record_method(&self, span: &tracing::Span, method: &http::Method) {
+        if is_known_method(method) {
+            span.record("http.request.method", method.as_str());
+        } else {
+            span.record("http.request.method", "_OTHER");
+            span.record("http.request.method_original", method.as_str());
+        }
+    }
+
+    #[inline(always)]
+    fn record_path(&self, span: &tracing::Span, path: &str) {
+        span.record("url.path", path);
+    }
+
+    #[inline(always)]
+    fn record_query(&self, span: &tracing::Span, query: &str) {
+        span.record("url.query", query);
+    }
+
+    #[inline(always)]
+    fn record_scheme(&self, span: &tracing::Span, scheme: &str) {
+        span.record("url.scheme", scheme);
+    }
+
+    #[inline(always)]
+    fn record_request_id(&self, span: &tracing::Span, request_id: &RequestId) {
+        record_request_id(span, "http.request_id", request_id);
+    }
+
+    #[inline(always)]
+    fn record_user_agent(&self, span: &tracing::Span, user_agent: &str) {
+        span.record("user_agent.original", user_agent);
+    }
+
+    #[inline(always)]
+    fn record_client_address(&self, span: &tracing::Span, address: &dyn tracing::Value) {
+        span.record("client.address", address);
+    }
+
+    fn record_protocol(&self, span: &tracing::Span, protocol: Protocol, version: http::Version) {
+        span.record("network.protocol.name", protocol.as_str());
+        span.record("network.protocol.version", version_str(version));
+    }
+
+    #[inline(always)]
+    fn record_status_code(&self, span: &tracing::Span, status: u16) {
+        span.record("http.response.status_code", status);
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+///[Elastic Common Schema](https://www.elastic.co/guide/en/ecs/current/index.html) semantic convention
+pub struct EcsSemconv;
+
+impl SemanticConvention for EcsSemconv {
+    #[inline(always)]
+    fn record_method(&self, span: &tracing::Span, method: &http::Method) {
+        span.record("http.request.method", method.as_str());
+    }
+
+    #[inline(always)]
+    fn record_path(&self, span: &tracing::Span, path: &str) {
+        span.record("url.path", path);
+    }
+
+    #[inline(always)]
+    fn record_query(&self, span: &tracing::Span, query: &str) {
+        span.record("url.query", query);
+    }
+
+    #[inline(always)]
+    fn record_scheme(&self, span: &tracing::Span, scheme: &str) {
+        span.record("url.scheme", scheme);
+    }
+
+    #[inline(always)]
+    fn record_request_id(&self, span: &tracing::Span, request_id: &RequestId) {
+        record_request_id(span, "http.request.id", request_id);
+    }
+
+    #[inline(always)]
+    fn record_user_agent(&self, span: &tracing::Span, user_agent: &str) {
+        span.record("user_agent.original", user_agent);
+    }
+
+    #[inline(always)]
+    fn record_client_address(&self, span: &tracing::Span, address: &dyn tracing::Value) {
+        span.record("client.ip", address);
+    }
+
+    fn record_protocol(&self, span: &tracing::Span, protocol: Protocol, version: http::Version) {
+        span.record("network.protocol", protocol.as_str());
+        span.record("http.version", version_str(version));
+    }
+
+    #[inline(always)]
+    fn record_status_code(&self, span: &tracing::Span, status: u16) {
+        span.record("http.response.status_code", status);
+    }
+}
@@ -0,0 +1,114 @@
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    #[inline(always)]
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Default)]
+///Middleware's internal counters, shared between layer and all its services
+pub struct MiddlewareStats {
+    pub spans_created: Counter,
+    pub spans_disabled: Counter,
+    pub ids_generated: Counter,
+    pub ids_inherited: Counter,
+    pub header_insert_failures: Counter,
+    pub truncations: Counter,
+    pub subscriber_panics: Counter,
+    pub spans_shed: Counter,
+    pub requests_skipped: Counter,
+    pub spans_unsampled: Counter,
+}
+
+impl MiddlewareStats {
+    pub fn snapshot(&self) -> MiddlewareStatsSnapshot {
+        MiddlewareStatsSnapshot {
+            spans_created: self.spans_created.get(),
+            spans_disabled: self.spans_disabled.get(),
+            ids_generated: self.ids_generated.get(),
+            ids_inherited: self.ids_inherited.get(),
+            header_insert_failures: self.header_insert_failures.get(),
+            truncations: self.truncations.get(),
+            subscriber_panics: self.subscriber_panics.get(),
+            spans_shed: self.spans_shed.get(),
+            requests_skipped: self.requests_skipped.get(),
+            spans_unsampled: self.spans_unsampled.get(),
+        }
+    }
+}
+
+///Increments gauge of active requests, decrementing it on drop
+pub struct ActiveRequestGuard(Arc<AtomicU64>);
+
+impl ActiveRequestGuard {
+    #[inline(always)]
+    pub fn new(gauge: &Arc<AtomicU64>) -> Self {
+        gauge.fetch_add(1, Ordering::Relaxed);
+        Self(gauge.clone())
+    }
+}
+
+impl Drop for ActiveRequestGuard {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+///Slot of concurrent request span, releasing it on drop
+pub struct SpanPermit(Arc<AtomicUsize>);
+
+impl SpanPermit {
+    #[inline(always)]
+    ///Acquires slot, unless `max` slots are already taken
+    pub fn try_acquire(count: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        count.fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| if count < max { Some(count + 1) } else { None })
+             .ok()
+             .map(|_| Self(count.clone()))
+    }
+}
+
+impl Drop for SpanPermit {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Snapshot of middleware's internal counters
+///
+///Counters are shared by layer and all services created from it.
+pub struct MiddlewareStatsSnapshot {
+    ///Number of request spans created
+    pub spans_created: u64,
+    ///Number of request spans, which turned out to be disabled by subscriber
+    pub spans_disabled: u64,
+    ///Number of request ids generated by middleware
+    pub ids_generated: u64,
+    ///Number of request ids inherited from request, including ids derived from its trace context
+    pub ids_inherited: u64,
+    ///Number of times middleware failed to insert header into response
+    pub header_insert_failures: u64,
+    ///Number of times request's data had to be truncated
+    pub truncations: u64,
+    ///Number of subscriber panics caught by [guard](struct.HttpRequestLayer.html#method.with_subscriber_panic_guard)
+    pub subscriber_panics: u64,
+    ///Number of requests, which were not given span due to [limit](struct.HttpRequestLayer.html#method.with_max_concurrent_spans)
+    pub spans_shed: u64,
+    ///Number of requests, which bypassed middleware due to [skipped path](struct.HttpRequestLayer.html#method.with_skip_paths)
+    pub requests_skipped: u64,
+    ///Number of requests, which were not given span due to [sampling](trait.LayerContext.html#method.should_sample)
+    pub spans_unsampled: u64,
+}
@@ -0,0 +1,17 @@
+//! Tracing targets
+//!
+//! Events emitted by middleware use stable targets listed here, which allows to build filter directives (e.g. `EnvFilter`) programmatically.
+//!
+//! Note that request spans are created by function declared via [make_request_spanner](../macro.make_request_spanner.html),
+//! therefore their target is module path of the declaration, rather than [SPAN].
+
+///Target reserved for span related events
+pub const SPAN: &str = "tower_http_tracing::span";
+///Target of access log events, emitted once request is complete, if enabled via [with_access_log](../struct.HttpRequestLayer.html#method.with_access_log)
+pub const ACCESS: &str = "tower_http_tracing::access";
+///Target of events reporting middleware's internal errors (e.g. failure to propagate context)
+pub const ERROR: &str = "tower_http_tracing::error";
+///Target of events marking intermediate milestones of the request
+pub const MILESTONE: &str = "tower_http_tracing::milestone";
+///Target of service level events, emitted once per service (e.g. startup metadata)
+pub const SERVICE: &str = "tower_http_tracing::service";
@@ -0,0 +1,170 @@
+//! Time utilities
+//!
+//! Parsing and formatting of time related header values
+
+use std::time::{Duration, SystemTime};
+
+const MONTHS: [&[u8; 3]; 12] = [b"Jan", b"Feb", b"Mar", b"Apr", b"May", b"Jun", b"Jul", b"Aug", b"Sep", b"Oct", b"Nov", b"Dec"];
+
+fn parse_num(bytes: &[u8]) -> Option<u64> {
+    if bytes.is_empty() {
+        return None;
+    }
+
+    let mut result = 0u64;
+    for byt in bytes {
+        if !byt.is_ascii_digit() {
+            return None;
+        }
+        result = result.checked_mul(10)?.checked_add((byt - b'0') as u64)?;
+    }
+    Some(result)
+}
+
+fn parse_month(bytes: &[u8]) -> Option<u64> {
+    MONTHS.iter().position(|month| month.as_slice() == bytes).map(|idx| idx as u64 + 1)
+}
+
+//hh:mm:ss
+fn parse_time(bytes: &[u8]) -> Option<u64> {
+    match bytes {
+        [h1, h2, b':', m1, m2, b':', s1, s2] => {
+            let hours = parse_num(&[*h1, *h2])?;
+            let minutes = parse_num(&[*m1, *m2])?;
+            let seconds = parse_num(&[*s1, *s2])?;
+            if hours > 23 || minutes > 59 || seconds > 60 {
+                return None;
+            }
+            Some(hours * 3600 + minutes * 60 + seconds)
+        },
+        _ => None,
+    }
+}
+
+//Days since unix epoch for proleptic Gregorian calendar date
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    (era * 146097 + day_of_era).checked_sub(719468)
+}
+
+fn to_system_time(year: u64, month: u64, day: u64, time: u64) -> Option<SystemTime> {
+    let days = days_from_civil(year, month, day)?;
+    SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(days * 86400 + time))
+}
+
+///Parses HTTP-date as defined by RFC 9110, supporting IMF-fixdate, obsolete RFC 850 and asctime formats
+pub fn parse_http_date(value: &[u8]) -> Option<SystemTime> {
+    let value = value.trim_ascii();
+    let mut parts = value.split(|byt| *byt == b' ').filter(|part| !part.is_empty());
+
+    let day_name = parts.next()?;
+    match day_name.last() {
+        //IMF-fixdate: Sun, 06 Nov 1994 08:49:37 GMT
+        Some(b',') if day_name.len() == 4 => {
+            let day = parse_num(parts.next()?)?;
+            let month = parse_month(parts.next()?)?;
+            let year = parse_num(parts.next()?)?;
+            let time = parse_time(parts.next()?)?;
+            match (parts.next(), parts.next()) {
+                (Some(b"GMT"), None) => to_system_time(year, month, day, time),
+                _ => None,
+            }
+        },
+        //RFC 850: Sunday, 06-Nov-94 08:49:37 GMT
+        Some(b',') => {
+            let mut date = parts.next()?.split(|byt| *byt == b'-');
+            let day = parse_num(date.next()?)?;
+            let month = parse_month(date.next()?)?;
+            let year = date.next()?;
+            if year.len() != 2 || date.next().is_some() {
+                return None;
+            }
+            let year = parse_num(year)?;
+            let year = if year < 70 { 2000 + year } else { 1900 + year };
+            let time = parse_time(parts.next()?)?;
+            match (parts.next(), parts.next()) {
+                (Some(b"GMT"), None) => to_system_time(year, month, day, time),
+                _ => None,
+            }
+        },
+        //asctime: Sun Nov  6 08:49:37 1994
+        Some(_) if day_name.len() == 3 => {
+            let month = parse_month(parts.next()?)?;
+            let day = parse_num(parts.next()?)?;
+            let time = parse_time(parts.next()?)?;
+            let year = parse_num(parts.next()?)?;
+            match parts.next() {
+                None => to_system_time(year, month, day, time),
+                Some(_) => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+///Parses `Retry-After` value, which can be either delta-seconds or HTTP-date, relative to `now`
+///
+///Dates in the past result in zero duration
+pub fn parse_retry_after(value: &http::HeaderValue, now: SystemTime) -> Option<Duration> {
+    let value = value.as_bytes().trim_ascii();
+    match parse_num(value) {
+        Some(seconds) => Some(Duration::from_secs(seconds)),
+        None => {
+            let date = parse_http_date(value)?;
+            Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+        }
+    }
+}
+
+///Parses `grpc-timeout` value: up to 8 digits followed by unit (`H`, `M`, `S`, `m`, `u` or `n`)
+pub fn parse_grpc_timeout(value: &[u8]) -> Option<Duration> {
+    let (unit, digits) = value.split_last()?;
+    if digits.len() > 8 {
+        return None;
+    }
+    let value = parse_num(digits)?;
+
+    match unit {
+        b'H' => Some(Duration::from_secs(value * 3600)),
+        b'M' => Some(Duration::from_secs(value * 60)),
+        b'S' => Some(Duration::from_secs(value)),
+        b'm' => Some(Duration::from_millis(value)),
+        b'u' => Some(Duration::from_micros(value)),
+        b'n' => Some(Duration::from_nanos(value)),
+        _ => None,
+    }
+}
+
+///Formats `grpc-timeout` value, using the most precise unit that fits into 8 digits
+///
+///Value is rounded up to the selected unit and capped at `99999999H`
+pub fn format_grpc_timeout(timeout: Duration) -> http::HeaderValue {
+    const MAX_VALUE: u128 = 99_999_999;
+    const UNITS: [(u128, char); 6] = [
+        (1, 'n'),
+        (1_000, 'u'),
+        (1_000_000, 'm'),
+        (1_000_000_000, 'S'),
+        (60 * 1_000_000_000, 'M'),
+        (3600 * 1_000_000_000, 'H'),
+    ];
+
+    let nanos = timeout.as_nanos();
+    for (unit_nanos, unit) in UNITS {
+        let value = nanos.div_ceil(unit_nanos);
+        if value <= MAX_VALUE {
+            return crate::headers::to_header_value(format_args!("{value}{unit}"));
+        }
+    }
+
+    http::HeaderValue::from_static("99999999H")
+}
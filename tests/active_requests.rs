@@ -0,0 +1,95 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+use tower_http_tracing::body::ResponseBodyLayer;
+
+use bytes::Bytes;
+use core::{future::Future, pin::{pin, Pin}, task};
+use http_body_util::BodyExt;
+use std::collections::VecDeque;
+use core::sync::atomic::Ordering;
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[test]
+fn should_track_active_requests() {
+    let layer = HttpRequestLayer::new_simple(my_span);
+    let active_requests = layer.active_requests();
+    let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+    let mut receiver = Some(receiver);
+    let mut service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| {
+        let receiver = receiver.take().expect("single request");
+        async move {
+            receiver.await.expect("to be released");
+            Ok::<_, std::io::Error>(http::Response::new(()))
+        }
+    });
+
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    assert!(service.poll_ready(&mut ctx).is_ready());
+    let mut fut = pin!(service.call(http::Request::new(())));
+    assert_eq!(active_requests.load(Ordering::Relaxed), 1);
+    assert!(fut.as_mut().poll(&mut ctx).is_pending());
+    assert_eq!(active_requests.load(Ordering::Relaxed), 1);
+
+    sender.send(()).unwrap();
+    assert!(fut.as_mut().poll(&mut ctx).is_ready());
+    assert_eq!(active_requests.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn should_release_active_request_on_cancel() {
+    let layer = HttpRequestLayer::new_simple(my_span);
+    let active_requests = layer.active_requests();
+    let mut service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| core::future::pending::<Result<http::Response<()>, std::io::Error>>());
+
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    assert!(service.poll_ready(&mut ctx).is_ready());
+    {
+        let first = service.call(http::Request::new(()));
+        let mut second = pin!(service.call(http::Request::new(())));
+        assert!(second.as_mut().poll(&mut ctx).is_pending());
+        assert_eq!(active_requests.load(Ordering::Relaxed), 2);
+        drop(first);
+        assert_eq!(active_requests.load(Ordering::Relaxed), 1);
+    }
+    assert_eq!(active_requests.load(Ordering::Relaxed), 0);
+}
+
+//Body yielding each chunk as separate frame
+struct Chunks(VecDeque<&'static str>);
+
+impl http_body::Body for Chunks {
+    type Data = Bytes;
+    type Error = core::convert::Infallible;
+
+    fn poll_frame(mut self: Pin<&mut Self>, _: &mut task::Context<'_>) -> task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        task::Poll::Ready(self.0.pop_front().map(|chunk| Ok(http_body::Frame::data(Bytes::from_static(chunk.as_bytes())))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[tokio::test]
+async fn should_keep_request_active_until_tracked_body_ends() {
+    let layer = HttpRequestLayer::new_simple(my_span);
+    let active_requests = layer.active_requests();
+    let service = ServiceBuilder::new().layer(layer)
+                                       .layer(ResponseBodyLayer::new().with_track_response_body(true))
+                                       .service_fn(|_: http::Request<()>| async move {
+        Ok::<_, std::io::Error>(http::Response::new(Chunks(VecDeque::from(["hello ", "world"]))))
+    });
+
+    let mut body = service.clone().oneshot(http::Request::new(())).await.unwrap().into_body();
+    assert_eq!(active_requests.load(Ordering::Relaxed), 1);
+    assert_eq!(body.frame().await.unwrap().unwrap().into_data().unwrap(), "hello ");
+    assert_eq!(active_requests.load(Ordering::Relaxed), 1);
+    assert_eq!(body.frame().await.unwrap().unwrap().into_data().unwrap(), "world");
+    assert_eq!(active_requests.load(Ordering::Relaxed), 0);
+
+    let body = service.oneshot(http::Request::new(())).await.unwrap().into_body();
+    assert_eq!(active_requests.load(Ordering::Relaxed), 1);
+    drop(body);
+    assert_eq!(active_requests.load(Ordering::Relaxed), 0);
+}
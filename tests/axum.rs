@@ -0,0 +1,39 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestInfo};
+use tower_http_tracing::axum::RequestSpanHandle;
+
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn handler(info: RequestInfo, span: RequestSpanHandle) -> String {
+    span.span().record("url.path", "/handler");
+    info.request_id.to_string()
+}
+
+fn router() -> axum::Router {
+    axum::Router::new().route("/", axum::routing::get(handler))
+}
+
+async fn request(router: axum::Router) -> (http::StatusCode, String) {
+    let mut request = http::Request::new(axum::body::Body::empty());
+    request.headers_mut().insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static("request-ID"));
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn should_extract_request_info() {
+    let (status, body) = request(router().layer(HttpRequestLayer::new_simple(my_span))).await;
+    assert_eq!(status, http::StatusCode::OK);
+    assert_eq!(body, "request-ID");
+}
+
+#[tokio::test]
+async fn should_reject_without_layer() {
+    let (status, body) = request(router()).await;
+    assert_eq!(status, http::StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body, "RequestInfo is missing: HttpRequestLayer is not installed");
+}
@@ -0,0 +1,540 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, SpanHandle};
+use tower_http_tracing::body::{BodyHasher, BodyLayer, DisableBodyInstrumentation, RequestBody, ResponseBodyLayer};
+
+use tower_http_tracing::clock::ManualClock;
+
+use core::{pin::{pin, Pin}, task};
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use sha2::Digest;
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+struct Sha256(sha2::Sha256);
+
+impl BodyHasher for Sha256 {
+    fn update(&mut self, chunk: &[u8]) {
+        self.0.update(chunk);
+    }
+
+    fn finish(self: Box<Self>) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+fn make_hasher() -> Box<dyn BodyHasher> {
+    Box::new(Sha256(sha2::Sha256::new()))
+}
+
+//Body without size hint, yielding each chunk as separate frame
+struct Chunks(VecDeque<&'static str>);
+
+impl http_body::Body for Chunks {
+    type Data = Bytes;
+    type Error = core::convert::Infallible;
+
+    fn poll_frame(mut self: Pin<&mut Self>, _: &mut task::Context<'_>) -> task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        task::Poll::Ready(self.0.pop_front().map(|chunk| Ok(http_body::Frame::data(Bytes::from_static(chunk.as_bytes())))))
+    }
+}
+
+async fn request<B: http_body::Body + Send + 'static>(body: B) where B::Data: Send, B::Error: core::fmt::Debug {
+    send(BodyLayer::new().with_request_body_hash(16, make_hasher), http::Request::new(body)).await
+}
+
+async fn send<B: http_body::Body + Send + 'static>(layer: BodyLayer, req: http::Request<B>) where B::Data: Send, B::Error: core::fmt::Debug {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span))
+                                       .layer(layer)
+                                       .service_fn(|req: http::Request<RequestBody<B>>| async move {
+        req.into_body().collect().await.unwrap();
+        tracing::info!("HANDLER");
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_hash_body_chunks() {
+    request(Chunks(VecDeque::from(["hello ", "world"]))).await;
+    //sha256("hello world") = b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9
+    assert!(logs_contain(r#"http.request.body.sha256="b94d27b9934d3e08""#));
+    assert!(!logs_contain("http.request.body.hash_skipped"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_hash_body_ending_with_data() {
+    request(http_body_util::Full::new(Bytes::from_static(b"hello world"))).await;
+    assert!(logs_contain(r#"http.request.body.sha256="b94d27b9934d3e08""#));
+}
+
+//Data split into single byte segments
+struct Segments(VecDeque<u8>);
+
+impl bytes::Buf for Segments {
+    fn remaining(&self) -> usize {
+        self.0.len()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.0.as_slices().0.get(..1).unwrap_or_default()
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.0.drain(..cnt);
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [std::io::IoSlice<'a>]) -> usize {
+        let (first, second) = self.0.as_slices();
+        let mut len = 0;
+        for (slot, byte) in dst.iter_mut().zip(first.chunks(1).chain(second.chunks(1))) {
+            *slot = std::io::IoSlice::new(byte);
+            len += 1;
+        }
+        len
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_hash_body_of_many_segments() {
+    let body = Segments(b"abcdefghijklmnopqrstuvwxyzabcdefghijklmnopqrstuvwxyz".iter().copied().collect());
+    send(BodyLayer::new().with_request_body_hash(64, make_hasher), http::Request::new(http_body_util::Full::new(body))).await;
+    //sha256("abcdefghijklmnopqrstuvwxyz" * 2) = 2378d314a98e2394...
+    assert!(logs_contain(r#"http.request.body.sha256="2378d314a98e2394""#));
+    assert!(!logs_contain("http.request.body.hash_skipped"));
+}
+
+//Body yielding each chunk as frame of single byte segments
+struct SegmentFrames(VecDeque<&'static [u8]>);
+
+impl http_body::Body for SegmentFrames {
+    type Data = Segments;
+    type Error = core::convert::Infallible;
+
+    fn poll_frame(mut self: Pin<&mut Self>, _: &mut task::Context<'_>) -> task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        task::Poll::Ready(self.0.pop_front().map(|chunk| Ok(http_body::Frame::data(Segments(chunk.iter().copied().collect())))))
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_hash_body_of_many_segments_per_frame() {
+    let body = SegmentFrames(VecDeque::from([&b"abcdefghijklmnopqrstuvwxyz"[..], b"abcdefghijklmnopqrstuvwxyz"]));
+    send(BodyLayer::new().with_request_body_hash(64, make_hasher), http::Request::new(body)).await;
+    assert!(logs_contain(r#"http.request.body.sha256="2378d314a98e2394""#));
+    assert!(!logs_contain("http.request.body.hash_skipped"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_skip_hash_of_large_body() {
+    request(Chunks(VecDeque::from(["0123456789", "0123456789"]))).await;
+    assert!(logs_contain("http.request.body.hash_skipped=true"));
+    assert!(!logs_contain("http.request.body.sha256"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_skip_hash_of_body_with_large_size_hint() {
+    request(http_body_util::Full::new(Bytes::from_static(b"01234567890123456789"))).await;
+    assert!(logs_contain("http.request.body.hash_skipped=true"));
+    assert!(!logs_contain("http.request.body.sha256"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_instrument_body_with_disable_extension() {
+    let mut req = http::Request::new(http_body_util::Full::new(Bytes::from_static(b"hello world")));
+    req.extensions_mut().insert(DisableBodyInstrumentation);
+    send(BodyLayer::new().with_request_body_hash(16, make_hasher), req).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("http.request.body.sha256"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_instrument_body_of_excluded_path() {
+    let layer = BodyLayer::new().with_request_body_hash(16, make_hasher).with_no_body_instrumentation_paths(&["/proxy/"]);
+    let mut req = http::Request::new(http_body_util::Full::new(Bytes::from_static(b"01234567890123456789")));
+    *req.uri_mut() = http::Uri::from_static("/proxy/stream");
+    send(layer.clone(), req).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("http.request.body.hash_skipped"));
+
+    let mut req = http::Request::new(http_body_util::Full::new(Bytes::from_static(b"hello world")));
+    *req.uri_mut() = http::Uri::from_static("/api/proxy/stream");
+    send(layer, req).await;
+    assert!(logs_contain(r#"http.request.body.sha256="b94d27b9934d3e08""#));
+}
+
+//Body which becomes ready only once its gate is opened
+struct Delayed {
+    gate: Arc<AtomicBool>,
+    chunk: Option<&'static str>,
+}
+
+impl http_body::Body for Delayed {
+    type Data = Bytes;
+    type Error = core::convert::Infallible;
+
+    fn poll_frame(mut self: Pin<&mut Self>, ctx: &mut task::Context<'_>) -> task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        if !self.gate.load(Ordering::Acquire) {
+            ctx.waker().wake_by_ref();
+            return task::Poll::Pending;
+        }
+        task::Poll::Ready(self.chunk.take().map(|chunk| Ok(http_body::Frame::data(Bytes::from_static(chunk.as_bytes())))))
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_first_byte_delay_of_expect_continue() {
+    let clock = Arc::new(ManualClock::new(SystemTime::now()));
+    let gate = Arc::new(AtomicBool::new(false));
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span))
+                                       .layer(BodyLayer::new().with_request_first_byte_delay().with_clock(clock.clone()))
+                                       .service_fn(|req: http::Request<RequestBody<Delayed>>| async move {
+        req.into_body().collect().await.unwrap();
+        tracing::info!("HANDLER");
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(Delayed { gate: gate.clone(), chunk: Some("hello") });
+    req.headers_mut().insert(http::header::EXPECT, http::HeaderValue::from_static("100-Continue"));
+    let mut response = pin!(service.oneshot(req));
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    assert!(response.as_mut().poll(&mut ctx).is_pending());
+    //Client starts sending body only after interim response
+    clock.advance(Duration::from_millis(250));
+    gate.store(true, Ordering::Release);
+    response.await.unwrap();
+
+    assert!(logs_contain("http.request.expect_continue=true"));
+    assert!(logs_contain("http.request.body.first_byte_delay_ms=250"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_first_byte_delay_by_default() {
+    request(http_body_util::Full::new(Bytes::from_static(b"hello world"))).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("http.request.body.first_byte_delay_ms"));
+    assert!(!logs_contain("http.request.expect_continue"));
+}
+
+//Body yielding single data frame followed by trailers
+struct WithTrailers(Option<&'static str>, Option<http::HeaderMap>);
+
+impl http_body::Body for WithTrailers {
+    type Data = Bytes;
+    type Error = core::convert::Infallible;
+
+    fn poll_frame(mut self: Pin<&mut Self>, _: &mut task::Context<'_>) -> task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        if let Some(chunk) = self.0.take() {
+            return task::Poll::Ready(Some(Ok(http_body::Frame::data(Bytes::from_static(chunk.as_bytes())))));
+        }
+        task::Poll::Ready(self.1.take().map(|trailers| Ok(http_body::Frame::trailers(trailers))))
+    }
+}
+
+fn trailers(values: &[(&'static str, &'static str)]) -> http::HeaderMap {
+    values.iter().map(|(name, value)| (http::HeaderName::from_static(name), http::HeaderValue::from_static(value))).collect()
+}
+
+async fn respond_with_trailers(layer: ResponseBodyLayer, response_trailers: http::HeaderMap) {
+    let request_span = Arc::new(std::sync::Mutex::new(tracing::Span::none()));
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span))
+                                       .layer(layer)
+                                       .service_fn(|req: http::Request<()>| {
+        *request_span.lock().unwrap() = req.extensions().get::<SpanHandle>().unwrap().span().clone();
+        let body = WithTrailers(Some("data"), Some(response_trailers.clone()));
+        async move {
+            Ok::<_, std::io::Error>(http::Response::new(body))
+        }
+    });
+
+    let body = service.oneshot(http::Request::new(())).await.unwrap().into_body();
+    let collected = body.collect().await.unwrap();
+    assert!(collected.trailers().is_some());
+    //Trailers are recorded after response is returned, so emit event to print them
+    tracing::info!(parent: &*request_span.lock().unwrap(), "BODY");
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_inspect_response_trailers() {
+    let layer = ResponseBodyLayer::new().with_inspect_trailers(&[http::HeaderName::from_static("grpc-status"), http::HeaderName::from_static("server-timing")]);
+    respond_with_trailers(layer, trailers(&[("grpc-status", "0"), ("server-timing", "db;dur=53"), ("x-other", "secret")])).await;
+    assert!(logs_contain("http.response.trailers={\"grpc-status\": 0, \"server-timing\": db;dur=53}"));
+    assert!(!logs_contain("secret"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_absent_response_trailers() {
+    let layer = ResponseBodyLayer::new().with_inspect_trailers(&[http::HeaderName::from_static("grpc-status")]);
+    respond_with_trailers(layer, trailers(&[("x-other", "value")])).await;
+    assert!(logs_contain("BODY"));
+    assert!(!logs_contain("http.response.trailers"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_inspect_response_trailers_by_default() {
+    respond_with_trailers(ResponseBodyLayer::new(), trailers(&[("grpc-status", "0")])).await;
+    assert!(logs_contain("BODY"));
+    assert!(!logs_contain("http.response.trailers"));
+}
+
+//Captures milestone and span events with recorded body sizes and trailers
+#[derive(Clone, Default)]
+struct MilestoneCapture(Arc<std::sync::Mutex<Vec<String>>>);
+
+//Formats fields with any of suffixes (or all fields, if none are specified) into single line
+struct FieldsVisitor<'a>(&'a mut String, &'static [&'static str]);
+
+impl tracing::field::Visit for FieldsVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+        if !self.1.is_empty() && !self.1.iter().any(|suffix| field.name().ends_with(suffix)) {
+            return;
+        }
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        self.0.push_str(&format!("{}={value:?}", field.name()));
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for MilestoneCapture {
+    fn on_record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        let mut line = String::new();
+        values.record(&mut FieldsVisitor(&mut line, &[".body.size", ".trailers"]));
+        if !line.is_empty() {
+            self.0.lock().unwrap().push(line);
+        }
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        if [tower_http_tracing::targets::MILESTONE, tower_http_tracing::targets::SPAN].contains(&event.metadata().target()) {
+            let mut line = String::new();
+            event.record(&mut FieldsVisitor(&mut line, &[]));
+            self.0.lock().unwrap().push(line);
+        }
+    }
+}
+
+impl MilestoneCapture {
+    fn take(&self) -> Vec<String> {
+        core::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+async fn respond_with_chunks(layer: ResponseBodyLayer, chunks: &'static [&'static str]) -> tower_http_tracing::body::ResponseBody<Chunks> {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span))
+                                       .layer(layer)
+                                       .service_fn(move |_: http::Request<()>| async move {
+        Ok::<_, std::io::Error>(http::Response::new(Chunks(VecDeque::from_iter(chunks.iter().copied()))))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap().into_body()
+}
+
+#[tokio::test]
+async fn should_track_streamed_response_body() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let capture = MilestoneCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let clock = Arc::new(ManualClock::new(SystemTime::now()));
+    let body = respond_with_chunks(ResponseBodyLayer::new().with_track_response_body(true).with_clock(clock.clone()), &["hello ", "streamed ", "world"]).await;
+    clock.advance(Duration::from_millis(25));
+
+    let collected = body.collect().await.unwrap().to_bytes();
+    assert_eq!(collected, "hello streamed world");
+    assert_eq!(capture.take(), [
+        "http.response.body.size=20",
+        "message=Response body is complete bytes=20 elapsed_ms=25 completed=true",
+    ]);
+}
+
+#[tokio::test]
+async fn should_track_response_body_dropped_midway() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let capture = MilestoneCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let clock = Arc::new(ManualClock::new(SystemTime::now()));
+    let mut body = respond_with_chunks(ResponseBodyLayer::new().with_track_response_body(true).with_clock(clock.clone()), &["hello ", "streamed ", "world"]).await;
+
+    let frame = body.frame().await.unwrap().unwrap();
+    assert_eq!(frame.into_data().unwrap(), "hello ");
+    clock.advance(Duration::from_millis(10));
+    assert!(capture.take().is_empty());
+
+    drop(body);
+    assert_eq!(capture.take(), [
+        "http.response.body.size=6",
+        "message=Response body is dropped before its end bytes=6 elapsed_ms=10 completed=false",
+    ]);
+}
+
+#[tokio::test]
+async fn should_not_track_response_body_by_default() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let capture = MilestoneCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let body = respond_with_chunks(ResponseBodyLayer::new(), &["hello"]).await;
+    assert_eq!(body.collect().await.unwrap().to_bytes(), "hello");
+    assert!(capture.take().is_empty());
+}
+
+//Responds with 4 bytes of body followed by trailers, returning records captured at response's head and once body is collected
+async fn respond_with_opt_out(layer: ResponseBodyLayer, req: http::Request<()>, handler_opt_out: bool) -> (Vec<String>, Vec<String>) {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let capture = MilestoneCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let layer = layer.with_track_response_body(true).with_inspect_trailers(&[http::HeaderName::from_static("grpc-status")]);
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span))
+                                       .layer(layer)
+                                       .service_fn(move |_: http::Request<()>| async move {
+        let mut response = http::Response::new(WithTrailers(Some("data"), Some(trailers(&[("grpc-status", "0")]))));
+        response.headers_mut().insert(http::header::CONTENT_LENGTH, http::HeaderValue::from_static("4"));
+        if handler_opt_out {
+            response.extensions_mut().insert(DisableBodyInstrumentation);
+        }
+        Ok::<_, std::io::Error>(response)
+    });
+
+    let body = service.oneshot(req).await.unwrap().into_body();
+    let head = capture.take();
+
+    let collected = body.collect().await.unwrap();
+    assert!(collected.trailers().is_some());
+    assert_eq!(collected.to_bytes(), "data");
+    (head, capture.take())
+}
+
+#[tokio::test]
+async fn should_not_track_response_body_opted_out_by_handler() {
+    let (head, body) = respond_with_opt_out(ResponseBodyLayer::new(), http::Request::new(()), true).await;
+    //Content-Length is recorded at response's head
+    assert_eq!(head, ["http.response.body.size=4"]);
+    assert!(body.is_empty(), "{body:?}");
+}
+
+#[tokio::test]
+async fn should_not_track_response_body_opted_out_by_request() {
+    let mut req = http::Request::new(());
+    req.extensions_mut().insert(DisableBodyInstrumentation);
+    let (head, body) = respond_with_opt_out(ResponseBodyLayer::new(), req, false).await;
+    assert_eq!(head, ["http.response.body.size=4"]);
+    assert!(body.is_empty(), "{body:?}");
+}
+
+#[tokio::test]
+async fn should_not_track_response_body_of_excluded_path() {
+    let layer = ResponseBodyLayer::new().with_no_body_instrumentation_paths(&["/proxy/"]);
+    let mut req = http::Request::new(());
+    *req.uri_mut() = http::Uri::from_static("/proxy/stream");
+    let (head, body) = respond_with_opt_out(layer.clone(), req, false).await;
+    assert_eq!(head, ["http.response.body.size=4"]);
+    assert!(body.is_empty(), "{body:?}");
+
+    let mut req = http::Request::new(());
+    *req.uri_mut() = http::Uri::from_static("/api/proxy/stream");
+    let (head, body) = respond_with_opt_out(layer, req, false).await;
+    assert_eq!(head, ["http.response.body.size=4"]);
+    assert_eq!(body.len(), 3, "{body:?}");
+    assert!(body[0].starts_with("http.response.trailers="), "{body:?}");
+    assert_eq!(body[1], "http.response.body.size=4");
+    assert!(body[2].starts_with("message=Response body is complete bytes=4"), "{body:?}");
+}
+
+//Reads up to `frames` frames of request's body, dropping the rest
+async fn receive_chunks(layer: BodyLayer, content_length: Option<&'static str>, chunks: &'static [&'static str], frames: usize) {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span))
+                                       .layer(layer)
+                                       .service_fn(move |req: http::Request<RequestBody<Chunks>>| async move {
+        let mut body = req.into_body();
+        for _ in 0..frames {
+            if body.frame().await.is_none() {
+                break;
+            }
+        }
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(Chunks(VecDeque::from_iter(chunks.iter().copied())));
+    if let Some(content_length) = content_length {
+        req.headers_mut().insert(http::header::CONTENT_LENGTH, http::HeaderValue::from_static(content_length));
+    }
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+async fn should_track_streamed_request_body() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let capture = MilestoneCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    receive_chunks(BodyLayer::new().with_track_request_body(true), None, &["hello ", "streamed ", "world"], usize::MAX).await;
+    assert_eq!(capture.take(), ["http.request.body.size=20"]);
+
+    //Actual size takes precedence over declared one
+    receive_chunks(BodyLayer::new().with_track_request_body(true), Some("5"), &["hello ", "streamed ", "world"], usize::MAX).await;
+    assert_eq!(capture.take(), ["http.request.body.size=5", "http.request.body.size=20"]);
+}
+
+#[tokio::test]
+async fn should_warn_of_request_body_dropped_before_content_length() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let capture = MilestoneCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    receive_chunks(BodyLayer::new().with_track_request_body(true), Some("20"), &["hello ", "streamed ", "world"], 1).await;
+    assert_eq!(capture.take(), [
+        "http.request.body.size=20",
+        "message=Request body is dropped before its end received=6 expected=20",
+    ]);
+
+    //Without declared length there is nothing to complain about
+    receive_chunks(BodyLayer::new().with_track_request_body(true), None, &["hello ", "streamed ", "world"], 1).await;
+    assert!(capture.take().is_empty());
+}
+
+#[tokio::test]
+async fn should_not_track_request_body_by_default() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let capture = MilestoneCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    receive_chunks(BodyLayer::new(), None, &["hello"], usize::MAX).await;
+    assert!(capture.take().is_empty());
+    receive_chunks(BodyLayer::new(), Some("20"), &["hello"], 1).await;
+    assert_eq!(capture.take(), ["http.request.body.size=20"]);
+}
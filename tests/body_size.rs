@@ -0,0 +1,74 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+//Logs event once response fields are recorded
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+async fn request(request_len: Option<&'static str>, response_len: Option<&'static str>) {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        if let Some(len) = response_len {
+            response.headers_mut().insert(http::header::CONTENT_LENGTH, http::HeaderValue::from_static(len));
+        }
+        Ok::<_, core::convert::Infallible>(response)
+    });
+
+    let mut request = http::Request::post("/");
+    if let Some(len) = request_len {
+        request = request.header(http::header::CONTENT_LENGTH, len);
+    }
+    service.oneshot(request.body(()).unwrap()).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_body_sizes_from_content_length() {
+    request(Some("42"), Some("1024")).await;
+    assert!(logs_contain("http.request.body.size=42"));
+    assert!(logs_contain("http.response.body.size=1024"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_empty_body_size() {
+    request(Some("0"), Some("0")).await;
+    assert!(logs_contain("http.request.body.size=0"));
+    assert!(logs_contain("http.response.body.size=0"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_ignore_malformed_content_length() {
+    request(Some("-1"), Some("12abc")).await;
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("body.size"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_ignore_overflowing_content_length() {
+    request(Some("18446744073709551616"), None).await;
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("body.size"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_body_size_without_content_length() {
+    request(None, None).await;
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("body.size"));
+}
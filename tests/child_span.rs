@@ -0,0 +1,55 @@
+use tower_http_tracing::{make_child_spanner, make_request_spanner, HttpRequestLayer, RequestInfo, SpanHandle};
+
+use core::time::Duration;
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+make_child_spanner!(db_span("db", tracing::Level::INFO, db.system = "postgresql"));
+make_child_spanner!(cache_span("cache", tracing::Level::INFO));
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_create_child_spans() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_default_timeout_budget(Duration::from_secs(60));
+    let service = ServiceBuilder::new().layer(layer).service_fn(|req: http::Request<()>| async move {
+        let info = req.extensions().get::<RequestInfo>().unwrap();
+        let handle = req.extensions().get::<SpanHandle>().unwrap();
+
+        for make_span in [db_span as tower_http_tracing::MakeChildSpan, cache_span] {
+            let span = info.child_span(handle, make_span);
+            let _entered = span.enter();
+            tracing::info!("DOWNSTREAM");
+        }
+
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    req.headers_mut().insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static("request-ID"));
+    service.oneshot(req).await.unwrap();
+
+    assert!(logs_contain(r#"}:db{span.kind="client" db.system="postgresql" http.request_id="request-ID" deadline.remaining_ms="#));
+    assert!(logs_contain(r#"}:cache{span.kind="client" http.request_id="request-ID" deadline.remaining_ms="#));
+    assert!(logs_contain(r#"should_create_child_spans:request{span.kind="server""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_omit_remaining_without_deadline() {
+    let layer = HttpRequestLayer::new_simple(my_span);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|req: http::Request<()>| async move {
+        let info = req.extensions().get::<RequestInfo>().unwrap();
+        let handle = req.extensions().get::<SpanHandle>().unwrap();
+
+        let span = info.child_span(handle, cache_span);
+        let _entered = span.enter();
+        tracing::info!("DOWNSTREAM");
+
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+
+    assert!(logs_contain("cache{span.kind=\"client\" http.request_id="));
+    assert!(!logs_contain("deadline.remaining_ms"));
+}
@@ -0,0 +1,83 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestId, RequestInfo, REQUEST_ID};
+use tower_http_tracing::client::RequestIdPropagationLayer;
+
+use tower::{Layer, ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const REQUEST_ID_VALUE: &str = "request-ID";
+
+//Downstream service, replying with request id it observed
+fn downstream() -> impl tower::Service<http::Request<()>, Response = http::Response<String>, Error = core::convert::Infallible, Future: Send> + Clone + Send {
+    ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.request_id.to_string()))
+    })
+}
+
+#[tokio::test]
+async fn should_propagate_request_id_end_to_end() {
+    let upstream = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        let client = RequestIdPropagationLayer::with_request_id(info.request_id.clone()).layer(downstream());
+        let response = client.oneshot(http::Request::new(())).await?;
+        Ok::<_, core::convert::Infallible>(response)
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(REQUEST_ID_VALUE));
+    let response = upstream.oneshot(request).await.unwrap();
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert_eq!(response.body(), REQUEST_ID_VALUE);
+
+    //Generated id is propagated the same way
+    let upstream = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info").clone();
+        let client = RequestIdPropagationLayer::with_request_id(info.request_id.clone()).layer(downstream());
+        let response = client.oneshot(http::Request::new(())).await?;
+        Ok::<_, core::convert::Infallible>(http::Response::new((info.request_id, response.into_body())))
+    });
+    let response = upstream.oneshot(http::Request::new(())).await.unwrap();
+    let (request_id, downstream_id) = response.into_body();
+    assert_eq!(request_id.as_str(), Some(downstream_id.as_str()));
+}
+
+#[tokio::test]
+async fn should_propagate_request_id_from_extensions() {
+    let client = RequestIdPropagationLayer::new().layer(downstream());
+
+    let mut request = http::Request::new(());
+    request.extensions_mut().insert(RequestInfo {
+        protocol: tower_http_tracing::Protocol::Http,
+        request_id: REQUEST_ID_VALUE.parse().unwrap(),
+        request_id_origin: tower_http_tracing::RequestIdOrigin::Inherited,
+        incoming_request_id: None,
+        correlation_id: None,
+        peer_service: None,
+        client_ip: None,
+        client_ip_display: None,
+        deadline: None,
+        trace_context: None,
+        details: tower_http_tracing::ProtocolDetails::Http { version: http::Version::HTTP_11 },
+        request_id_source: None,
+        sampled: true,
+        started: std::time::Instant::now(),
+        clock: None,
+    });
+    let response = client.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.body(), REQUEST_ID_VALUE);
+
+    //Without id to propagate, downstream generates its own
+    let response = client.oneshot(http::Request::new(())).await.unwrap();
+    assert_ne!(response.body(), REQUEST_ID_VALUE);
+}
+
+#[tokio::test]
+async fn should_not_overwrite_outgoing_request_id() {
+    let client = RequestIdPropagationLayer::with_request_id(RequestId::new_v4()).layer(downstream());
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(REQUEST_ID_VALUE));
+    let response = client.oneshot(request).await.unwrap();
+    assert_eq!(response.body(), REQUEST_ID_VALUE);
+}
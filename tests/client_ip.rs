@@ -0,0 +1,184 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext, RequestInfo, IpPrivacy};
+
+use std::net::IpAddr;
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const TEST_IP: http::HeaderName = http::HeaderName::from_static("x-test-ip");
+
+#[derive(Copy, Clone)]
+struct HeaderIpContext;
+
+impl LayerContext for HeaderIpContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn extract_client_ip(&self, _: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
+        parts.headers.get(TEST_IP)?.to_str().ok()?.parse().ok()
+    }
+}
+
+async fn request_from(layer: HttpRequestLayer<HeaderIpContext>, ip: &'static str) -> Option<IpAddr> {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        tracing::info!("LOG");
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.client_ip))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(TEST_IP, http::HeaderValue::from_static(ip));
+    service.oneshot(request).await.unwrap().into_body()
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_normalize_mapped_ipv4() {
+    let client_ip = request_from(HttpRequestLayer::new(my_span, HeaderIpContext), "::ffff:203.0.113.7").await;
+    assert_eq!(client_ip, "203.0.113.7".parse().ok());
+    assert!(logs_contain(r#"client.address=203.0.113.7 client.address.family="ipv4""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_normalize_mapped_ipv4_when_disabled() {
+    let client_ip = request_from(HttpRequestLayer::new(my_span, HeaderIpContext).with_normalize_mapped_ipv4(false), "::ffff:203.0.113.7").await;
+    assert_eq!(client_ip, "::ffff:203.0.113.7".parse().ok());
+    assert!(logs_contain(r#"client.address=::ffff:203.0.113.7 client.address.family="ipv6""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_native_ipv6() {
+    let client_ip = request_from(HttpRequestLayer::new(my_span, HeaderIpContext), "2001:db8::1").await;
+    assert_eq!(client_ip, "2001:db8::1".parse().ok());
+    assert!(logs_contain(r#"client.address=2001:db8::1 client.address.family="ipv6""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_native_ipv4() {
+    let client_ip = request_from(HttpRequestLayer::new(my_span, HeaderIpContext), "198.51.100.1").await;
+    assert_eq!(client_ip, "198.51.100.1".parse().ok());
+    assert!(logs_contain(r#"client.address=198.51.100.1 client.address.family="ipv4""#));
+}
+
+#[test]
+fn should_truncate_ip() {
+    assert_eq!(IpPrivacy::truncate("203.0.113.7".parse().unwrap()), "203.0.113.0".parse::<IpAddr>().unwrap());
+    assert_eq!(IpPrivacy::truncate("2001:db8:85a3:8d3:1319:8a2e:370:7348".parse().unwrap()), "2001:db8:85a3::".parse::<IpAddr>().unwrap());
+    assert_eq!(IpPrivacy::truncate("2001:db8:85a3:ffff:ffff:ffff:ffff:ffff".parse().unwrap()), "2001:db8:85a3::".parse::<IpAddr>().unwrap());
+    assert_eq!(IpPrivacy::truncate("ffff:ffff:ffff:ffff::".parse().unwrap()), "ffff:ffff:ffff::".parse::<IpAddr>().unwrap());
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_raw_ip_by_default() {
+    let client_ip = request_from(HttpRequestLayer::new(my_span, HeaderIpContext).with_client_ip_privacy(IpPrivacy::Raw), "203.0.113.7").await;
+    assert_eq!(client_ip, "203.0.113.7".parse().ok());
+    assert!(logs_contain("client.address=203.0.113.7 "));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_truncated_ip() {
+    let layer = HttpRequestLayer::new(my_span, HeaderIpContext).with_client_ip_privacy(IpPrivacy::Truncate);
+    let client_ip = request_from(layer.clone(), "203.0.113.7").await;
+    assert_eq!(client_ip, "203.0.113.0".parse().ok());
+    assert!(logs_contain(r#"client.address=203.0.113.0 client.address.family="ipv4""#));
+
+    let client_ip = request_from(layer, "2001:db8:85a3:8d3:1319:8a2e:370:7348").await;
+    assert_eq!(client_ip, "2001:db8:85a3::".parse().ok());
+    assert!(logs_contain(r#"client.address=2001:db8:85a3:: client.address.family="ipv6""#));
+    assert!(!logs_contain("203.0.113.7"));
+}
+
+fn fake_hash(ip: &IpAddr) -> String {
+    format!("hash-{}", ip.is_ipv4())
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_hashed_ip() {
+    let layer = HttpRequestLayer::new(my_span, HeaderIpContext).with_client_ip_privacy(IpPrivacy::Hash(fake_hash));
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        tracing::info!("LOG");
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        assert_eq!(info.client_ip, None);
+        assert_eq!(info.client_ip_display.as_deref(), Some("hash-true"));
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(TEST_IP, http::HeaderValue::from_static("203.0.113.7"));
+    service.oneshot(request).await.unwrap();
+    assert!(logs_contain(r#"client.address="hash-true" client.address.family="ipv4""#));
+    assert!(!logs_contain("203.0.113.7"));
+}
+
+async fn debug_request_info(layer: HttpRequestLayer<HeaderIpContext>, request_id: &'static str) -> (String, String) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new((format!("{info:?}"), format!("{:?}", info.full_debug()))))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(TEST_IP, http::HeaderValue::from_static("203.0.113.7"));
+    request.headers_mut().insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static(request_id));
+    service.oneshot(request).await.unwrap().into_body()
+}
+
+#[tokio::test]
+async fn should_redact_request_info_debug() {
+    let layer = HttpRequestLayer::new(my_span, HeaderIpContext).with_client_ip_privacy(IpPrivacy::Truncate);
+    let (redacted, full) = debug_request_info(layer, "0123456789abcdef").await;
+    assert_eq!(redacted, r#"RequestInfo { protocol: "http", request_id: "01234567…", client_ip: Some(203.0.113.0), deadline: None, trace_context: None }"#);
+    assert_eq!(full, r#"RequestInfo { protocol: "http", request_id: "0123456789abcdef", client_ip: Some(203.0.113.0), deadline: None, trace_context: None }"#);
+
+    let layer = HttpRequestLayer::new(my_span, HeaderIpContext).with_client_ip_privacy(IpPrivacy::Hash(fake_hash));
+    let (redacted, _) = debug_request_info(layer, "short").await;
+    assert!(redacted.contains(r#"request_id: "short""#));
+    assert!(!redacted.contains("203.0.113.7"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_build_layer_profiles() {
+    let mut layers = HttpRequestLayer::new(my_span, HeaderIpContext).variants()
+                                                                   .profile("internal", |layer| layer)
+                                                                   .profile("public", |layer| layer.with_client_ip_privacy(IpPrivacy::Truncate))
+                                                                   .build();
+    assert_eq!(layers.len(), 2);
+
+    let client_ip = request_from(layers.remove("internal").unwrap(), "203.0.113.7").await;
+    assert_eq!(client_ip, "203.0.113.7".parse().ok());
+
+    let client_ip = request_from(layers.remove("public").unwrap(), "203.0.113.7").await;
+    assert_eq!(client_ip, "203.0.113.0".parse().ok());
+    assert!(logs_contain("client.address=203.0.113.7"));
+    assert!(logs_contain("client.address=203.0.113.0"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_extract_client_ip_with_capturing_closure() {
+    let trusted: Vec<ipnet::IpNet> = vec!["10.0.0.0/8".parse().unwrap(), "2001:db8::/32".parse().unwrap()];
+    let layer = HttpRequestLayer::new_simple(my_span).with_extract_client_ip(move |parts: &http::request::Parts| {
+        let ip: IpAddr = parts.headers.get(TEST_IP)?.to_str().ok()?.parse().ok()?;
+        trusted.iter().any(|range| range.contains(&ip)).then_some(ip)
+    });
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        tracing::info!("LOG");
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.client_ip))
+    });
+
+    for (ip, expected) in [("10.1.2.3", "10.1.2.3".parse().ok()), ("2001:db8::7", "2001:db8::7".parse().ok()), ("203.0.113.7", None)] {
+        let mut request = http::Request::new(());
+        request.headers_mut().insert(TEST_IP, http::HeaderValue::from_static(ip));
+        let client_ip = service.clone().oneshot(request).await.unwrap().into_body();
+        assert_eq!(client_ip, expected, "{ip}");
+    }
+    assert!(logs_contain("client.address=10.1.2.3"));
+    assert!(logs_contain("client.address=2001:db8::7"));
+    assert!(!logs_contain("client.address=203.0.113.7"));
+}
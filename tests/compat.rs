@@ -0,0 +1,64 @@
+//Guarantees that code written against fn pointer based API keeps compiling without changes
+use tower_http_tracing::{DefaultHttpRequestLayer, HttpRequestLayer, MakeSpan};
+
+use tower::{Layer, ServiceExt};
+
+mod readme {
+    //Example from README.md verbatim
+    #![allow(unused)]
+    use std::net::IpAddr;
+
+    use tower_http_tracing::{http, HttpRequestLayer};
+
+    #[derive(Clone)]
+    pub struct MyContext;
+
+    impl tower_http_tracing::LayerContext for MyContext {
+        const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[&http::header::FORWARDED];
+
+        //Logic to extract client ip has to be written by user
+        //You can use utilities in separate crate to design this logic:
+        //https://docs.rs/http-ip/latest/http_ip/
+        fn extract_client_ip(&self, span: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
+            None
+        }
+    }
+
+    pub fn layer() -> HttpRequestLayer<MyContext> {
+        tower_http_tracing::make_request_spanner!(make_my_request_span("my_request", tracing::Level::INFO));
+        let layer = HttpRequestLayer::new(make_my_request_span, MyContext);
+        //Use above layer in your service
+        layer
+    }
+}
+
+tower_http_tracing::make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+struct Config {
+    make_span: MakeSpan,
+    layer: DefaultHttpRequestLayer,
+}
+
+fn make_layer(make_span: MakeSpan) -> HttpRequestLayer {
+    HttpRequestLayer::new_simple(make_span)
+}
+
+#[tokio::test]
+async fn should_keep_fn_pointer_api() {
+    let config = Config {
+        make_span: my_span,
+        layer: make_layer(my_span),
+    };
+    let layers: [DefaultHttpRequestLayer; 2] = [config.layer, HttpRequestLayer::new_simple(config.make_span)];
+    for layer in layers {
+        let service = layer.layer(tower::service_fn(|_: http::Request<()>| async move {
+            Ok::<_, std::io::Error>(http::Response::new(()))
+        }));
+        service.oneshot(http::Request::new(())).await.unwrap();
+    }
+
+    let service = readme::layer().layer(tower::service_fn(|_: http::Request<()>| async move {
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    }));
+    service.oneshot(http::Request::new(())).await.unwrap();
+}
@@ -0,0 +1,66 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+fn extract_connection_id(parts: &http::request::Parts) -> Option<u64> {
+    parts.headers.get("x-connection-id")?.to_str().ok()?.parse().ok()
+}
+
+async fn request(layer: &HttpRequestLayer, connection_id: Option<&'static str>) {
+    let service = ServiceBuilder::new().layer(layer.clone()).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    if let Some(connection_id) = connection_id {
+        req.headers_mut().insert("x-connection-id", http::HeaderValue::from_static(connection_id));
+    }
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_count_requests_per_connection() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_extract_connection_id(extract_connection_id);
+
+    request(&layer, Some("1")).await;
+    assert!(logs_contain("network.connection.id=1 network.connection.requests_seen=1"));
+    request(&layer, Some("1")).await;
+    assert!(logs_contain("network.connection.id=1 network.connection.requests_seen=2"));
+    request(&layer, Some("2")).await;
+    assert!(logs_contain("network.connection.id=2 network.connection.requests_seen=1"));
+    assert!(!logs_contain("network.connection.id=2 network.connection.requests_seen=2"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_without_connection_id() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_extract_connection_id(extract_connection_id);
+
+    request(&layer, None).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("network.connection"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_evict_least_recently_used_connection() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_extract_connection_id(extract_connection_id).with_connection_tracking_capacity(2);
+
+    request(&layer, Some("1")).await;
+    request(&layer, Some("2")).await;
+    request(&layer, Some("1")).await;
+    assert!(logs_contain("network.connection.id=1 network.connection.requests_seen=2"));
+    //Evicts connection 2
+    request(&layer, Some("3")).await;
+    request(&layer, Some("2")).await;
+    assert!(!logs_contain("network.connection.id=2 network.connection.requests_seen=2"));
+    //Connection 1 is evicted by re-appearing connection 2
+    request(&layer, Some("3")).await;
+    assert!(logs_contain("network.connection.id=3 network.connection.requests_seen=2"));
+    request(&layer, Some("1")).await;
+    assert!(!logs_contain("network.connection.id=1 network.connection.requests_seen=3"));
+}
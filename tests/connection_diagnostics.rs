@@ -0,0 +1,72 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+async fn respond_with(layer: HttpRequestLayer<ResponseLogContext>, headers: &'static [(http::HeaderName, &'static str)]) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        for (name, value) in headers {
+            response.headers_mut().append(name, http::HeaderValue::from_static(value));
+        }
+        Ok::<_, std::io::Error>(response)
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+}
+
+fn layer() -> HttpRequestLayer<ResponseLogContext> {
+    HttpRequestLayer::new(my_span, ResponseLogContext).with_connection_diagnostics()
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_detect_connection_close_in_list() {
+    respond_with(layer(), &[(http::header::CONNECTION, "keep-alive, close")]).await;
+    assert!(logs_contain("http.response.connection_close=true"));
+    assert!(!logs_contain("http.response.alt_svc.present"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_detect_connection_close_case_insensitive() {
+    respond_with(layer(), &[(http::header::CONNECTION, "Upgrade"), (http::header::CONNECTION, " CLOSE ")]).await;
+    assert!(logs_contain("http.response.connection_close=true"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_match_partial_connection_token() {
+    respond_with(layer(), &[(http::header::CONNECTION, "closed, keep-alive-close")]).await;
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.response.connection_close"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_detect_alt_svc() {
+    respond_with(layer(), &[(http::header::ALT_SVC, r#"h3=":443"; ma=86400"#)]).await;
+    assert!(logs_contain("http.response.alt_svc.present=true"));
+    assert!(!logs_contain("http.response.connection_close"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_connection_diagnostics_by_default() {
+    respond_with(HttpRequestLayer::new(my_span, ResponseLogContext), &[(http::header::CONNECTION, "close"), (http::header::ALT_SVC, "clear")]).await;
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.response.connection_close"));
+    assert!(!logs_contain("http.response.alt_svc.present"));
+}
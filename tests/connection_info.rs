@@ -0,0 +1,88 @@
+use tower_http_tracing::{make_request_spanner, ConnectionInfo, HttpRequestLayer, IpPrivacy, LayerContext};
+
+use std::net::{IpAddr, SocketAddr};
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const TEST_IP: http::HeaderName = http::HeaderName::from_static("x-test-ip");
+
+#[derive(Copy, Clone)]
+struct HeaderIpContext;
+
+impl LayerContext for HeaderIpContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn extract_client_ip(&self, _: &tracing::Span, parts: &http::request::Parts) -> Option<IpAddr> {
+        parts.headers.get(TEST_IP)?.to_str().ok()?.parse().ok()
+    }
+}
+
+async fn request(layer: HttpRequestLayer<HeaderIpContext>, client_ip: Option<&'static str>, connection: Option<ConnectionInfo>) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    if let Some(client_ip) = client_ip {
+        req.headers_mut().insert(TEST_IP, http::HeaderValue::from_static(client_ip));
+    }
+    if let Some(connection) = connection {
+        req.extensions_mut().insert(connection);
+    }
+    service.oneshot(req).await.unwrap();
+}
+
+fn peer(addr: &str) -> ConnectionInfo {
+    ConnectionInfo::from(addr.parse::<SocketAddr>().unwrap())
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_peer_as_client() {
+    request(HttpRequestLayer::new(my_span, HeaderIpContext), Some("203.0.113.7"), Some(peer("203.0.113.7:54321"))).await;
+    assert!(logs_contain(r#"client.address=203.0.113.7 client.address.family="ipv4" client.port=54321 network.peer.address=203.0.113.7 network.peer.port=54321 network.protocol.name="#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_client_port_of_proxy() {
+    request(HttpRequestLayer::new(my_span, HeaderIpContext), Some("203.0.113.7"), Some(peer("10.0.0.1:54321"))).await;
+    assert!(logs_contain(r#"client.address=203.0.113.7 client.address.family="ipv4" network.peer.address=10.0.0.1 network.peer.port=54321 network.protocol.name="#));
+    assert!(!logs_contain("client.port"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_local_address() {
+    let connection = ConnectionInfo {
+        peer: "[::ffff:10.0.0.1]:54321".parse().unwrap(),
+        local: Some("[2001:db8::1]:8080".parse().unwrap()),
+    };
+    request(HttpRequestLayer::new(my_span, HeaderIpContext), None, Some(connection)).await;
+    assert!(logs_contain("network.peer.address=10.0.0.1 network.peer.port=54321 network.local.address=2001:db8::1 network.local.port=8080 network.protocol.name="));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_apply_privacy_to_peer_address() {
+    let layer = HttpRequestLayer::new(my_span, HeaderIpContext).with_client_ip_privacy(IpPrivacy::Truncate);
+    request(layer, Some("203.0.113.7"), Some(peer("203.0.113.7:54321"))).await;
+    assert!(logs_contain("client.address=203.0.113.0"));
+    assert!(logs_contain("client.port=54321 network.peer.address=203.0.113.0 network.peer.port=54321 network.protocol.name="));
+
+    let layer = HttpRequestLayer::new(my_span, HeaderIpContext).with_client_ip_privacy(IpPrivacy::Hash(|_| "hashed".to_owned()));
+    request(layer, None, Some(peer("203.0.113.7:54321"))).await;
+    assert!(logs_contain(r#"network.peer.address="hashed" network.peer.port=54321 network.protocol.name="#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_peer_without_connection_info() {
+    request(HttpRequestLayer::new(my_span, HeaderIpContext), Some("203.0.113.7"), None).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("client.port"));
+    assert!(!logs_contain("network.peer"));
+    assert!(!logs_contain("network.local"));
+}
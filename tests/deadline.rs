@@ -0,0 +1,88 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestInfo};
+use tower_http_tracing::clock::{Clock, ManualClock};
+
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn request_deadline(layer: HttpRequestLayer, request: http::Request<()>) -> Option<Instant> {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        tracing::info!("LOG");
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.deadline))
+    });
+
+    service.oneshot(request).await.unwrap().into_body()
+}
+
+fn grpc_request(timeout: Option<&'static str>) -> http::Request<()> {
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static("application/grpc"));
+    if let Some(timeout) = timeout {
+        request.headers_mut().insert(tower_http_tracing::grpc::GRPC_TIMEOUT, http::HeaderValue::from_static(timeout));
+    }
+    request
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_compute_grpc_deadline() {
+    let clock = Arc::new(ManualClock::new(SystemTime::now()));
+    let layer = HttpRequestLayer::new_simple(my_span).with_clock(clock.clone()).with_default_timeout_budget(Duration::from_secs(10));
+
+    let deadline = request_deadline(layer, grpc_request(Some("250m"))).await;
+    assert_eq!(deadline, Some(clock.instant() + Duration::from_millis(250)));
+    assert!(logs_contain("http.request.timeout_budget_ms=250"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_compute_grpc_deadline_without_timeout() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_default_timeout_budget(Duration::from_secs(10));
+
+    assert_eq!(request_deadline(layer.clone(), grpc_request(None)).await, None);
+    assert_eq!(request_deadline(layer, grpc_request(Some("123456789S"))).await, None);
+    assert!(logs_contain("LOG"));
+    assert!(!logs_contain("http.request.timeout_budget_ms"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_compute_http_deadline_from_default() {
+    let clock = Arc::new(ManualClock::new(SystemTime::now()));
+    clock.advance(Duration::from_secs(1));
+    let layer = HttpRequestLayer::new_simple(my_span).with_clock(clock.clone()).with_default_timeout_budget(Duration::from_millis(1500));
+
+    let deadline = request_deadline(layer, http::Request::new(())).await;
+    assert_eq!(deadline, Some(clock.instant() + Duration::from_millis(1500)));
+    assert!(logs_contain("http.request.timeout_budget_ms=1500"));
+}
+
+#[tokio::test]
+async fn should_not_compute_http_deadline_by_default() {
+    assert_eq!(request_deadline(HttpRequestLayer::new_simple(my_span), http::Request::new(())).await, None);
+}
+
+#[tokio::test]
+async fn should_compute_remaining_time() {
+    let service = |layer: HttpRequestLayer| ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.clone()))
+    });
+
+    let info = service(HttpRequestLayer::new_simple(my_span)).oneshot(http::Request::new(())).await.unwrap().into_body();
+    assert_eq!(info.remaining(), None);
+
+    let clock = Arc::new(ManualClock::new(SystemTime::now()));
+    let layer = HttpRequestLayer::new_simple(my_span).with_clock(clock.clone()).with_default_timeout_budget(Duration::from_millis(1500));
+    let info = service(layer).oneshot(http::Request::new(())).await.unwrap().into_body();
+    assert_eq!(info.remaining(), Some(Duration::from_millis(1500)));
+
+    clock.advance(Duration::from_millis(1000));
+    assert_eq!(info.remaining(), Some(Duration::from_millis(500)));
+
+    clock.advance(Duration::from_millis(1000));
+    assert_eq!(info.remaining(), Some(Duration::ZERO));
+}
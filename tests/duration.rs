@@ -0,0 +1,78 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestInfo};
+use tower_http_tracing::clock::{Clock, ManualClock};
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use tower::{ServiceBuilder, ServiceExt};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+//Captures recorded `http.server.request.duration`
+#[derive(Clone, Default)]
+struct DurationCapture(Arc<Mutex<Vec<f64>>>);
+
+struct DurationVisitor<'a>(&'a mut Vec<f64>);
+
+impl tracing::field::Visit for DurationVisitor<'_> {
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        if field.name() == "http.server.request.duration" {
+            self.0.push(value);
+        }
+    }
+
+    fn record_debug(&mut self, _: &tracing::field::Field, _: &dyn core::fmt::Debug) {
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for DurationCapture {
+    fn on_record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        values.record(&mut DurationVisitor(&mut self.0.lock().unwrap()));
+    }
+}
+
+impl DurationCapture {
+    fn take(&self) -> Vec<f64> {
+        core::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+#[tokio::test]
+async fn should_record_duration_of_response() {
+    let capture = DurationCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|_: http::Request<()>| async move {
+        std::thread::sleep(Duration::from_millis(10));
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+    service.oneshot(http::Request::new(())).await.unwrap();
+
+    let durations = capture.take();
+    assert_eq!(durations.len(), 1);
+    assert!(durations[0] >= 0.01, "duration={}", durations[0]);
+}
+
+#[tokio::test]
+async fn should_record_duration_of_error() {
+    let capture = DurationCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let clock = Arc::new(ManualClock::new(SystemTime::now()));
+    let handler_clock = clock.clone();
+    let layer = HttpRequestLayer::new_simple(my_span).with_clock(clock.clone());
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |req: http::Request<()>| {
+        let clock = handler_clock.clone();
+        async move {
+            let started = req.extensions().get::<RequestInfo>().unwrap().started;
+            clock.advance(Duration::from_millis(1500));
+            assert_eq!(clock.instant().duration_since(started), Duration::from_millis(1500));
+            Err::<http::Response<()>, _>(std::io::Error::other("failure"))
+        }
+    });
+    service.oneshot(http::Request::new(())).await.unwrap_err();
+
+    assert_eq!(capture.take(), [1.5]);
+}
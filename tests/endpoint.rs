@@ -0,0 +1,56 @@
+use tower_http_tracing::{make_request_spanner, Endpoint, HttpRequestLayer, LayerContext};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const PUBLIC: Endpoint = Endpoint { name: "public", port: 443 };
+const PARTNER: Endpoint = Endpoint { name: "partner", port: 8443 };
+
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+async fn request(endpoint: Option<Endpoint>) {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_extract_endpoint(Endpoint::from_extensions);
+    let service = ServiceBuilder::new().map_request(move |mut req: http::Request<()>| {
+        if let Some(endpoint) = endpoint {
+            req.extensions_mut().insert(endpoint);
+        }
+        req
+    }).layer(layer).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_public_endpoint() {
+    request(Some(PUBLIC)).await;
+    assert!(logs_contain(r#"server.endpoint="public" server.port=443"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_partner_endpoint() {
+    request(Some(PARTNER)).await;
+    assert!(logs_contain(r#"server.endpoint="partner" server.port=8443"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_missing_endpoint() {
+    request(None).await;
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("server.endpoint"));
+    assert!(!logs_contain("server.port"));
+}
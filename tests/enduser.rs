@@ -0,0 +1,57 @@
+use tower_http_tracing::{make_request_spanner, AuthenticatedUser, HttpRequestLayer, LayerContext, SpanHandle};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+//Logs event once response fields are recorded
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_user_from_response_extensions() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        response.extensions_mut().insert(AuthenticatedUser("user-1".to_owned()));
+        Ok::<_, core::convert::Infallible>(response)
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+    assert!(logs_contain(r#"enduser.id="user-1""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_user_reported_by_handler() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|req: http::Request<()>| async move {
+        req.extensions().get::<SpanHandle>().unwrap().record_user("user-2");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+    assert!(logs_contain(r#"enduser.id="user-2""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_user_without_authentication() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("enduser.id"));
+}
@@ -0,0 +1,209 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+use tower_http_tracing::error::ErrorCategorizer;
+
+use core::fmt;
+use std::io;
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[derive(Copy, Clone)]
+struct ErrorLogContext;
+
+impl LayerContext for ErrorLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_error(&self, _: &tracing::Span, _: &impl std::error::Error) {
+        tracing::info!("ERROR");
+    }
+}
+
+#[derive(Debug)]
+struct HandlerError(io::Error);
+
+impl fmt::Display for HandlerError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("handler failed")
+    }
+}
+
+impl std::error::Error for HandlerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+async fn failed_request(layer: HttpRequestLayer<ErrorLogContext>, kind: io::ErrorKind) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| async move {
+        Err::<http::Response<()>, _>(HandlerError(io::Error::from(kind)))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap_err();
+}
+
+#[test]
+fn should_detect_client_disconnect_in_source_chain() {
+    assert!(tower_http_tracing::is_client_disconnect(&HandlerError(io::Error::from(io::ErrorKind::BrokenPipe))));
+    assert!(tower_http_tracing::is_client_disconnect(&HandlerError(io::Error::from(io::ErrorKind::ConnectionReset))));
+    assert!(tower_http_tracing::is_client_disconnect(&io::Error::from(io::ErrorKind::ConnectionReset)));
+    assert!(!tower_http_tracing::is_client_disconnect(&HandlerError(io::Error::from(io::ErrorKind::NotFound))));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_benign_error() {
+    let layer = HttpRequestLayer::new(my_span, ErrorLogContext).with_benign_error_filter(tower_http_tracing::is_client_disconnect);
+    failed_request(layer, io::ErrorKind::BrokenPipe).await;
+
+    assert!(logs_contain(r#"http.response.status_code=499 error.type="client_disconnect"}: error: ERROR"#));
+    assert!(!logs_contain("error.message"));
+}
+
+//Captures level and message of access log events
+#[derive(Clone, Default)]
+struct AccessLogCapture(std::sync::Arc<std::sync::Mutex<Vec<(tracing::Level, String)>>>);
+
+struct MessageVisitor<'a>(&'a mut String);
+
+impl tracing::field::Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.push_str(&format!("{value:?}"));
+        }
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for AccessLogCapture {
+    fn on_event(&self, event: &tracing::Event<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        if event.metadata().target() == tower_http_tracing::targets::ACCESS {
+            let mut message = String::new();
+            event.record(&mut MessageVisitor(&mut message));
+            self.0.lock().unwrap().push((*event.metadata().level(), message));
+        }
+    }
+}
+
+async fn access_log(kind: io::ErrorKind) -> Vec<(tracing::Level, String)> {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let capture = AccessLogCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+    let layer = HttpRequestLayer::new(my_span, ErrorLogContext).with_benign_error_filter(tower_http_tracing::is_client_disconnect)
+                                                               .with_access_log(true);
+    failed_request(layer, kind).await;
+    core::mem::take(&mut *capture.0.lock().unwrap())
+}
+
+#[tokio::test]
+async fn should_log_benign_error_at_info() {
+    let events = access_log(io::ErrorKind::ConnectionReset).await;
+    assert_eq!(events.len(), 1, "{events:?}");
+    assert_eq!(events[0].0, tracing::Level::INFO);
+    assert!(events[0].1.starts_with("GET / http 499 client_disconnect "), "{events:?}");
+
+    let events = access_log(io::ErrorKind::PermissionDenied).await;
+    assert_eq!(events.len(), 1, "{events:?}");
+    assert_eq!(events[0].0, tracing::Level::ERROR);
+    assert!(events[0].1.starts_with("GET / http 500 error "), "{events:?}");
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_non_benign_error() {
+    let layer = HttpRequestLayer::new(my_span, ErrorLogContext).with_benign_error_filter(tower_http_tracing::is_client_disconnect);
+    failed_request(layer, io::ErrorKind::PermissionDenied).await;
+
+    assert!(logs_contain(r#"http.response.status_code=500 error.type="error::HandlerError" error.message=handler failed}: error: ERROR"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_error_without_filter() {
+    let layer = HttpRequestLayer::new(my_span, ErrorLogContext);
+    failed_request(layer, io::ErrorKind::BrokenPipe).await;
+
+    assert!(logs_contain(r#"http.response.status_code=500 error.type="error::HandlerError" error.message=handler failed}: error: ERROR"#));
+}
+
+#[derive(Debug)]
+enum AppError {
+    Database,
+    Validation,
+    Unknown,
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("application failed")
+    }
+}
+
+impl std::error::Error for AppError {
+}
+
+fn app_error(error: &(dyn std::error::Error + 'static)) -> Option<&'static str> {
+    match error.downcast_ref::<BoxedError>()?.0.downcast_ref::<AppError>()? {
+        AppError::Database => Some("database"),
+        AppError::Validation => Some("validation"),
+        AppError::Unknown => None,
+    }
+}
+
+async fn failed_boxed_request(error: Box<dyn std::error::Error + Send + Sync>) {
+    let categorizer = ErrorCategorizer::new().with_probe(app_error).with_probe(tower_http_tracing::error::io_error);
+    let layer = HttpRequestLayer::new(my_span, ErrorLogContext).with_error_categorizer(categorizer);
+    let error = std::sync::Mutex::new(Some(error));
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| {
+        let error = error.lock().unwrap().take().unwrap();
+        async move {
+            Err::<http::Response<()>, _>(BoxedError(error))
+        }
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap_err();
+}
+
+#[derive(Debug)]
+struct BoxedError(Box<dyn std::error::Error + Send + Sync>);
+
+impl fmt::Display for BoxedError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, fmt)
+    }
+}
+
+impl std::error::Error for BoxedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_categorize_database_error() {
+    failed_boxed_request(Box::new(AppError::Database)).await;
+    assert!(logs_contain(r#"error.type="error::BoxedError" error.category="database" error.message=application failed"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_categorize_validation_error() {
+    failed_boxed_request(Box::new(AppError::Validation)).await;
+    assert!(logs_contain(r#"error.category="validation""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_fall_through_to_next_probe() {
+    failed_boxed_request(Box::new(io::Error::from(io::ErrorKind::PermissionDenied))).await;
+    assert!(logs_contain(r#"error.category="io.permission_denied""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_unknown_category() {
+    failed_boxed_request(Box::new(AppError::Unknown)).await;
+    assert!(logs_contain("ERROR"));
+    assert!(!logs_contain("error.category"));
+}
@@ -0,0 +1,137 @@
+#[allow(dead_code)]
+#[path = "../examples/common/mod.rs"]
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tower::ServiceExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Default)]
+struct Captured {
+    fields: HashMap<String, String>,
+    events: Vec<String>,
+}
+
+//Captures fields of request spans and messages of events in order of emission
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Captured>>);
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl tracing::field::Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.insert(field.name().to_owned(), value.to_owned());
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+        self.0.insert(field.name().to_owned(), format!("{value:?}"));
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for Capture {
+    fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, _: &tracing::span::Id, _: tracing_subscriber::layer::Context<'_, S>) {
+        attrs.record(&mut FieldVisitor(&mut self.0.lock().unwrap().fields));
+    }
+
+    fn on_record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        values.record(&mut FieldVisitor(&mut self.0.lock().unwrap().fields));
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        //Skip middleware's own diagnostics, which depend on enabled features
+        if !event.metadata().target().starts_with(module_path!()) {
+            return;
+        }
+        let mut fields = HashMap::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        if let Some(message) = fields.remove("message") {
+            self.0.lock().unwrap().events.push(message);
+        }
+    }
+}
+
+impl Capture {
+    fn take(&self) -> Captured {
+        core::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+#[tokio::test]
+async fn should_trace_example_router_end_to_end() {
+    let capture = Capture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+    let router = common::router();
+
+    let request = http::Request::get("/hello").header(tower_http_tracing::X_FORWARDED_FOR, "203.0.113.7, 10.0.0.1")
+                                              .body(axum::body::Body::empty())
+                                              .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let Captured { fields, events } = capture.take();
+    assert_eq!(events, ["hook: on_request", "handler: hello", "hook: on_response_ok", "hook: on_complete"]);
+    assert_eq!(fields["http.request.method"], "GET");
+    assert_eq!(fields["url.path"], "/hello");
+    assert_eq!(fields["client.address"], "203.0.113.7");
+    assert_eq!(fields["network.protocol.name"], "http");
+    assert_eq!(fields["http.response.status_code"], "200");
+    assert!(fields.contains_key("http.request_id"));
+
+    let response = router.oneshot(common::grpc_health_check_request("")).await.unwrap();
+    assert_eq!(response.headers()[tower_http_tracing::grpc::GRPC_STATUS], "0");
+
+    let Captured { fields, events } = capture.take();
+    assert_eq!(events, ["hook: on_request", "handler: grpc_health_check", "hook: on_response_ok", "hook: on_complete"]);
+    assert_eq!(fields["http.request.method"], "POST");
+    assert_eq!(fields["url.path"], common::GRPC_HEALTH_CHECK);
+    assert_eq!(fields["network.protocol.name"], "grpc");
+    assert_eq!(fields["http.request.timeout_budget_ms"], "1000");
+    assert_eq!(fields["http.response.status_code"], "0");
+    assert!(!fields.contains_key("client.address"));
+}
+
+#[tokio::test]
+async fn should_trace_tonic_service_end_to_end() {
+    let capture = Capture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    //Unknown service is rejected with trailers-only response, so status is available with response's head
+    let response = common::tonic_health_service().oneshot(common::grpc_health_check_request("unknown")).await.unwrap();
+    assert_eq!(response.headers()[tower_http_tracing::grpc::GRPC_STATUS], "5");
+
+    let Captured { fields, events } = capture.take();
+    assert_eq!(events, ["hook: on_request", "hook: on_response_ok", "hook: on_complete"]);
+    assert_eq!(fields["http.request.method"], "POST");
+    assert_eq!(fields["url.path"], common::GRPC_HEALTH_CHECK);
+    assert_eq!(fields["network.protocol.name"], "grpc");
+    assert_eq!(fields["rpc.service"], "grpc.health.v1.Health");
+    assert_eq!(fields["rpc.method"], "Check");
+    assert_eq!(fields["http.request.timeout_budget_ms"], "1000");
+    assert_eq!(fields["http.response.status_code"], "5");
+}
+
+#[tokio::test]
+async fn should_trace_tonic_status_from_trailers() {
+    use http_body_util::BodyExt;
+
+    let capture = Capture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    //Status of successful call is only known once trailers are yielded by response's body
+    let response = common::tonic_health_service().oneshot(common::grpc_health_check_request("")).await.unwrap();
+    assert!(!response.headers().contains_key(tower_http_tracing::grpc::GRPC_STATUS));
+    {
+        let captured = capture.0.lock().unwrap();
+        assert!(!captured.fields.contains_key("http.response.status_code"));
+        assert_eq!(captured.events, ["hook: on_request", "hook: on_response_ok"]);
+    }
+
+    let body = response.into_body().collect().await.unwrap();
+    assert_eq!(body.trailers().unwrap()[tower_http_tracing::grpc::GRPC_STATUS], "0");
+    let Captured { fields, events } = capture.take();
+    assert_eq!(events, ["hook: on_request", "hook: on_response_ok", "hook: on_complete"]);
+    assert_eq!(fields["http.response.status_code"], "0");
+}
@@ -0,0 +1,74 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO,
+    experiment.e1 = tracing::field::Empty,
+    experiment.e2 = tracing::field::Empty,
+    experiment.e3 = tracing::field::Empty,
+    experiment.e4 = tracing::field::Empty,
+    experiment.e5 = tracing::field::Empty,
+    experiment.e6 = tracing::field::Empty,
+    experiment.e7 = tracing::field::Empty,
+    experiment.e8 = tracing::field::Empty,
+    experiment.e9 = tracing::field::Empty,
+    experiment.checkout_v2 = tracing::field::Empty,
+    experiment.search_rank = tracing::field::Empty,
+));
+
+const EXPERIMENTS: http::HeaderName = http::HeaderName::from_static("x-experiment-buckets");
+
+#[derive(Copy, Clone)]
+struct Context;
+
+impl LayerContext for Context {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+}
+
+async fn request(names: &'static [&'static str], value: &'static str) {
+    let layer = HttpRequestLayer::new(my_span, Context).with_experiment_header(EXPERIMENTS, names);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    req.headers_mut().insert(EXPERIMENTS, http::HeaderValue::from_static(value));
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_allowed_experiments() {
+    request(&["checkout_v2", "search_rank"], "checkout_v2=treatment; unknown=value;search_rank = control").await;
+
+    assert!(logs_contain(r#"experiment.checkout_v2="treatment" experiment.search_rank="control""#));
+    assert!(!logs_contain("unknown"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_skip_not_allowed_and_malformed_experiments() {
+    request(&["checkout_v2", "search_rank"], "search_rank;checkout_v2=;=control;search_rank=control").await;
+
+    assert!(logs_contain(r#"experiment.search_rank="control""#));
+    assert!(!logs_contain("experiment.checkout_v2"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_cap_experiment_value() {
+    request(&["checkout_v2"], "checkout_v2=0123456789abcdef0123456789abcdefOVERFLOW").await;
+
+    assert!(logs_contain(r#"experiment.checkout_v2="0123456789abcdef0123456789abcdef""#));
+    assert!(!logs_contain("OVERFLOW"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_cap_experiment_count() {
+    request(&["e1", "e2", "e3", "e4", "e5", "e6", "e7", "e8", "e9"], "e1=a;e2=a;e3=a;e4=a;e5=a;e6=a;e7=a;e8=a;e9=a").await;
+
+    assert!(logs_contain(r#"experiment.e8="a""#));
+    assert!(!logs_contain("experiment.e9"));
+}
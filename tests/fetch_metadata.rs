@@ -0,0 +1,46 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn request(layer: HttpRequestLayer, headers: &[(&'static str, &'static str)]) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    for (name, value) in headers {
+        req.headers_mut().insert(*name, http::HeaderValue::from_static(value));
+    }
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_cross_site_navigation() {
+    let headers = [("sec-fetch-site", "cross-site"), ("sec-fetch-mode", "navigate"), ("sec-fetch-dest", "document"), ("sec-fetch-user", "?1")];
+    request(HttpRequestLayer::new_simple(my_span).with_record_fetch_metadata(), &headers).await;
+
+    assert!(logs_contain(r#"http.request.header.sec-fetch-site="cross-site" http.request.header.sec-fetch-mode="navigate" http.request.header.sec-fetch-dest="document" http.request.header.sec-fetch-user="?1""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_same_origin_fetch() {
+    let headers = [("sec-fetch-site", "Same-Origin"), ("sec-fetch-mode", "cors"), ("sec-fetch-dest", "unknown-destination")];
+    request(HttpRequestLayer::new_simple(my_span).with_record_fetch_metadata(), &headers).await;
+
+    assert!(logs_contain(r#"http.request.header.sec-fetch-site="same-origin" http.request.header.sec-fetch-mode="cors" http.request.header.sec-fetch-dest="other""#));
+    assert!(!logs_contain("http.request.header.sec-fetch-user"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_fetch_metadata_by_default() {
+    request(HttpRequestLayer::new_simple(my_span), &[("sec-fetch-site", "cross-site")]).await;
+
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("sec-fetch-site"));
+}
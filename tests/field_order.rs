@@ -0,0 +1,212 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext, RequestIdPolicy, ResponseRequestIdConflict, REQUEST_SPAN_FIELDS};
+use tower_http_tracing::error::ErrorCategorizer;
+use tower_http_tracing::body::{BodyHasher, BodyLayer, RequestBody};
+use tower_http_tracing::resource::ThreadLocalTracker;
+
+use core::pin::Pin;
+use core::task;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use tower::{Layer, Service};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+type Fields = Arc<Mutex<Vec<&'static str>>>;
+
+//Collects names of span's fields in order they are populated
+struct FieldOrder(Fields);
+
+struct FieldVisitor<'a>(&'a mut Vec<&'static str>);
+
+impl tracing::field::Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, _: &dyn core::fmt::Debug) {
+        self.0.push(field.name());
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for FieldOrder {
+    fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, _: &tracing::span::Id, _: tracing_subscriber::layer::Context<'_, S>) {
+        attrs.record(&mut FieldVisitor(&mut self.0.lock().unwrap()));
+    }
+
+    fn on_record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        values.record(&mut FieldVisitor(&mut self.0.lock().unwrap()));
+    }
+}
+
+#[derive(Copy, Clone)]
+struct FullContext;
+
+impl LayerContext for FullContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[&http::header::HOST];
+
+    fn extract_client_ip(&self, _: &tracing::Span, _: &http::request::Parts) -> Option<IpAddr> {
+        Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    }
+}
+
+struct ZeroHasher;
+
+impl BodyHasher for ZeroHasher {
+    fn update(&mut self, _: &[u8]) {
+    }
+
+    fn finish(self: Box<Self>) -> [u8; 32] {
+        [0; 32]
+    }
+}
+
+//Service that becomes ready on second poll and responds with every response field populated
+struct FullService {
+    ready: bool,
+    fail: bool,
+}
+
+impl Service<http::Request<RequestBody<Full<Bytes>>>> for FullService {
+    type Response = http::Response<()>;
+    type Error = std::io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, _: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        if self.ready {
+            task::Poll::Ready(Ok(()))
+        } else {
+            self.ready = true;
+            task::Poll::Pending
+        }
+    }
+
+    fn call(&mut self, req: http::Request<RequestBody<Full<Bytes>>>) -> Self::Future {
+        let fail = self.fail;
+        Box::pin(async move {
+            req.into_body().collect().await.unwrap();
+            if fail {
+                return Err(std::io::Error::other("failure"));
+            }
+            let mut response = http::Response::new(());
+            *response.status_mut() = http::StatusCode::TOO_MANY_REQUESTS;
+            response.extensions_mut().insert(tower_http_tracing::MatchedRoute("/path".to_owned()));
+            response.extensions_mut().insert(tower_http_tracing::AuthenticatedUser("user".to_owned()));
+            let headers = response.headers_mut();
+            headers.insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static("other-ID"));
+            headers.insert("ratelimit-limit", http::HeaderValue::from_static("10"));
+            headers.insert("ratelimit-remaining", http::HeaderValue::from_static("0"));
+            headers.insert(http::header::RETRY_AFTER, http::HeaderValue::from_static("5"));
+            headers.insert(http::header::CONNECTION, http::HeaderValue::from_static("close"));
+            headers.insert(http::header::ALT_SVC, http::HeaderValue::from_static("clear"));
+            headers.insert(http::header::CONTENT_LENGTH, http::HeaderValue::from_static("0"));
+            Ok(response)
+        })
+    }
+}
+
+async fn record_fields(body: &'static str, fail: bool) -> Vec<&'static str> {
+    let fields = Fields::default();
+    let _guard = tracing_subscriber::registry().with(FieldOrder(fields.clone())).set_default();
+
+    let layer = HttpRequestLayer::new(my_span, FullContext).with_default_timeout_budget(Duration::from_secs(1))
+                                                           .with_record_ready_wait()
+                                                           .with_extract_stream_info(|_| Some(1))
+                                                           .with_extract_connection_id(|_| Some(2))
+                                                           .with_extract_endpoint(|_| Some(tower_http_tracing::Endpoint { name: "public", port: 443 }))
+                                                           .with_request_fingerprint()
+                                                           .with_record_forwarded_for_first()
+                                                           .with_record_full_url(true)
+                                                           .with_record_trace_ids()
+                                                           .with_record_fetch_metadata()
+                                                           .with_request_id_policy(RequestIdPolicy::AlwaysGenerate)
+                                                           .with_response_request_id_conflict(ResponseRequestIdConflict::KeepExisting)
+                                                           .with_correlation_id_header(http::HeaderName::from_static("x-correlation-id"))
+                                                           .with_peer_service_header(http::HeaderName::from_static("x-client-service"))
+                                                           .with_retry_header(http::HeaderName::from_static("x-retry-attempt"))
+                                                           .with_connection_diagnostics()
+                                                           .with_inspect_cookies(&["locale"])
+                                                           .with_session_cookie("session")
+                                                           .with_inspect_response_headers(&[http::header::RETRY_AFTER])
+                                                           .with_response_header_allowlist(&[])
+                                                           .with_error_categorizer(ErrorCategorizer::new().with_probe(tower_http_tracing::error::io_error))
+                                                           .with_resource_tracker(ThreadLocalTracker);
+    let body_layer = BodyLayer::new().with_request_body_hash(8, || Box::new(ZeroHasher)).with_request_first_byte_delay();
+    let mut service = layer.layer(body_layer.layer(FullService { ready: false, fail }));
+
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    assert!(service.poll_ready(&mut ctx).is_pending());
+    assert!(service.poll_ready(&mut ctx).is_ready());
+
+    let mut req = http::Request::new(Full::new(Bytes::from_static(body.as_bytes())));
+    *req.uri_mut() = "http://localhost/path?query=1".parse().unwrap();
+    let headers = req.headers_mut();
+    headers.insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static("request-ID"));
+    headers.append(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static("duplicate-ID"));
+    headers.insert("x-correlation-id", http::HeaderValue::from_static("correlation-ID"));
+    headers.insert("x-client-service", http::HeaderValue::from_static("client"));
+    headers.insert("x-retry-attempt", http::HeaderValue::from_static("1"));
+    headers.insert(tower_http_tracing::IDEMPOTENCY_KEY, http::HeaderValue::from_static("key"));
+    headers.insert(http::header::USER_AGENT, http::HeaderValue::from_static("test/1.0"));
+    headers.insert(http::header::CONTENT_LENGTH, http::HeaderValue::from(body.len()));
+    headers.insert(http::header::COOKIE, http::HeaderValue::from_static("locale=en; session=abc"));
+    headers.insert(http::header::HOST, http::HeaderValue::from_static("localhost"));
+    headers.insert(tower_http_tracing::X_FORWARDED_FOR, http::HeaderValue::from_static("10.0.0.1"));
+    headers.insert(http::header::EXPECT, http::HeaderValue::from_static("100-continue"));
+    headers.insert("sec-fetch-site", http::HeaderValue::from_static("same-origin"));
+    headers.insert("sec-fetch-mode", http::HeaderValue::from_static("cors"));
+    headers.insert("sec-fetch-dest", http::HeaderValue::from_static("empty"));
+    headers.insert("sec-fetch-user", http::HeaderValue::from_static("?1"));
+    req.extensions_mut().insert(tower_http_tracing::ConnectionInfo {
+        peer: (Ipv4Addr::LOCALHOST, 54321).into(),
+        local: Some((Ipv4Addr::LOCALHOST, 8080).into()),
+    });
+    req.extensions_mut().insert(tower_http_tracing::TlsInfo {
+        protocol_version: "1.3",
+        cipher: Some("TLS13_AES_128_GCM_SHA256"),
+        sni: Some("localhost".to_owned()),
+    });
+    let _ = service.call(req).await;
+
+    fields.lock().unwrap().clone()
+}
+
+fn canonical_without(skipped: &[&str]) -> Vec<&'static str> {
+    REQUEST_SPAN_FIELDS.iter().copied().filter(|field| !skipped.contains(field)).collect()
+}
+
+#[tokio::test]
+async fn should_record_response_fields_in_canonical_order() {
+    let fields = record_fields("body", false).await;
+    assert_eq!(fields, canonical_without(&["http.request.method_original", "span_id", "http.request_id.rejected", "rpc.service", "rpc.method", "http.response.trailers", "url.length", "url.truncated", "http.request.body.hash_skipped", "error.type", "error.category", "error.message"]));
+}
+
+#[tokio::test]
+async fn should_record_error_fields_in_canonical_order() {
+    let fields = record_fields("too large body", true).await;
+    assert_eq!(fields, canonical_without(&[
+        "http.request.method_original",
+        "http.request_id.rejected",
+        "span_id",
+        "rpc.service",
+        "rpc.method",
+        "http.response.trailers",
+        "http.response.headers_stripped",
+        "url.length",
+        "url.truncated",
+        "http.request.body.sha256",
+        "http.route",
+        "enduser.id",
+        "http.request_id.response_mismatch",
+        "http.response.body.size",
+        "http.response.rate_limit.limit",
+        "http.response.rate_limit.remaining",
+        "http.response.rate_limit.retry_after_seconds",
+        "http.response.retry_after",
+        "http.response.ratelimit_remaining",
+        "http.response.connection_close",
+        "http.response.alt_svc.present",
+        "http.response.headers",
+    ]));
+}
@@ -0,0 +1,49 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+use tower_http_tracing::fingerprint::{compute, Components, Fingerprint};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+fn request(user_agent: &'static str) -> http::Request<()> {
+    http::Request::builder().uri("/users/42?query=1")
+                            .header(http::header::HOST, "localhost")
+                            .header(http::header::USER_AGENT, user_agent)
+                            .header(http::header::ACCEPT, "*/*")
+                            .body(())
+                            .unwrap()
+}
+
+fn parts(user_agent: &'static str) -> http::request::Parts {
+    request(user_agent).into_parts().0
+}
+
+#[test]
+fn should_pin_fingerprint_algorithm() {
+    //Values must never change for the same algorithm version
+    assert_eq!(compute(Components::ALL, &parts("curl/8.0")), Fingerprint(0x0bc770c868227b99));
+    assert_eq!(compute(Components::METHOD | Components::PATH, &parts("curl/8.0")), Fingerprint(0x2b36b853c76e8db1));
+    assert_eq!(compute(Components::NONE, &parts("curl/8.0")), Fingerprint(0xaf63bc4c8601b62c));
+    assert_eq!(compute(Components::ALL, &parts("curl/8.0")).to_string(), "0bc770c868227b99");
+}
+
+#[test]
+fn should_ignore_header_values_and_user_agent_version() {
+    let fingerprint = compute(Components::ALL, &parts("curl/8.0"));
+    assert_eq!(compute(Components::ALL, &parts("Curl/7.1 (linux)")), fingerprint);
+    assert_ne!(compute(Components::ALL, &parts("wget/1.0")), fingerprint);
+    assert_eq!(compute(Components::METHOD | Components::PATH | Components::HEADER_NAMES, &parts("wget/1.0")), compute(Components::METHOD | Components::PATH | Components::HEADER_NAMES, &parts("curl/8.0")));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_fingerprint() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_request_fingerprint();
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    service.oneshot(request("curl/8.0")).await.unwrap();
+    assert!(logs_contain("http.request.fingerprint=0bc770c868227b99"));
+}
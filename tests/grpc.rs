@@ -0,0 +1,195 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, Protocol, RequestSummary, ResponseStatus};
+use tower_http_tracing::body::{ResponseBody, ResponseBodyLayer};
+use tower_http_tracing::grpc::{resolve_grpc_status, parse_grpc_status, GRPC_STATUS, UNKNOWN};
+
+use core::pin::Pin;
+use core::task;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use tower::{ServiceBuilder, ServiceExt};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+fn headers_with_status(status: &'static str) -> http::HeaderMap {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(GRPC_STATUS, http::HeaderValue::from_static(status));
+    headers
+}
+
+#[test]
+fn should_parse_grpc_status() {
+    assert_eq!(parse_grpc_status(b"0"), 0);
+    assert_eq!(parse_grpc_status(b"16"), 16);
+    assert_eq!(parse_grpc_status(b"17"), UNKNOWN);
+    assert_eq!(parse_grpc_status(b"abc"), UNKNOWN);
+}
+
+#[test]
+fn should_detect_grpc_protocols() {
+    assert_eq!(Protocol::from_content_type(b"application/grpc"), Protocol::Grpc);
+    assert_eq!(Protocol::from_content_type(b"application/grpc+proto"), Protocol::Grpc);
+    assert_eq!(Protocol::from_content_type(b"application/grpc-web"), Protocol::GrpcWeb);
+    assert_eq!(Protocol::from_content_type(b"application/grpc-web-text+proto"), Protocol::GrpcWeb);
+    assert_eq!(Protocol::from_content_type(b"application/json"), Protocol::Http);
+}
+
+#[test]
+fn should_resolve_grpc_status_from_headers_first() {
+    for protocol in [Protocol::Grpc, Protocol::GrpcWeb] {
+        assert_eq!(resolve_grpc_status(protocol, &headers_with_status("5"), Some(&headers_with_status("7"))), 5);
+    }
+}
+
+#[test]
+fn should_resolve_grpc_status_from_trailers() {
+    for protocol in [Protocol::Grpc, Protocol::GrpcWeb] {
+        assert_eq!(resolve_grpc_status(protocol, &http::HeaderMap::new(), Some(&headers_with_status("7"))), 7);
+    }
+}
+
+#[test]
+fn should_resolve_unknown_grpc_status() {
+    for protocol in [Protocol::Grpc, Protocol::GrpcWeb] {
+        assert_eq!(resolve_grpc_status(protocol, &http::HeaderMap::new(), None), UNKNOWN);
+        assert_eq!(resolve_grpc_status(protocol, &http::HeaderMap::new(), Some(&http::HeaderMap::new())), UNKNOWN);
+    }
+    assert_eq!(resolve_grpc_status(Protocol::Http, &headers_with_status("0"), None), UNKNOWN);
+}
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+thread_local! {
+    static COMPLETED: RefCell<Vec<ResponseStatus>> = const { RefCell::new(Vec::new()) };
+}
+
+fn on_complete(_: &tracing::Span, summary: &RequestSummary) {
+    COMPLETED.with_borrow_mut(|completed| completed.push(summary.status));
+}
+
+fn take_completed() -> Vec<ResponseStatus> {
+    COMPLETED.with_borrow_mut(core::mem::take)
+}
+
+//Captures values of `http.response.status_code` as they are recorded
+#[derive(Clone, Default)]
+struct StatusCapture(Arc<Mutex<Vec<u64>>>);
+
+impl tracing::field::Visit for StatusCapture {
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        if field.name() == "http.response.status_code" {
+            self.0.lock().unwrap().push(value);
+        }
+    }
+
+    fn record_debug(&mut self, _: &tracing::field::Field, _: &dyn core::fmt::Debug) {
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for StatusCapture {
+    fn on_record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        values.record(&mut self.clone());
+    }
+}
+
+impl StatusCapture {
+    fn take(&self) -> Vec<u64> {
+        core::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+//Body yielding specified frames
+struct Frames(VecDeque<http_body::Frame<Bytes>>);
+
+impl http_body::Body for Frames {
+    type Data = Bytes;
+    type Error = core::convert::Infallible;
+
+    fn poll_frame(mut self: Pin<&mut Self>, _: &mut task::Context<'_>) -> task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        task::Poll::Ready(self.0.pop_front().map(Ok))
+    }
+}
+
+async fn respond(content_type: &'static str, headers: http::HeaderMap, frames: fn() -> Vec<http_body::Frame<Bytes>>) -> http::Response<ResponseBody<Frames>> {
+    let layer = HttpRequestLayer::new_simple(my_span).with_on_complete(on_complete);
+    let service = ServiceBuilder::new().layer(layer)
+                                       .layer(ResponseBodyLayer::new())
+                                       .service_fn(move |_: http::Request<()>| {
+        let headers = headers.clone();
+        async move {
+            let mut resp = http::Response::new(Frames(frames().into()));
+            *resp.headers_mut() = headers;
+            resp.headers_mut().insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static(content_type));
+            Ok::<_, std::io::Error>(resp)
+        }
+    });
+    let req = http::Request::builder().method(http::Method::POST).uri("/helloworld.Greeter/SayHello").header(http::header::CONTENT_TYPE, content_type).body(()).unwrap();
+    service.oneshot(req).await.unwrap()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_record_grpc_status_from_trailers() {
+    let capture = StatusCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let resp = respond("application/grpc", http::HeaderMap::new(), || vec![
+        http_body::Frame::data(Bytes::from_static(b"\0\0\0\0\x03abc")),
+        http_body::Frame::trailers(headers_with_status("0")),
+    ]).await;
+    assert!(capture.take().is_empty());
+    assert!(take_completed().is_empty());
+
+    resp.into_body().collect().await.unwrap();
+    assert_eq!(capture.take(), [0]);
+    assert_eq!(take_completed(), [ResponseStatus::Code(0)]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_record_grpc_status_from_headers() {
+    let capture = StatusCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let resp = respond("application/grpc", headers_with_status("5"), || vec![
+        http_body::Frame::trailers(headers_with_status("0")),
+    ]).await;
+    assert_eq!(capture.take(), [5]);
+    assert_eq!(take_completed(), [ResponseStatus::Code(5)]);
+
+    resp.into_body().collect().await.unwrap();
+    assert!(capture.take().is_empty());
+    assert!(take_completed().is_empty());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_record_grpc_web_status_from_trailers_frame() {
+    let capture = StatusCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let resp = respond("application/grpc-web+proto", http::HeaderMap::new(), || vec![
+        http_body::Frame::data(Bytes::from_static(b"\0\0\0\0\x03abc\x80\0\0")),
+        http_body::Frame::data(Bytes::from_static(b"\0\x21grpc-status:7\r\ngrpc-message:")),
+        http_body::Frame::data(Bytes::from_static(b" no\r\n")),
+    ]).await;
+    assert!(capture.take().is_empty());
+
+    resp.into_body().collect().await.unwrap();
+    assert_eq!(capture.take(), [7]);
+    assert_eq!(take_completed(), [ResponseStatus::Code(7)]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_record_unknown_grpc_status_once_body_ends_without_trailers() {
+    let capture = StatusCapture::default();
+    let _guard = tracing_subscriber::registry().with(capture.clone()).set_default();
+
+    let resp = respond("application/grpc", http::HeaderMap::new(), || vec![
+        http_body::Frame::data(Bytes::from_static(b"\0\0\0\0\x03abc")),
+    ]).await;
+    assert!(capture.take().is_empty());
+
+    resp.into_body().collect().await.unwrap();
+    assert_eq!(capture.take(), [UNKNOWN as u64]);
+    assert_eq!(take_completed(), [ResponseStatus::Code(UNKNOWN)]);
+}
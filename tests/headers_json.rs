@@ -0,0 +1,127 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+
+use std::sync::{Arc, Mutex};
+use tower::{ServiceBuilder, ServiceExt};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+type Captured = Arc<Mutex<Option<String>>>;
+
+//Captures recorded `http.headers` as is
+struct Capture(Captured);
+
+struct Visitor<'a>(&'a mut Option<String>);
+
+impl tracing::field::Visit for Visitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+        if field.name() == "http.headers" {
+            *self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for Capture {
+    fn on_record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        values.record(&mut Visitor(&mut self.0.lock().unwrap()));
+    }
+}
+
+async fn inspect_headers<C: LayerContext + 'static>(layer: HttpRequestLayer<C>, headers: Vec<(&'static str, http::HeaderValue)>) -> Option<serde_json::Value> {
+    let captured = Captured::default();
+    let _guard = tracing_subscriber::registry().with(Capture(captured.clone())).set_default();
+
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+    let mut request = http::Request::new(());
+    for (name, value) in headers {
+        request.headers_mut().append(name, value);
+    }
+    service.oneshot(request).await.unwrap();
+
+    let captured = captured.lock().unwrap().take()?;
+    Some(serde_json::from_str(&captured).unwrap_or_else(|error| panic!("invalid JSON '{captured}': {error}")))
+}
+
+#[tokio::test]
+async fn should_render_headers_as_json() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers([http::header::ACCEPT, http::HeaderName::from_static("x-tenant")])
+                                                     .with_inspect_headers_json();
+
+    let json = inspect_headers(layer, vec![
+        ("accept", http::HeaderValue::from_static("text/plain")),
+        ("x-tenant", http::HeaderValue::from_static("acme")),
+        ("x-tenant", http::HeaderValue::from_static("globex")),
+    ]).await;
+    assert_eq!(json, Some(serde_json::json!({
+        "accept": "text/plain",
+        "x-tenant": ["acme", "globex"],
+    })));
+}
+
+#[tokio::test]
+async fn should_escape_header_values_in_json() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers_all(&[]).with_inspect_headers_json();
+
+    //`HeaderValue` rejects CR, LF and other control characters, except tab, so newlines can only appear as escaped sequence
+    let json = inspect_headers(layer, vec![
+        ("x-quoted", http::HeaderValue::from_static("say \"hi\" \\o/")),
+        ("x-escaped-newline", http::HeaderValue::from_static("line1\\nline2")),
+        ("x-tab", http::HeaderValue::from_bytes(b"a\tb").unwrap()),
+        ("x-unicode", http::HeaderValue::from_bytes("caf\u{e9} \"\u{2603}\"".as_bytes()).unwrap()),
+        ("x-binary", http::HeaderValue::from_bytes(b"\xff\xfe").unwrap()),
+    ]).await;
+    assert_eq!(json, Some(serde_json::json!({
+        "x-quoted": "say \"hi\" \\o/",
+        "x-escaped-newline": "line1\\nline2",
+        "x-tab": "a\tb",
+        "x-unicode": "caf\u{e9} \"\u{2603}\"",
+        "x-binary": "b64://4=",
+    })));
+}
+
+fn redact(name: &http::HeaderName, _: &http::HeaderValue) -> tower_http_tracing::HeaderRedaction {
+    match name.as_str() {
+        "x-secret" => tower_http_tracing::HeaderRedaction::Replace,
+        "x-hidden" => tower_http_tracing::HeaderRedaction::Drop,
+        _ => tower_http_tracing::HeaderRedaction::Keep,
+    }
+}
+
+#[tokio::test]
+async fn should_apply_redaction_and_length_limit_in_json() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers_all(&[])
+                                                     .with_header_redactor(redact)
+                                                     .with_max_header_value_len(4)
+                                                     .with_inspect_headers_json();
+
+    let json = inspect_headers(layer, vec![
+        ("x-secret", http::HeaderValue::from_static("token")),
+        ("x-hidden", http::HeaderValue::from_static("value")),
+        ("x-long", http::HeaderValue::from_static("\"quoted\"")),
+    ]).await;
+    assert_eq!(json, Some(serde_json::json!({
+        "x-secret": "[REDACTED]",
+        "x-long": "\"quo...",
+    })));
+}
+
+#[tokio::test]
+async fn should_apply_values_strategy_in_json() {
+    let headers = || vec![
+        ("x-tenant", http::HeaderValue::from_static("acme")),
+        ("x-tenant", http::HeaderValue::from_static("globex")),
+        ("x-tenant", http::HeaderValue::from_static("initech")),
+    ];
+    let layer = |strategy| HttpRequestLayer::new_simple(my_span).with_inspect_headers([http::HeaderName::from_static("x-tenant")])
+                                                                .with_header_values_strategy(strategy)
+                                                                .with_inspect_headers_json();
+
+    let json = inspect_headers(layer(tower_http_tracing::HeaderValuesStrategy::Last), headers()).await;
+    assert_eq!(json, Some(serde_json::json!({"x-tenant": "initech"})));
+
+    let json = inspect_headers(layer(tower_http_tracing::HeaderValuesStrategy::Count), headers()).await;
+    assert_eq!(json, Some(serde_json::json!({"x-tenant": 3})));
+}
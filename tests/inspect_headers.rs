@@ -0,0 +1,407 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[derive(Copy, Clone)]
+struct HostContext;
+
+impl LayerContext for HostContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[&http::header::HOST];
+}
+
+async fn request<C: LayerContext + 'static, V: AsRef<[u8]>>(layer: HttpRequestLayer<C>, headers: &[(&'static str, V)]) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    for (name, value) in headers {
+        request.headers_mut().append(*name, http::HeaderValue::from_bytes(value.as_ref()).unwrap());
+    }
+    service.oneshot(request).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_inspect_headers_configured_at_runtime() {
+    //Imitates list read from configuration file
+    let config: Vec<String> = "X-Tenant, x-region".split(',').map(|name| name.trim().to_owned()).collect();
+    let inspect_headers = config.iter().map(|name| name.parse::<http::HeaderName>().unwrap());
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers(inspect_headers);
+
+    request(layer, &[("x-tenant", "acme"), ("x-region", "eu"), ("x-other", "secret")]).await;
+    assert!(logs_contain("http.headers={\"x-tenant\": acme, \"x-region\": eu}"));
+    assert!(!logs_contain("secret"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_inspect_runtime_headers_after_context_headers() {
+    let layer = HttpRequestLayer::new(my_span, HostContext).with_inspect_headers([http::header::HOST, http::header::ACCEPT]);
+
+    request(layer, &[("host", "localhost"), ("accept", "text/plain")]).await;
+    assert!(logs_contain("http.headers={\"host\": localhost, \"accept\": text/plain}"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_headers_without_list() {
+    request(HttpRequestLayer::new_simple(my_span).with_inspect_headers([] as [http::HeaderName; 0]), &[("host", "localhost")]).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("http.headers"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_inspect_all_headers_except_denied() {
+    let layer = HttpRequestLayer::new(my_span, HostContext).with_inspect_headers_all(&[http::HeaderName::from_static("x-api-key")]);
+
+    request(layer, &[
+        ("host", "localhost"),
+        ("accept", "text/plain"),
+        ("x-tenant", "acme"),
+        ("x-tenant", "globex"),
+        ("authorization", "Bearer token"),
+        ("cookie", "session=secret"),
+        ("x-api-key", "key"),
+        ("user-agent", "test/1.0"),
+    ]).await;
+    assert!(logs_contain("http.headers={\"host\": localhost, \"accept\": text/plain, \"x-tenant\": acme, globex, \"user-agent\": test/1.0}"));
+    assert!(!logs_contain("token"));
+    assert!(!logs_contain("secret"));
+    assert!(!logs_contain("x-api-key"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_deny_credentials_when_inspecting_all_headers() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers_all(&[]);
+
+    request(layer, &[
+        ("host", "localhost"),
+        ("accept", "text/plain"),
+        ("x-tenant", "acme"),
+        ("user-agent", "test/1.0"),
+        ("proxy-authorization", "Basic secret"),
+    ]).await;
+    assert!(logs_contain("http.headers={\"host\": localhost, \"accept\": text/plain, \"x-tenant\": acme, \"user-agent\": test/1.0}"));
+    assert!(!logs_contain("secret"));
+}
+
+fn redact(name: &http::HeaderName, value: &http::HeaderValue) -> tower_http_tracing::HeaderRedaction {
+    use tower_http_tracing::HeaderRedaction;
+
+    match name.as_str() {
+        "authorization" => match value.as_bytes().iter().position(|byt| *byt == b' ') {
+            Some(idx) => HeaderRedaction::Prefix(idx + 1),
+            None => HeaderRedaction::Replace,
+        },
+        "x-api-key" => HeaderRedaction::Replace,
+        "x-internal" => HeaderRedaction::Drop,
+        "x-tenant" if value.as_bytes().starts_with(b"secret") => HeaderRedaction::Drop,
+        _ => HeaderRedaction::Keep,
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_redact_inspected_headers() {
+    let inspect_headers = ["authorization", "x-api-key", "x-internal", "host"].map(http::HeaderName::from_static);
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers(inspect_headers).with_header_redactor(redact);
+
+    request(layer, &[("authorization", "Bearer token"), ("x-api-key", "key"), ("x-internal", "value"), ("host", "localhost")]).await;
+    assert!(logs_contain("http.headers={\"authorization\": Bearer [REDACTED], \"x-api-key\": [REDACTED], \"host\": localhost}"));
+    assert!(!logs_contain("token"));
+    assert!(!logs_contain("x-internal"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_redact_each_header_value_independently() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers_all(&[]).with_header_redactor(redact);
+
+    request(layer, &[
+        ("authorization", "Bearer token"),
+        ("x-tenant", "acme"),
+        ("x-tenant", "secret-tenant"),
+        ("x-tenant", "globex"),
+        ("x-api-key", "first"),
+        ("x-api-key", "second"),
+    ]).await;
+    assert!(logs_contain("http.headers={\"x-tenant\": acme, globex, \"x-api-key\": [REDACTED], [REDACTED]}"));
+    assert!(!logs_contain("secret"));
+    assert!(!logs_contain("first"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_replace_credential_without_scheme() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers([http::header::AUTHORIZATION]).with_header_redactor(redact);
+
+    request(layer, &[("authorization", "token")]).await;
+    assert!(logs_contain("http.headers={\"authorization\": [REDACTED]}"));
+    assert!(!logs_contain("token"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_truncate_long_header_values() {
+    let inspect_headers = ["cookie", "x-forwarded-for", "x-short"].map(http::HeaderName::from_static);
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers(inspect_headers).with_max_header_value_len(10);
+
+    request(layer, &[
+        ("cookie", "session=0123456789"),
+        ("x-forwarded-for", "10.0.0.1, 10.0.0.2"),
+        ("x-forwarded-for", "10.0.0.3"),
+        ("x-short", "exactly10b"),
+    ]).await;
+    assert!(logs_contain("http.headers={\"cookie\": session=01..., \"x-forwarded-for\": 10.0.0.1, ..., 10.0.0.3, \"x-short\": exactly10b}"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_truncate_multibyte_header_values_at_char_boundary() {
+    let inspect_headers = ["x-long", "x-exact"].map(http::HeaderName::from_static);
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers(inspect_headers).with_max_header_value_len(10);
+
+    //`ж` is 2 bytes, so 10th byte falls in the middle of last character of `x-long`
+    request(layer, &[("x-long", "aжжжжж"), ("x-exact", "жжжжж")]).await;
+    assert!(logs_contain("http.headers={\"x-long\": aжжжж..., \"x-exact\": жжжжж}"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_render_binary_header_values_as_base64() {
+    let inspect_headers = ["custom-bin", "x-text"].map(http::HeaderName::from_static);
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers(inspect_headers);
+
+    request(layer, &[
+        ("custom-bin", &b"\xde\xad\xbe\xef"[..]),
+        ("custom-bin", &b"\x80\xff"[..]),
+        ("x-text", &b"plain"[..]),
+    ]).await;
+    assert!(logs_contain("http.headers={\"custom-bin\": b64:3q2+7w==, b64:gP8=, \"x-text\": plain}"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_truncate_binary_header_values_before_encoding() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers([http::HeaderName::from_static("custom-bin")]).with_max_header_value_len(3);
+
+    request(layer, &[("custom-bin", &b"\xde\xad\xbe\xef"[..])]).await;
+    assert!(logs_contain("http.headers={\"custom-bin\": b64:3q2+...}"));
+}
+
+const SET_COOKIES: [(&str, &str); 3] = [
+    ("set-cookie", "a=1; Expires=Wed, 21 Oct 2026 07:28:00 GMT"),
+    ("set-cookie", "b=2"),
+    ("set-cookie", "c=3"),
+];
+
+async fn request_with_strategy(strategy: tower_http_tracing::HeaderValuesStrategy) {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers([http::header::SET_COOKIE]).with_header_values_strategy(strategy);
+    request(layer, &SET_COOKIES).await;
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_join_multiple_header_values() {
+    request_with_strategy(tower_http_tracing::HeaderValuesStrategy::Join).await;
+    assert!(logs_contain("http.headers={\"set-cookie\": a=1; Expires=Wed, 21 Oct 2026 07:28:00 GMT, b=2, c=3}"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_first_header_value() {
+    request_with_strategy(tower_http_tracing::HeaderValuesStrategy::First).await;
+    assert!(logs_contain("http.headers={\"set-cookie\": a=1; Expires=Wed, 21 Oct 2026 07:28:00 GMT}"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_last_header_value() {
+    request_with_strategy(tower_http_tracing::HeaderValuesStrategy::Last).await;
+    assert!(logs_contain("http.headers={\"set-cookie\": c=3}"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_count_of_header_values() {
+    request_with_strategy(tower_http_tracing::HeaderValuesStrategy::Count).await;
+    assert!(logs_contain("http.headers={\"set-cookie\": 3}"));
+    assert!(!logs_contain("b=2"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_count_dropped_header_values() {
+    fn drop_second(_: &http::HeaderName, value: &http::HeaderValue) -> tower_http_tracing::HeaderRedaction {
+        match value.as_bytes() {
+            b"b=2" => tower_http_tracing::HeaderRedaction::Drop,
+            _ => tower_http_tracing::HeaderRedaction::Keep,
+        }
+    }
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_headers([http::header::SET_COOKIE])
+                                                     .with_header_redactor(drop_second)
+                                                     .with_header_values_strategy(tower_http_tracing::HeaderValuesStrategy::Count);
+    request(layer, &SET_COOKIES).await;
+    assert!(logs_contain("http.headers={\"set-cookie\": 2}"));
+}
+
+make_request_spanner!(header_span("request", tracing::Level::INFO, headers = [FORWARDED, ACCEPT_LANGUAGE, "x-tenant"]));
+make_request_spanner!(ecs_header_span("request", tracing::Level::INFO, semconv = ecs, headers = [ACCEPT], service.name = "test"));
+
+#[test]
+#[tracing_test::traced_test]
+fn should_declare_header_fields() {
+    let span = header_span();
+    let metadata = span.metadata().expect("to have metadata");
+    for field in ["http.request.header.forwarded", "http.request.header.accept-language", "http.request.header.x-tenant"] {
+        assert!(metadata.fields().field(field).is_some(), "{field}");
+    }
+
+    let span = ecs_header_span();
+    let metadata = span.metadata().expect("to have metadata");
+    assert!(metadata.fields().field("http.request.header.accept").is_some());
+    assert!(metadata.fields().field("service.name").is_some());
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_headers_as_individual_fields() {
+    let inspect_headers = ["forwarded", "accept-language", "x-tenant", "x-undeclared"].map(http::HeaderName::from_static);
+    let layer = HttpRequestLayer::new(header_span, HostContext).with_inspect_headers(inspect_headers).with_inspect_headers_as_fields();
+
+    request(layer, &[
+        ("host", "localhost"),
+        ("forwarded", "for=192.0.2.60"),
+        ("accept-language", "en"),
+        ("accept-language", "fr;q=0.8"),
+        ("x-undeclared", "value"),
+    ]).await;
+    assert!(logs_contain("http.request.header.forwarded=for=192.0.2.60"));
+    assert!(logs_contain("http.request.header.accept-language=en,fr;q=0.8"));
+    assert!(!logs_contain("http.request.header.x-tenant"));
+    assert!(!logs_contain("x-undeclared"));
+    assert!(!logs_contain("http.headers"));
+}
+
+//Logs event once response fields are recorded
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+async fn response(layer: HttpRequestLayer<ResponseLogContext>, response_headers: &'static [(&'static str, &'static str)]) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        for (name, value) in response_headers {
+            response.headers_mut().append(*name, http::HeaderValue::from_static(value));
+        }
+        Ok::<_, core::convert::Infallible>(response)
+    });
+    service.oneshot(http::Request::new(())).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_inspect_response_headers() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_inspect_response_headers(&[http::header::CACHE_CONTROL, http::HeaderName::from_static("x-ratelimit-remaining")]);
+
+    response(layer, &[("cache-control", "no-store"), ("x-ratelimit-remaining", "5"), ("x-other", "secret")]).await;
+    assert!(logs_contain("http.response.headers={\"cache-control\": no-store, \"x-ratelimit-remaining\": 5}"));
+    assert!(!logs_contain("secret"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_absent_response_headers() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_inspect_response_headers(&[http::header::CACHE_CONTROL]);
+    response(layer, &[("x-other", "value")]).await;
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.response.headers"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_inspect_response_headers_by_default() {
+    response(HttpRequestLayer::new(my_span, ResponseLogContext), &[("cache-control", "no-store")]).await;
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.response.headers"));
+}
+
+static COOKIES: [&str; 2] = ["locale", "ab_test_bucket"];
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_inspect_selected_cookies() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_cookies(&COOKIES);
+
+    request(layer, &[("cookie", "session=secret; ab_test_bucket=b; malformed; =empty; locale=en-US")]).await;
+    assert!(logs_contain("http.request.cookies={\"locale\": en-US, \"ab_test_bucket\": b}"));
+    assert!(!logs_contain("secret"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_first_occurrence_of_duplicated_cookie() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_cookies(&COOKIES);
+
+    //HTTP/2 allows cookie to be split over multiple headers
+    request(layer, &[("cookie", "locale=en; session=secret"), ("cookie", "locale=fr")]).await;
+    assert!(logs_contain("http.request.cookies={\"locale\": en}"));
+    assert!(!logs_contain("locale\": fr"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_absent_cookies() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_inspect_cookies(&COOKIES);
+    request(layer.clone(), &[("host", "localhost")]).await;
+    request(layer, &[("cookie", "session=secret")]).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("http.request.cookies"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_inspect_headers_matching_prefix() {
+    use tower_http_tracing::HeaderMatch;
+
+    let layer = HttpRequestLayer::new(my_span, HostContext).with_inspect_headers([HeaderMatch::Prefix("X-Exp-"), HeaderMatch::Exact(http::header::ACCEPT), HeaderMatch::Prefix("x-exp-checkout")]);
+
+    request(layer, &[
+        ("x-exp-search", "v2"),
+        ("accept", "text/plain"),
+        ("x-other", "value"),
+        ("host", "localhost"),
+        ("x-exp-checkout", "control"),
+        ("x-exp", "too-short"),
+    ]).await;
+    //Recorded in order of request's headers, each once
+    assert!(logs_contain("http.headers={\"x-exp-search\": v2, \"accept\": text/plain, \"host\": localhost, \"x-exp-checkout\": control}"));
+    assert!(!logs_contain("x-other"));
+    assert!(!logs_contain("too-short"));
+}
+
+#[test]
+fn should_match_header_names() {
+    use tower_http_tracing::HeaderMatch;
+
+    let name = http::HeaderName::from_static("x-exp-search");
+    assert!(HeaderMatch::Prefix("x-exp-").matches(&name));
+    assert!(HeaderMatch::Prefix("X-EXP-").matches(&name));
+    assert!(HeaderMatch::Prefix("").matches(&name));
+    assert!(!HeaderMatch::Prefix("x-exp-search-v2").matches(&name));
+    assert!(HeaderMatch::from(name.clone()).matches(&name));
+    assert!(!HeaderMatch::Exact(http::header::ACCEPT).matches(&name));
+}
@@ -0,0 +1,79 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, REQUEST_ID};
+
+use std::sync::Mutex;
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+//Captures records up to `Warn` level
+struct CaptureLogger(Mutex<Vec<(log::Level, String, String)>>);
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push((record.level(), record.target().to_owned(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {
+    }
+}
+
+static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+
+async fn respond(layer: HttpRequestLayer, status: Result<http::StatusCode, &'static str>) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| async move {
+        let status = status.map_err(std::io::Error::other)?;
+        let mut response = http::Response::new(());
+        *response.status_mut() = status;
+        Ok::<_, std::io::Error>(response)
+    });
+
+    let mut request = http::Request::builder().method(http::Method::POST).uri("/bridge?secret=1").body(()).unwrap();
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static("request-ID"));
+    let _ = service.oneshot(request).await;
+}
+
+fn take_records() -> Vec<(log::Level, String, String)> {
+    core::mem::take(&mut *LOGGER.0.lock().unwrap())
+}
+
+#[tokio::test]
+async fn should_mirror_completed_requests_into_log() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+    let layer = HttpRequestLayer::new_simple(my_span).with_log_bridge(true);
+
+    respond(layer.clone(), Ok(http::StatusCode::OK)).await;
+    //Info level is disabled by logger
+    assert!(take_records().is_empty());
+
+    respond(layer.clone(), Ok(http::StatusCode::NOT_FOUND)).await;
+    let records = take_records();
+    assert_eq!(records.len(), 1);
+    let (level, target, message) = &records[0];
+    assert_eq!(*level, log::Level::Warn);
+    assert_eq!(target, tower_http_tracing::targets::ACCESS);
+    assert!(message.starts_with("POST /bridge http 404 "), "{message}");
+    assert!(message.ends_with("ms request_id=request-ID"), "{message}");
+
+    respond(layer.clone(), Ok(http::StatusCode::SERVICE_UNAVAILABLE)).await;
+    respond(layer.clone(), Err("failure")).await;
+    let records = take_records();
+    assert_eq!(records.iter().map(|record| record.0).collect::<Vec<_>>(), [log::Level::Error, log::Level::Error]);
+    assert!(records[1].2.starts_with("POST /bridge http 500 error "), "{}", records[1].2);
+
+    let mut service = ServiceBuilder::new().layer(layer.clone()).service_fn(|_: http::Request<()>| core::future::pending::<Result<http::Response<()>, std::io::Error>>());
+    drop(service.call(http::Request::new(())));
+    let records = take_records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].0, log::Level::Warn);
+    assert!(records[0].2.starts_with("GET / http cancelled "), "{}", records[0].2);
+
+    respond(layer.with_log_bridge(false), Ok(http::StatusCode::NOT_FOUND)).await;
+    assert!(take_records().is_empty());
+}
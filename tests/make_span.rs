@@ -0,0 +1,25 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use std::sync::Arc;
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO, service.name = tracing::field::Empty));
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_create_span_with_capturing_closure() {
+    let service_name = Arc::new("runtime-service".to_owned());
+    let layer = HttpRequestLayer::new_simple(my_span).with_make_span(move || {
+        let span = my_span();
+        span.record("service.name", service_name.as_str());
+        span
+    });
+    let service = ServiceBuilder::new().layer(layer.clone()).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+    assert!(logs_contain(r#"service.name="runtime-service""#));
+    assert_eq!(layer.stats().spans_created, 1);
+}
@@ -0,0 +1,141 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext, MatchedRoute, RequestSummary, ResponseStatus};
+use tower_http_tracing::body::ResponseBodyLayer;
+
+use core::{future::Future, pin::pin, task};
+use std::cell::RefCell;
+use std::io;
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[derive(Copy, Clone)]
+struct Context;
+
+impl LayerContext for Context {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+}
+
+thread_local! {
+    static COMPLETED: RefCell<Vec<RequestSummary>> = const { RefCell::new(Vec::new()) };
+}
+
+fn on_complete(_: &tracing::Span, summary: &RequestSummary) {
+    COMPLETED.with_borrow_mut(|completed| completed.push(summary.clone()));
+}
+
+fn take_completed() -> Vec<RequestSummary> {
+    COMPLETED.with_borrow_mut(core::mem::take)
+}
+
+fn request() -> http::Request<()> {
+    http::Request::builder().method(http::Method::POST).uri("/complete?query=1").header(http::header::CONTENT_LENGTH, "4").body(()).unwrap()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_complete_with_response() {
+    let layer = HttpRequestLayer::new(my_span, Context).with_on_complete(on_complete);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        let mut resp = http::Response::new(());
+        *resp.status_mut() = http::StatusCode::CREATED;
+        resp.headers_mut().insert(http::header::CONTENT_LENGTH, http::HeaderValue::from_static("7"));
+        resp.extensions_mut().insert(MatchedRoute("/complete".to_owned()));
+        Ok::<_, io::Error>(resp)
+    });
+
+    service.oneshot(request()).await.unwrap();
+
+    let completed = take_completed();
+    assert_eq!(completed.len(), 1);
+    let summary = &completed[0];
+    assert_eq!(summary.method, http::Method::POST);
+    assert_eq!(summary.path, "/complete");
+    assert_eq!(summary.uri, "/complete?query=1");
+    assert_eq!(summary.status, ResponseStatus::Code(201));
+    assert_eq!(summary.route.as_deref(), Some("/complete"));
+    assert_eq!(summary.request_body_size, Some(4));
+    assert_eq!(summary.response_body_size, Some(7));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_complete_once_tracked_body_ends() {
+    let layer = HttpRequestLayer::new(my_span, Context).with_on_complete(on_complete);
+    let service = ServiceBuilder::new().layer(layer)
+                                       .layer(ResponseBodyLayer::new().with_track_response_body(true))
+                                       .service_fn(|_: http::Request<()>| async move {
+        Ok::<_, io::Error>(http::Response::new(http_body_util::Full::new(Bytes::from_static(b"hello world"))))
+    });
+
+    let resp = service.oneshot(request()).await.unwrap();
+    assert!(take_completed().is_empty());
+
+    let body = resp.into_body().collect().await.unwrap();
+    assert_eq!(body.to_bytes(), "hello world");
+    let completed = take_completed();
+    assert_eq!(completed.len(), 1);
+    assert_eq!(completed[0].status, ResponseStatus::Code(200));
+    assert_eq!(completed[0].response_body_size, Some(11));
+    assert!(take_completed().is_empty());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_complete_once_tracked_body_is_dropped() {
+    let layer = HttpRequestLayer::new(my_span, Context).with_on_complete(on_complete);
+    let service = ServiceBuilder::new().layer(layer)
+                                       .layer(ResponseBodyLayer::new().with_track_response_body(true))
+                                       .service_fn(|_: http::Request<()>| async move {
+        Ok::<_, io::Error>(http::Response::new(http_body_util::Full::new(Bytes::from_static(b"hello world"))))
+    });
+
+    let resp = service.oneshot(request()).await.unwrap();
+    assert!(take_completed().is_empty());
+
+    drop(resp);
+    let completed = take_completed();
+    assert_eq!(completed.len(), 1);
+    assert_eq!(completed[0].response_body_size, Some(0));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_complete_with_error() {
+    let layer = HttpRequestLayer::new(my_span, Context).with_on_complete(on_complete);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        Err::<http::Response<()>, _>(io::Error::other("failed"))
+    });
+
+    service.oneshot(request()).await.unwrap_err();
+
+    let completed = take_completed();
+    assert_eq!(completed.len(), 1);
+    assert_eq!(completed[0].status, ResponseStatus::Error(500));
+}
+
+#[test]
+fn should_complete_on_cancel() {
+    let layer = HttpRequestLayer::new(my_span, Context).with_on_complete(on_complete);
+    let mut service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| core::future::pending::<Result<http::Response<()>, io::Error>>());
+
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    assert!(service.poll_ready(&mut ctx).is_ready());
+    {
+        let mut fut = pin!(service.call(request()));
+        assert!(fut.as_mut().poll(&mut ctx).is_pending());
+        assert!(take_completed().is_empty());
+    }
+
+    let completed = take_completed();
+    assert_eq!(completed.len(), 1);
+    assert_eq!(completed[0].status, ResponseStatus::Cancelled);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn should_not_complete_without_callback() {
+    let layer = HttpRequestLayer::new(my_span, Context);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, io::Error>(http::Response::new(()))
+    });
+
+    service.oneshot(request()).await.unwrap();
+    assert!(take_completed().is_empty());
+}
@@ -0,0 +1,90 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext, RequestId, RequestIdFormat, REQUEST_ID};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tower::{Layer, ServiceExt};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[derive(Copy, Clone)]
+enum PanicOn {
+    NewSpan,
+    Record,
+}
+
+//Subscriber layer with a bug
+struct PanickingLayer(PanicOn);
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for PanickingLayer {
+    fn on_new_span(&self, _: &tracing::span::Attributes<'_>, _: &tracing::span::Id, _: tracing_subscriber::layer::Context<'_, S>) {
+        if let PanicOn::NewSpan = self.0 {
+            panic!("subscriber failure");
+        }
+    }
+
+    fn on_record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        if let PanicOn::Record = self.0 {
+            panic!("subscriber failure");
+        }
+    }
+}
+
+async fn request<C: LayerContext>(layer: HttpRequestLayer<C>, panic_on: PanicOn) -> http::Response<()> {
+    let _guard = tracing_subscriber::registry().with(PanickingLayer(panic_on)).set_default();
+    let service = layer.layer(tower::service_fn(|_: http::Request<()>| async move {
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    }));
+    service.oneshot(http::Request::new(())).await.unwrap()
+}
+
+#[tokio::test]
+async fn should_survive_panic_on_span_creation() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_subscriber_panic_guard();
+    let response = request(layer.clone(), PanicOn::NewSpan).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert!(response.headers().contains_key(tower_http_tracing::REQUEST_ID));
+    assert_eq!(layer.stats().subscriber_panics, 1);
+}
+
+#[tokio::test]
+async fn should_survive_panic_on_record() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_subscriber_panic_guard();
+    let response = request(layer.clone(), PanicOn::Record).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert!(response.headers().contains_key(tower_http_tracing::REQUEST_ID));
+    assert_eq!(layer.stats().subscriber_panics, 1);
+}
+
+#[tokio::test]
+#[should_panic(expected = "subscriber failure")]
+async fn should_panic_without_guard() {
+    request(HttpRequestLayer::new_simple(my_span), PanicOn::Record).await;
+}
+
+//Generates sequential request ids
+#[derive(Clone, Default)]
+struct SequentialIds(Arc<AtomicUsize>);
+
+impl LayerContext for SequentialIds {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn generate_request_id(&self, _: RequestIdFormat) -> RequestId {
+        RequestId::from_bytes(format!("id-{}", self.0.fetch_add(1, Ordering::Relaxed)).as_bytes())
+    }
+}
+
+#[tokio::test]
+async fn should_keep_generated_request_id_after_panic_on_record() {
+    let ids = SequentialIds::default();
+    let layer = HttpRequestLayer::new(my_span, ids.clone()).with_subscriber_panic_guard();
+    let response = request(layer.clone(), PanicOn::Record).await;
+
+    assert_eq!(layer.stats().subscriber_panics, 1);
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), "id-0");
+    assert_eq!(ids.0.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,51 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestInfo};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const CLIENT_SERVICE: http::HeaderName = http::HeaderName::from_static("x-client-service");
+
+async fn request(layer: HttpRequestLayer, peer_service: Option<&[u8]>) -> Option<Box<str>> {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|req: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        let info = req.extensions().get::<RequestInfo>().unwrap();
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.peer_service.clone()))
+    });
+
+    let mut req = http::Request::new(());
+    if let Some(peer_service) = peer_service {
+        req.headers_mut().insert(CLIENT_SERVICE, http::HeaderValue::from_bytes(peer_service).unwrap());
+    }
+    service.oneshot(req).await.unwrap().into_body()
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_peer_service() {
+    let peer_service = request(HttpRequestLayer::new_simple(my_span).with_peer_service_header(CLIENT_SERVICE), Some(b"billing")).await;
+    assert_eq!(peer_service.as_deref(), Some("billing"));
+    assert!(logs_contain(r#"peer.service="billing""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_skip_absent_or_invalid_peer_service() {
+    let peer_service = request(HttpRequestLayer::new_simple(my_span).with_peer_service_header(CLIENT_SERVICE), None).await;
+    assert_eq!(peer_service, None);
+
+    let peer_service = request(HttpRequestLayer::new_simple(my_span).with_peer_service_header(CLIENT_SERVICE), Some(b"bill\xffing")).await;
+    assert_eq!(peer_service, None);
+
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("peer.service"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_peer_service_by_default() {
+    let peer_service = request(HttpRequestLayer::new_simple(my_span), Some(b"billing")).await;
+    assert_eq!(peer_service, None);
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("peer.service"));
+}
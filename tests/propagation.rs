@@ -0,0 +1,144 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestInfo};
+use tower_http_tracing::propagation::{PropagationFormat, TraceContext, TRACEPARENT, DATADOG_TRACE_ID, DATADOG_PARENT_ID, DATADOG_SAMPLING_PRIORITY, DATADOG_TAGS};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const CONTEXT: TraceContext = TraceContext {
+    trace_id: 0x0af7651916cd43dd8448eb211c80319c,
+    span_id: 0xb7ad6b7169203331,
+    sampled: true,
+};
+
+#[test]
+fn should_round_trip_w3c_context() {
+    let mut headers = http::HeaderMap::new();
+    PropagationFormat::W3c.inject(&mut headers, &CONTEXT);
+    assert_eq!(headers.get(TRACEPARENT).unwrap(), "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01");
+    assert_eq!(PropagationFormat::W3c.extract(&headers), Some(CONTEXT));
+
+    headers.insert(TRACEPARENT, http::HeaderValue::from_static("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00"));
+    assert_eq!(PropagationFormat::W3c.extract(&headers), Some(TraceContext { sampled: false, ..CONTEXT }));
+}
+
+#[test]
+fn should_reject_invalid_w3c_context() {
+    let mut headers = http::HeaderMap::new();
+    assert_eq!(PropagationFormat::W3c.extract(&headers), None);
+
+    for invalid in ["01-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01", "00-00000000000000000000000000000000-b7ad6b7169203331-01", "00-0af7651916cd43dd8448eb211c80319c-b7ad6b716920333-01", "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-00"] {
+        headers.insert(TRACEPARENT, http::HeaderValue::from_static(invalid));
+        assert_eq!(PropagationFormat::W3c.extract(&headers), None, "{invalid}");
+    }
+}
+
+#[test]
+fn should_round_trip_datadog_context() {
+    let mut headers = http::HeaderMap::new();
+    PropagationFormat::Datadog.inject(&mut headers, &CONTEXT);
+    assert_eq!(headers.get(DATADOG_TRACE_ID).unwrap(), "9532127138774266268");
+    assert_eq!(headers.get(DATADOG_PARENT_ID).unwrap(), "13235353014750950193");
+    assert_eq!(headers.get(DATADOG_SAMPLING_PRIORITY).unwrap(), "1");
+    assert_eq!(headers.get(DATADOG_TAGS).unwrap(), "_dd.p.tid=0af7651916cd43dd");
+    assert_eq!(PropagationFormat::Datadog.extract(&headers), Some(CONTEXT));
+}
+
+#[test]
+fn should_round_trip_context_over_custom_carrier() {
+    let mut carrier = std::collections::HashMap::<String, String>::new();
+    assert_eq!(PropagationFormat::W3c.extract_from(&carrier), None);
+
+    PropagationFormat::W3c.inject_into(&mut carrier, &CONTEXT);
+    PropagationFormat::Datadog.inject_into(&mut carrier, &CONTEXT);
+    assert_eq!(carrier["traceparent"], "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01");
+    assert_eq!(carrier["x-datadog-trace-id"], "9532127138774266268");
+    assert_eq!(carrier["x-datadog-tags"], "_dd.p.tid=0af7651916cd43dd");
+    assert_eq!(PropagationFormat::W3c.extract_from(&carrier), Some(CONTEXT));
+    assert_eq!(PropagationFormat::Datadog.extract_from(&carrier), Some(CONTEXT));
+
+    let mut headers = http::HeaderMap::new();
+    for (key, value) in &carrier {
+        headers.insert(http::HeaderName::from_bytes(key.as_bytes()).unwrap(), value.parse().unwrap());
+    }
+    assert_eq!(PropagationFormat::extract_first(&[PropagationFormat::Datadog], &headers), Some(CONTEXT));
+}
+
+#[test]
+fn should_extract_first_available_format() {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(DATADOG_TRACE_ID, http::HeaderValue::from_static("1"));
+    headers.insert(DATADOG_PARENT_ID, http::HeaderValue::from_static("2"));
+    headers.insert(DATADOG_SAMPLING_PRIORITY, http::HeaderValue::from_static("-1"));
+
+    let formats = [PropagationFormat::W3c, PropagationFormat::Datadog];
+    assert_eq!(PropagationFormat::extract_first(&formats, &headers), Some(TraceContext { trace_id: 1, span_id: 2, sampled: false }));
+
+    PropagationFormat::W3c.inject(&mut headers, &CONTEXT);
+    assert_eq!(PropagationFormat::extract_first(&formats, &headers), Some(CONTEXT));
+}
+
+#[tokio::test]
+async fn should_extract_datadog_and_inject_w3c() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_propagation_extract(&[PropagationFormat::Datadog, PropagationFormat::W3c])
+                                                     .with_propagation_inject(&[PropagationFormat::W3c]);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        assert_eq!(info.trace_context, Some(CONTEXT));
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(DATADOG_TRACE_ID, http::HeaderValue::from_static("9532127138774266268"));
+    request.headers_mut().insert(DATADOG_PARENT_ID, http::HeaderValue::from_static("13235353014750950193"));
+    request.headers_mut().insert(DATADOG_SAMPLING_PRIORITY, http::HeaderValue::from_static("2"));
+    request.headers_mut().insert(DATADOG_TAGS, http::HeaderValue::from_static("_dd.p.dm=-1,_dd.p.tid=0af7651916cd43dd"));
+
+    let response = service.oneshot(request).await.unwrap();
+    assert_eq!(response.headers().get(TRACEPARENT).unwrap(), "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01");
+    assert!(response.headers().get(DATADOG_TRACE_ID).is_none());
+}
+
+#[tokio::test]
+async fn should_not_propagate_by_default() {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        assert_eq!(info.trace_context, None);
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(TRACEPARENT, http::HeaderValue::from_static("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"));
+    let response = service.oneshot(request).await.unwrap();
+    assert!(response.headers().get(TRACEPARENT).is_none());
+}
+
+#[test]
+fn should_round_trip_trace_response() {
+    let mut headers = http::HeaderMap::new();
+    assert_eq!(tower_http_tracing::propagation::extract_trace_response(&headers), None);
+
+    tower_http_tracing::propagation::inject_trace_response(&mut headers, &CONTEXT);
+    assert_eq!(headers.get(tower_http_tracing::propagation::TRACERESPONSE).unwrap(), "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01");
+    assert!(!headers.contains_key(TRACEPARENT));
+    assert_eq!(tower_http_tracing::propagation::extract_trace_response(&headers), Some(CONTEXT));
+
+    let mut headers = http::HeaderMap::new();
+    tower_http_tracing::propagation::inject_trace_response(&mut headers, &TraceContext::default());
+    assert!(headers.is_empty());
+}
+
+tower_http_tracing::make_child_spanner!(downstream_span("downstream", tracing::Level::INFO));
+
+#[test]
+#[tracing_test::traced_test]
+fn should_record_trace_response_of_downstream() {
+    let mut headers = http::HeaderMap::new();
+    tower_http_tracing::propagation::inject_trace_response(&mut headers, &CONTEXT);
+
+    let span = downstream_span(&tracing::Span::current());
+    tower_http_tracing::propagation::record_trace_response(&span, &headers);
+    span.in_scope(|| tracing::info!("RESPONSE"));
+
+    assert!(logs_contain("traceresponse.trace_id=0af7651916cd43dd8448eb211c80319c traceresponse.span_id=b7ad6b7169203331"));
+}
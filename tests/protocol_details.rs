@@ -0,0 +1,60 @@
+use tower_http_tracing::{make_request_spanner, Noop, ProtocolDetails, RequestSpan};
+
+use std::time::Duration;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+fn details(req: http::Request<()>) -> ProtocolDetails {
+    let (parts, ()) = req.into_parts();
+    RequestSpan::new(&Noop, my_span(), &parts).info.details
+}
+
+#[test]
+fn should_provide_grpc_details() {
+    let req = http::Request::builder().uri("/grpc.health.v1.Health/Check")
+                                      .header(http::header::CONTENT_TYPE, "application/grpc")
+                                      .header(tower_http_tracing::grpc::GRPC_TIMEOUT, "1S")
+                                      .body(())
+                                      .unwrap();
+    assert_eq!(details(req), ProtocolDetails::Grpc {
+        service: "grpc.health.v1.Health".into(),
+        method: "Check".into(),
+        timeout: Some(Duration::from_secs(1)),
+    });
+
+    let req = http::Request::builder().uri("/grpc.health.v1.Health/Watch")
+                                      .header(http::header::CONTENT_TYPE, "application/grpc-web+proto")
+                                      .body(())
+                                      .unwrap();
+    assert_eq!(details(req), ProtocolDetails::GrpcWeb {
+        service: "grpc.health.v1.Health".into(),
+        method: "Watch".into(),
+        timeout: None,
+    });
+}
+
+#[test]
+fn should_provide_empty_grpc_details_for_invalid_path() {
+    let req = http::Request::builder().uri("/Check")
+                                      .header(http::header::CONTENT_TYPE, "application/grpc")
+                                      .body(())
+                                      .unwrap();
+    assert_eq!(details(req), ProtocolDetails::Grpc {
+        service: "".into(),
+        method: "".into(),
+        timeout: None,
+    });
+}
+
+#[test]
+fn should_provide_http_details() {
+    let req = http::Request::builder().uri("/index.html").version(http::Version::HTTP_2).body(()).unwrap();
+    assert_eq!(details(req), ProtocolDetails::Http { version: http::Version::HTTP_2 });
+
+    let req = http::Request::builder().uri("/ws")
+                                      .header(http::header::CONNECTION, "Upgrade")
+                                      .header(http::header::UPGRADE, "WebSocket")
+                                      .body(())
+                                      .unwrap();
+    assert_eq!(details(req), ProtocolDetails::WebSocket { version: http::Version::HTTP_11 });
+}
@@ -0,0 +1,108 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+use tower_http_tracing::clock::ManualClock;
+
+use std::time::{Duration, SystemTime};
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+//Sun, 06 Nov 1994 08:49:37 GMT
+const NOW: u64 = 784111777;
+
+async fn throttled_request(status: http::StatusCode, headers: &'static [(&'static str, &'static str)]) {
+    let clock = ManualClock::new(SystemTime::UNIX_EPOCH + Duration::from_secs(NOW));
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_clock(clock);
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        *response.status_mut() = status;
+        for (name, value) in headers {
+            response.headers_mut().insert(*name, http::HeaderValue::from_static(value));
+        }
+        Ok::<_, core::convert::Infallible>(response)
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_rate_limit_with_delta_seconds() {
+    throttled_request(http::StatusCode::TOO_MANY_REQUESTS, &[
+        ("ratelimit-limit", "100, 100;w=60"),
+        ("ratelimit-remaining", "0"),
+        ("retry-after", "120"),
+    ]).await;
+
+    assert!(logs_contain("http.response.status_code=429 http.response.rate_limit.limit=100 http.response.rate_limit.remaining=0 http.response.rate_limit.retry_after_seconds=120 http.response.retry_after=120 http.response.ratelimit_remaining=0}: rate_limit: RESPONSE"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_rate_limit_with_http_date() {
+    throttled_request(http::StatusCode::TOO_MANY_REQUESTS, &[
+        ("retry-after", "Sun, 06 Nov 1994 08:51:07 GMT"),
+    ]).await;
+
+    assert!(logs_contain("http.response.status_code=429 http.response.rate_limit.retry_after_seconds=90 http.response.retry_after=90}: rate_limit: RESPONSE"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_retry_after_for_unavailable() {
+    throttled_request(http::StatusCode::SERVICE_UNAVAILABLE, &[
+        ("retry-after", "Sun Nov  6 08:49:30 1994"),
+    ]).await;
+
+    assert!(logs_contain("http.response.status_code=503 http.response.rate_limit.retry_after_seconds=0 http.response.retry_after=0}: rate_limit: RESPONSE"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_rate_limit_for_other_statuses() {
+    throttled_request(http::StatusCode::OK, &[
+        ("ratelimit-limit", "100"),
+        ("retry-after", "120"),
+    ]).await;
+    throttled_request(http::StatusCode::SERVICE_UNAVAILABLE, &[
+        ("ratelimit-limit", "100"),
+    ]).await;
+
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.response.rate_limit"));
+    assert!(!logs_contain("http.response.retry_after"));
+    assert!(!logs_contain("http.response.ratelimit_remaining"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_legacy_rate_limit_headers() {
+    throttled_request(http::StatusCode::TOO_MANY_REQUESTS, &[
+        ("x-ratelimit-limit", "100"),
+        ("x-ratelimit-remaining", "0"),
+        ("retry-after", "30"),
+    ]).await;
+
+    assert!(logs_contain("http.response.status_code=429 http.response.rate_limit.limit=100 http.response.rate_limit.remaining=0 http.response.rate_limit.retry_after_seconds=30 http.response.retry_after=30 http.response.ratelimit_remaining=0}: rate_limit: RESPONSE"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_prefer_standard_rate_limit_headers() {
+    throttled_request(http::StatusCode::TOO_MANY_REQUESTS, &[
+        ("ratelimit-remaining", "5"),
+        ("x-ratelimit-remaining", "7"),
+    ]).await;
+
+    assert!(logs_contain("http.response.status_code=429 http.response.rate_limit.remaining=5 http.response.ratelimit_remaining=5}: rate_limit: RESPONSE"));
+}
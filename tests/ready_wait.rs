@@ -0,0 +1,92 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+use tower_http_tracing::clock::ManualClock;
+
+use core::task;
+use core::future::{ready, Ready};
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tower::{Layer, Service};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+struct GatedService {
+    gate: Arc<AtomicBool>,
+}
+
+impl Service<http::Request<()>> for GatedService {
+    type Response = http::Response<()>;
+    type Error = core::convert::Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        if self.gate.load(Ordering::Acquire) {
+            task::Poll::Ready(Ok(()))
+        } else {
+            task::Poll::Pending
+        }
+    }
+
+    fn call(&mut self, _: http::Request<()>) -> Self::Future {
+        ready(Ok(http::Response::new(())))
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_ready_wait() {
+    let clock = Arc::new(ManualClock::new(SystemTime::now()));
+    let gate = Arc::new(AtomicBool::new(false));
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_clock(clock.clone()).with_record_ready_wait();
+    let mut service = layer.layer(GatedService { gate: gate.clone() });
+
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    assert!(service.poll_ready(&mut ctx).is_pending());
+    clock.advance(Duration::from_millis(100));
+    //Wait starts with the first pending poll
+    assert!(service.poll_ready(&mut ctx).is_pending());
+    clock.advance(Duration::from_millis(150));
+    gate.store(true, Ordering::Release);
+    assert!(service.poll_ready(&mut ctx).is_ready());
+    service.call(http::Request::new(())).await.unwrap();
+
+    assert!(logs_contain("http.server.ready_wait_ms=250"));
+
+    //Wait is reset by call
+    assert!(service.poll_ready(&mut ctx).is_ready());
+    service.call(http::Request::new(())).await.unwrap();
+    assert!(logs_contain("http.server.ready_wait_ms=250"));
+    logs_assert(|lines| {
+        match lines.iter().filter(|line| line.contains("http.server.ready_wait_ms")).count() {
+            1 => Ok(()),
+            count => Err(format!("ready wait recorded {count} times")),
+        }
+    });
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_ready_wait_by_default() {
+    let gate = Arc::new(AtomicBool::new(false));
+    let mut service = HttpRequestLayer::new(my_span, ResponseLogContext).layer(GatedService { gate: gate.clone() });
+
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    assert!(service.poll_ready(&mut ctx).is_pending());
+    gate.store(true, Ordering::Release);
+    assert!(service.poll_ready(&mut ctx).is_ready());
+    service.call(http::Request::new(())).await.unwrap();
+
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.server.ready_wait_ms"));
+}
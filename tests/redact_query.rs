@@ -0,0 +1,55 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const SENSITIVE: &[&str] = &["token", "api_key"];
+
+async fn request(layer: HttpRequestLayer, uri: &'static str) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    *req.uri_mut() = http::Uri::from_static(uri);
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_redact_sensitive_query_params() {
+    request(HttpRequestLayer::new_simple(my_span).with_redact_query_params(SENSITIVE), "/path?user=1&Token=secret&page=2&API_KEY=key&api_key_hint=3").await;
+    assert!(logs_contain(r#"url.query="user=1&Token=REDACTED&page=2&API_KEY=REDACTED&api_key_hint=3""#));
+    assert!(!logs_contain("secret"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_redact_repeated_query_params() {
+    request(HttpRequestLayer::new_simple(my_span).with_redact_query_params(SENSITIVE), "/path?token=first&token&token=&token=second").await;
+    assert!(logs_contain(r#"url.query="token=REDACTED&token&token=REDACTED&token=REDACTED""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_keep_query_without_sensitive_params() {
+    request(HttpRequestLayer::new_simple(my_span).with_redact_query_params(SENSITIVE), "/path?user=1&&page=2").await;
+    assert!(logs_contain(r#"url.query="user=1&&page=2""#));
+
+    request(HttpRequestLayer::new_simple(my_span).with_redact_query_params(SENSITIVE), "/path?").await;
+    assert!(logs_contain(r#"url.path="/path" url.query="""#));
+
+    request(HttpRequestLayer::new_simple(my_span), "/path?token=secret").await;
+    assert!(logs_contain(r#"url.query="token=secret""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_redact_query_params_of_full_url() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_redact_query_params(SENSITIVE).with_record_full_url(true);
+    request(layer, "https://example.com/path?token=secret&user=1").await;
+    assert!(logs_contain("url.full=https://example.com/path?token=REDACTED&user=1"));
+    assert!(!logs_contain("secret"));
+}
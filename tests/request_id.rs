@@ -0,0 +1,642 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext, RequestIdPolicy, ResponseRequestIdConflict, REQUEST_ID};
+use tower_http_tracing::propagation::{PropagationFormat, TRACEPARENT};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const REQUEST_ID_VALUE: &str = "request-id";
+
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+async fn conflicting_request(policy: ResponseRequestIdConflict, response_id: &'static str) -> http::Response<()> {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_response_request_id_conflict(policy);
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        response.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(response_id));
+        Ok::<_, core::convert::Infallible>(response)
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(REQUEST_ID_VALUE));
+    service.oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_overwrite_response_request_id() {
+    let response = conflicting_request(ResponseRequestIdConflict::Overwrite, "handler-id").await;
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.request_id.response_mismatch"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_keep_existing_response_request_id() {
+    let response = conflicting_request(ResponseRequestIdConflict::KeepExisting, "handler-id").await;
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), "handler-id");
+    assert!(logs_contain("http.request_id.response_mismatch=true http.response.status_code=200}: request_id: RESPONSE"));
+
+    let response = conflicting_request(ResponseRequestIdConflict::KeepExisting, REQUEST_ID_VALUE).await;
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_append_both_response_request_ids() {
+    let response = conflicting_request(ResponseRequestIdConflict::AppendBoth, "handler-id").await;
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), "handler-id,request-id");
+    assert!(logs_contain("http.request_id.response_mismatch=true http.response.status_code=200}: request_id: RESPONSE"));
+
+    let response = conflicting_request(ResponseRequestIdConflict::AppendBoth, REQUEST_ID_VALUE).await;
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+}
+
+const TRACEPARENT_VALUE: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+async fn traced_request(traceparent: Option<&'static str>, request_id: Option<&'static str>) -> http::Response<()> {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_propagation_extract(&[PropagationFormat::W3c])
+                                                                  .with_request_id_policy(RequestIdPolicy::DeriveFromTrace);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    if let Some(traceparent) = traceparent {
+        request.headers_mut().insert(TRACEPARENT, http::HeaderValue::from_static(traceparent));
+    }
+    if let Some(request_id) = request_id {
+        request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(request_id));
+    }
+    service.oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_derive_request_id_from_trace() {
+    let response = traced_request(Some(TRACEPARENT_VALUE), None).await;
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), "0af7651916cd43dd8448eb211c80319c");
+    assert!(logs_contain(r#"http.request_id="0af7651916cd43dd8448eb211c80319c""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_generate_request_id_without_trace() {
+    let response = traced_request(None, None).await;
+    let request_id = response.headers().get(REQUEST_ID).unwrap().to_str().unwrap();
+    assert_eq!(request_id.len(), 36);
+    assert!(uuid_like(request_id));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_prefer_request_id_header_over_trace() {
+    let response = traced_request(Some(TRACEPARENT_VALUE), Some(REQUEST_ID_VALUE)).await;
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+}
+
+fn uuid_like(value: &str) -> bool {
+    value.split('-').map(str::len).eq([8, 4, 4, 4, 12])
+}
+
+const CORRELATION_ID: http::HeaderName = http::HeaderName::from_static("x-correlation-id");
+const AMZN_TRACE_ID: http::HeaderName = http::HeaderName::from_static("x-amzn-trace-id");
+static REQUEST_ID_HEADERS: [http::HeaderName; 3] = [REQUEST_ID, CORRELATION_ID, AMZN_TRACE_ID];
+
+async fn request_with_headers(headers: &[(http::HeaderName, &'static str)]) -> (Option<http::HeaderName>, String, http::HeaderMap) {
+    let layer = HttpRequestLayer::new_simple(my_span).with_request_id_headers(&REQUEST_ID_HEADERS);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new((info.request_id_source.clone(), info.request_id.to_string())))
+    });
+
+    let mut request = http::Request::new(());
+    for (name, value) in headers {
+        request.headers_mut().insert(name, http::HeaderValue::from_static(value));
+    }
+    let (parts, (source, request_id)) = service.oneshot(request).await.unwrap().into_parts();
+    (source, request_id, parts.headers)
+}
+
+#[tokio::test]
+async fn should_inherit_request_id_from_first_matching_header() {
+    let (source, request_id, headers) = request_with_headers(&[(AMZN_TRACE_ID, "Root=1-abc"), (CORRELATION_ID, "correlation")]).await;
+    assert_eq!(source, Some(CORRELATION_ID));
+    assert_eq!(request_id, "correlation");
+    //Response uses primary header
+    assert_eq!(headers.get(REQUEST_ID).unwrap(), "correlation");
+    assert!(!headers.contains_key(CORRELATION_ID));
+
+    let (source, request_id, _) = request_with_headers(&[(AMZN_TRACE_ID, "Root=1-abc")]).await;
+    assert_eq!(source, Some(AMZN_TRACE_ID));
+    assert_eq!(request_id, "Root=1-abc");
+
+    let (source, request_id, _) = request_with_headers(&[(AMZN_TRACE_ID, "Root=1-abc"), (REQUEST_ID, REQUEST_ID_VALUE)]).await;
+    assert_eq!(source, Some(REQUEST_ID));
+    assert_eq!(request_id, REQUEST_ID_VALUE);
+}
+
+#[tokio::test]
+async fn should_generate_request_id_without_matching_headers() {
+    let (source, request_id, headers) = request_with_headers(&[(http::HeaderName::from_static("x-other-id"), "other")]).await;
+    assert_eq!(source, None);
+    assert!(uuid_like(&request_id), "{request_id}");
+    assert_eq!(headers.get(REQUEST_ID).unwrap().to_str().unwrap(), request_id);
+}
+
+#[tokio::test]
+async fn should_round_trip_long_request_id() {
+    let long_id = "Root=1-67891233-abcdef012345678912345678;Parent=53995c3f42cd8ad8;Sampled=1;Self=1-67891233-12456789abcdef012345678;Lineage=a87bc";
+    assert_eq!(long_id.len(), 128);
+
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        let request_id = info.request_id.clone();
+        Ok::<_, core::convert::Infallible>(http::Response::new((request_id.as_str().map(str::to_owned), request_id.as_bytes().len())))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(long_id));
+    let (parts, (request_id, len)) = service.oneshot(request).await.unwrap().into_parts();
+    assert_eq!(request_id.as_deref(), Some(long_id));
+    assert_eq!(len, 128);
+    assert_eq!(parts.headers.get(REQUEST_ID).unwrap(), long_id);
+}
+
+//Captures warnings emitted by middleware
+struct WarnCapture(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+//Formats all fields of event into single line
+struct EventVisitor(String);
+
+impl tracing::field::Visit for EventVisitor {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        self.0.push_str(&format!("{}={value:?}", field.name()));
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for WarnCapture {
+    fn on_event(&self, event: &tracing::Event<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        if *event.metadata().level() == tracing::Level::WARN && event.metadata().target() == tower_http_tracing::targets::SPAN {
+            let mut visitor = EventVisitor(String::new());
+            event.record(&mut visitor);
+            self.0.lock().unwrap().push(visitor.0);
+        }
+    }
+}
+
+async fn inherit_request_id(request_id: http::HeaderValue) -> (bool, usize) {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new((info.request_id.truncated(), info.request_id.as_bytes().len())))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, request_id);
+    service.oneshot(request).await.unwrap().into_body()
+}
+
+#[tokio::test]
+async fn should_warn_on_truncated_request_id() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let captured = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let _guard = tracing_subscriber::registry().with(WarnCapture(captured.clone())).set_default();
+
+    let long_id = http::HeaderValue::from_bytes(&[b'a'; tower_http_tracing::REQUEST_ID_MAX_LEN + 1]).unwrap();
+    let (truncated, len) = inherit_request_id(long_id).await;
+    assert!(truncated);
+    assert_eq!(len, tower_http_tracing::REQUEST_ID_MAX_LEN);
+    let warnings = core::mem::take(&mut *captured.lock().unwrap());
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("truncated"), "{warnings:?}");
+
+    let (truncated, len) = inherit_request_id(http::HeaderValue::from_static(REQUEST_ID_VALUE)).await;
+    assert!(!truncated);
+    assert_eq!(len, REQUEST_ID_VALUE.len());
+    assert!(captured.lock().unwrap().is_empty());
+}
+
+#[test]
+fn should_construct_request_id() {
+    use tower_http_tracing::RequestId;
+
+    let request_id: RequestId = REQUEST_ID_VALUE.parse().unwrap();
+    assert_eq!(request_id.as_str(), Some(REQUEST_ID_VALUE));
+    assert_eq!(request_id.len(), REQUEST_ID_VALUE.len());
+    assert!(!request_id.is_empty());
+    assert!(!request_id.truncated());
+    assert_eq!(request_id, RequestId::from_bytes(REQUEST_ID_VALUE.as_bytes()));
+    assert!(RequestId::from_bytes(b"").is_empty());
+
+    let generated = RequestId::new_v4();
+    assert_eq!(generated.len(), 36);
+    assert_ne!(generated, RequestId::new_v4());
+
+    let ids = std::collections::HashSet::from([request_id.clone(), generated.clone(), request_id]);
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&generated));
+}
+
+#[test]
+fn should_access_request_id_in_const_context() {
+    use tower_http_tracing::RequestId;
+
+    const fn id_str(request_id: &RequestId) -> Option<&str> {
+        request_id.as_str()
+    }
+    const fn id_len(request_id: &RequestId) -> usize {
+        request_id.as_bytes().len()
+    }
+
+    let inline = RequestId::from_bytes(REQUEST_ID_VALUE.as_bytes());
+    assert_eq!(id_str(&inline), Some(REQUEST_ID_VALUE));
+    let heap = RequestId::from_bytes(&[b'a'; 128]);
+    assert_eq!(id_len(&heap), 128);
+}
+
+#[tokio::test]
+async fn should_allow_handler_tests_with_synthetic_request_info() {
+    use tower_http_tracing::{Protocol, ProtocolDetails, RequestId, RequestInfo};
+
+    async fn handler(request: http::Request<()>) -> Result<http::Response<String>, core::convert::Infallible> {
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        Ok(http::Response::new(info.request_id.to_string()))
+    }
+
+    let mut request = http::Request::new(());
+    request.extensions_mut().insert(RequestInfo {
+        protocol: Protocol::Http,
+        request_id: REQUEST_ID_VALUE.parse().unwrap(),
+        request_id_origin: tower_http_tracing::RequestIdOrigin::Inherited,
+        incoming_request_id: None,
+        correlation_id: None,
+        peer_service: None,
+        client_ip: None,
+        client_ip_display: None,
+        deadline: None,
+        trace_context: None,
+        details: ProtocolDetails::Http { version: http::Version::HTTP_11 },
+        request_id_source: None,
+        sampled: true,
+        started: std::time::Instant::now(),
+        clock: None,
+    });
+    let response = handler(request).await.unwrap();
+    assert_eq!(response.body().parse::<RequestId>().unwrap(), REQUEST_ID_VALUE.parse::<RequestId>().unwrap());
+}
+
+async fn policy_request(policy: RequestIdPolicy) -> (String, Option<String>) {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new(my_span, ResponseLogContext).with_request_id_policy(policy)).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        let ids = (info.request_id.to_string(), info.incoming_request_id.as_ref().map(ToString::to_string));
+        Ok::<_, core::convert::Infallible>(http::Response::new(ids))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(REQUEST_ID_VALUE));
+    let (parts, (request_id, incoming)) = service.oneshot(request).await.unwrap().into_parts();
+    assert_eq!(parts.headers.get(REQUEST_ID).unwrap(), request_id.as_str());
+    (request_id, incoming)
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_always_generate_request_id() {
+    let (request_id, incoming) = policy_request(RequestIdPolicy::AlwaysGenerate).await;
+    assert!(uuid_like(&request_id));
+    assert_eq!(incoming.as_deref(), Some(REQUEST_ID_VALUE));
+    assert!(logs_contain(&format!(r#"http.request_id="{request_id}" http.request_id.origin="generated" http.request_id.incoming="{REQUEST_ID_VALUE}""#)));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_prefer_incoming_request_id() {
+    let (request_id, incoming) = policy_request(RequestIdPolicy::Generate).await;
+    assert_eq!(request_id, REQUEST_ID_VALUE);
+    assert_eq!(incoming, None);
+    assert!(logs_contain(&format!(r#"http.request_id="{REQUEST_ID_VALUE}""#)));
+    assert!(!logs_contain("http.request_id.incoming"));
+}
+
+async fn generated_request_id(format: tower_http_tracing::RequestIdFormat) -> String {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span).with_request_id_generator(format)).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let response = service.oneshot(http::Request::new(())).await.unwrap();
+    response.headers().get(REQUEST_ID).unwrap().to_str().unwrap().to_owned()
+}
+
+#[tokio::test]
+async fn should_generate_request_id_in_configured_format() {
+    use tower_http_tracing::RequestIdFormat;
+
+    let request_id = generated_request_id(RequestIdFormat::UuidV4Hyphenated).await;
+    assert_eq!(request_id.len(), 36);
+    assert!(uuid_like(&request_id));
+    assert_eq!(&request_id[14..15], "4");
+
+    let request_id = generated_request_id(RequestIdFormat::UuidV4Simple).await;
+    assert_eq!(request_id.len(), 32);
+    assert!(request_id.bytes().all(|byt| byt.is_ascii_hexdigit() && !byt.is_ascii_uppercase()));
+    assert_eq!(&request_id[12..13], "4");
+
+    let mut request_ids = Vec::new();
+    for _ in 0..16 {
+        let request_id = generated_request_id(RequestIdFormat::UuidV7).await;
+        assert_eq!(request_id.len(), 36);
+        assert!(uuid_like(&request_id));
+        assert_eq!(&request_id[14..15], "7");
+        request_ids.push(request_id);
+    }
+    let mut sorted = request_ids.clone();
+    sorted.sort();
+    assert_eq!(sorted, request_ids);
+}
+
+#[derive(Clone, Default)]
+struct SnowflakeContext(std::sync::Arc<std::sync::atomic::AtomicU64>);
+
+impl LayerContext for SnowflakeContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn generate_request_id(&self, _: tower_http_tracing::RequestIdFormat) -> tower_http_tracing::RequestId {
+        let id = self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        tower_http_tracing::RequestId::from_bytes(format!("snowflake-{id}").as_bytes())
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_generate_request_id_via_context() {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new(my_span, SnowflakeContext::default())).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    for expected in ["snowflake-0", "snowflake-1"] {
+        let response = service.clone().oneshot(http::Request::new(())).await.unwrap();
+        assert_eq!(response.headers().get(REQUEST_ID).unwrap(), expected);
+        assert!(logs_contain(&format!(r#"http.request_id="{expected}""#)));
+    }
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(REQUEST_ID_VALUE));
+    let response = service.oneshot(request).await.unwrap();
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert!(!logs_contain("snowflake-2"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_insert_missing_response_request_id_when_keeping_existing() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_response_request_id_conflict(ResponseRequestIdConflict::KeepExisting);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(REQUEST_ID_VALUE));
+    let response = service.oneshot(request).await.unwrap();
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert!(!logs_contain("http.request_id.response_mismatch"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_echo_request_id_when_disabled() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_response_request_id(false);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.request_id.to_string()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static(REQUEST_ID_VALUE));
+    let response = service.oneshot(request).await.unwrap();
+    assert!(response.headers().get(REQUEST_ID).is_none());
+    assert_eq!(response.body(), REQUEST_ID_VALUE);
+    assert!(logs_contain(&format!(r#"http.request_id="{REQUEST_ID_VALUE}""#)));
+}
+
+async fn validated_request(validation: tower_http_tracing::RequestIdValidation, request_id: &'static [u8]) -> String {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_request_id_validation(validation);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.request_id.to_string()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_bytes(request_id).unwrap());
+    service.oneshot(request).await.unwrap().into_body()
+}
+
+#[test]
+fn should_not_allow_line_breaks_and_nul_in_request_id() {
+    //`http` rejects these bytes already, leaving tab, space and non-ASCII bytes to be validated by middleware
+    for invalid in [&b"foo\n fake-log-line"[..], b"foo\r\nbar", b"foo\0bar"] {
+        assert!(http::HeaderValue::from_bytes(invalid).is_err());
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_reject_invalid_request_id() {
+    use tower_http_tracing::RequestIdValidation;
+
+    for invalid in [&b"foo\t fake-log-line"[..], b"foo\xffbar"] {
+        let request_id = validated_request(RequestIdValidation::Reject, invalid).await;
+        assert!(uuid_like(&request_id), "{request_id}");
+    }
+    assert!(logs_contain("http.request_id.rejected=true"));
+    assert!(!logs_contain("fake-log-line"));
+
+    let request_id = validated_request(RequestIdValidation::Reject, b"dGVzdC1pZA+/=").await;
+    assert_eq!(request_id, "dGVzdC1pZA+/=");
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_strip_invalid_request_id() {
+    use tower_http_tracing::RequestIdValidation;
+
+    assert_eq!(validated_request(RequestIdValidation::Strip, b"foo\t fake-log-line").await, "foofake-log-line");
+    assert_eq!(validated_request(RequestIdValidation::Strip, b"foo\xffbar").await, "foobar");
+    assert_eq!(validated_request(RequestIdValidation::Strip, b"dGVzdC1pZA+/=").await, "dGVzdC1pZA+/=");
+    assert!(!logs_contain("http.request_id.rejected"));
+
+    let request_id = validated_request(RequestIdValidation::Strip, b" \t\x80").await;
+    assert!(uuid_like(&request_id), "{request_id}");
+    assert!(logs_contain("http.request_id.rejected=true"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_reject_empty_request_id() {
+    use tower_http_tracing::RequestIdValidation;
+
+    for validation in [RequestIdValidation::Strip, RequestIdValidation::Reject] {
+        for empty in [&b""[..], b" ", b" \t "] {
+            let request_id = validated_request(validation, empty).await;
+            assert!(uuid_like(&request_id), "{validation:?}: {request_id:?}");
+        }
+    }
+    assert!(logs_contain("http.request_id.rejected=true"));
+}
+
+#[derive(Clone)]
+struct BinaryIdContext;
+
+impl LayerContext for BinaryIdContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn generate_request_id(&self, _: tower_http_tracing::RequestIdFormat) -> tower_http_tracing::RequestId {
+        tower_http_tracing::RequestId::from_bytes(b"\x00\x01\n\xff")
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_render_non_utf8_request_id_as_hex() {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|request: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.request_id.to_string()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_bytes(b"\xde\xad\xbe\xef").unwrap());
+    let response = service.oneshot(request).await.unwrap();
+    //Raw bytes are valid header value, hence echoed as they are
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap().as_bytes(), b"\xde\xad\xbe\xef");
+    assert_eq!(response.body(), "deadbeef");
+    assert!(logs_contain("http.request_id=deadbeef"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_echo_hex_request_id_when_not_valid_header_value() {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new(my_span, BinaryIdContext)).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let response = service.oneshot(http::Request::new(())).await.unwrap();
+    assert_eq!(response.headers().get(REQUEST_ID).unwrap(), "00010aff");
+    assert!(logs_contain("http.request_id=00010aff"));
+}
+
+async fn duplicated_request(values: &[&'static str]) -> String {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new(my_span, ResponseLogContext)).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.request_id.to_string()))
+    });
+
+    let mut request = http::Request::new(());
+    for value in values {
+        request.headers_mut().append(REQUEST_ID, http::HeaderValue::from_static(value));
+    }
+    service.oneshot(request).await.unwrap().into_body()
+}
+
+#[tokio::test]
+async fn should_record_duplicate_request_ids() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let captured = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let _guard = tracing_subscriber::registry().with(WarnCapture(captured.clone())).set_default();
+
+    assert_eq!(duplicated_request(&[REQUEST_ID_VALUE]).await, REQUEST_ID_VALUE);
+    assert!(captured.lock().unwrap().is_empty());
+
+    assert_eq!(duplicated_request(&[REQUEST_ID_VALUE, "proxy-id"]).await, REQUEST_ID_VALUE);
+    let warnings = core::mem::take(&mut *captured.lock().unwrap());
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("header=x-request-id values=proxy-id"), "{warnings:?}");
+
+    assert_eq!(duplicated_request(&[REQUEST_ID_VALUE, "proxy-id", "gateway-id"]).await, REQUEST_ID_VALUE);
+    let warnings = core::mem::take(&mut *captured.lock().unwrap());
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("values=proxy-id,gateway-id"), "{warnings:?}");
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_duplicates_count_in_span() {
+    duplicated_request(&[REQUEST_ID_VALUE]).await;
+    assert!(!logs_contain("http.request_id.duplicates"));
+
+    duplicated_request(&[REQUEST_ID_VALUE, "proxy-id"]).await;
+    assert!(logs_contain("http.request_id.duplicates=1"));
+
+    duplicated_request(&[REQUEST_ID_VALUE, "proxy-id", "gateway-id"]).await;
+    assert!(logs_contain("http.request_id.duplicates=2"));
+}
+
+async fn correlated_request(headers: &[(http::HeaderName, &'static str)]) -> (Option<String>, http::HeaderMap) {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext).with_correlation_id_header(CORRELATION_ID);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.correlation_id.as_ref().map(ToString::to_string)))
+    });
+
+    let mut request = http::Request::new(());
+    for (name, value) in headers {
+        request.headers_mut().insert(name, http::HeaderValue::from_static(value));
+    }
+    let (parts, correlation_id) = service.oneshot(request).await.unwrap().into_parts();
+    (correlation_id, parts.headers)
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_correlation_id_alongside_request_id() {
+    let (correlation_id, headers) = correlated_request(&[(REQUEST_ID, REQUEST_ID_VALUE), (CORRELATION_ID, "correlation")]).await;
+    assert_eq!(correlation_id.as_deref(), Some("correlation"));
+    assert_eq!(headers.get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert_eq!(headers.get(CORRELATION_ID).unwrap(), "correlation");
+    assert!(logs_contain("http.request_id=\"request-id\""));
+    assert!(logs_contain("http.correlation_id=\"correlation\""));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_correlation_id_without_request_id() {
+    let (correlation_id, headers) = correlated_request(&[(CORRELATION_ID, "correlation")]).await;
+    assert_eq!(correlation_id.as_deref(), Some("correlation"));
+    assert_eq!(headers.get(CORRELATION_ID).unwrap(), "correlation");
+    //Correlation id is never used as request's id
+    assert!(uuid_like(headers.get(REQUEST_ID).unwrap().to_str().unwrap()));
+    assert!(logs_contain("http.correlation_id=\"correlation\""));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_skip_correlation_id_when_absent() {
+    let (correlation_id, headers) = correlated_request(&[(REQUEST_ID, REQUEST_ID_VALUE)]).await;
+    assert_eq!(correlation_id, None);
+    assert_eq!(headers.get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert!(!headers.contains_key(CORRELATION_ID));
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.correlation_id"));
+
+    let (correlation_id, headers) = correlated_request(&[]).await;
+    assert_eq!(correlation_id, None);
+    assert!(!headers.contains_key(CORRELATION_ID));
+}
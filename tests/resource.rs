@@ -0,0 +1,73 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestSummary};
+use tower_http_tracing::resource::{ResourceSnapshot, ResourceTracker, ThreadLocalTracker};
+
+use core::{future::Future, pin::pin, task};
+use std::io;
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+struct ScriptedTracker(u64);
+
+impl ResourceTracker for ScriptedTracker {
+    fn start(&self) -> ResourceSnapshot {
+        ResourceSnapshot(100)
+    }
+
+    fn finish(&self, snapshot: ResourceSnapshot) -> u64 {
+        assert_eq!(snapshot, ResourceSnapshot(100));
+        self.0
+    }
+}
+
+fn on_complete(_: &tracing::Span, _: &RequestSummary) {
+    tracing::info!("COMPLETE");
+}
+
+fn layer(allocated: u64) -> HttpRequestLayer {
+    HttpRequestLayer::new_simple(my_span).with_resource_tracker(ScriptedTracker(allocated)).with_on_complete(on_complete)
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_allocated_bytes_on_response() {
+    let service = ServiceBuilder::new().layer(layer(512)).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, io::Error>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+    assert!(logs_contain("process.request.allocated_bytes=512"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_allocated_bytes_on_error() {
+    let service = ServiceBuilder::new().layer(layer(1024)).service_fn(|_: http::Request<()>| async move {
+        Err::<http::Response<()>, _>(io::Error::other("failed"))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap_err();
+    assert!(logs_contain("process.request.allocated_bytes=1024"));
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn should_record_allocated_bytes_on_cancel() {
+    let mut service = ServiceBuilder::new().layer(layer(2048)).service_fn(|_: http::Request<()>| core::future::pending::<Result<http::Response<()>, io::Error>>());
+
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    assert!(service.poll_ready(&mut ctx).is_ready());
+    {
+        let mut fut = pin!(service.call(http::Request::new(())));
+        assert!(fut.as_mut().poll(&mut ctx).is_pending());
+    }
+    assert!(logs_contain("process.request.allocated_bytes=2048"));
+}
+
+#[test]
+fn should_track_thread_local_allocations() {
+    let snapshot = ThreadLocalTracker.start();
+    ThreadLocalTracker::add(64);
+    ThreadLocalTracker::add(32);
+    assert_eq!(ThreadLocalTracker.finish(snapshot), 96);
+}
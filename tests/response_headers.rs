@@ -0,0 +1,88 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, REQUEST_ID};
+
+use std::sync::{Arc, Mutex};
+use tower::{Service, ServiceBuilder, ServiceExt};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+static ALLOWED: http::HeaderName = http::HeaderName::from_static("x-allowed");
+static ALLOWLIST: [&http::HeaderName; 2] = [&http::header::CONTENT_TYPE, &ALLOWED];
+
+type Captured = Arc<Mutex<Vec<String>>>;
+
+//Captures recorded `http.response.headers_stripped` and warnings of middleware
+struct Capture(Captured);
+
+struct Visitor<'a>(&'a mut Vec<String>, &'static str);
+
+impl tracing::field::Visit for Visitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+        if field.name() == self.1 {
+            self.0.push(format!("{}={value:?}", field.name()));
+        }
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for Capture {
+    fn on_record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        values.record(&mut Visitor(&mut self.0.lock().unwrap(), "http.response.headers_stripped"));
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        if *event.metadata().level() == tracing::Level::WARN && event.metadata().target() == tower_http_tracing::targets::SPAN {
+            event.record(&mut Visitor(&mut self.0.lock().unwrap(), "headers"));
+        }
+    }
+}
+
+fn take(captured: &Captured) -> Vec<String> {
+    core::mem::take(&mut *captured.lock().unwrap())
+}
+
+#[tokio::test]
+async fn should_strip_response_headers_not_allowlisted() {
+    let captured = Captured::default();
+    let _guard = tracing_subscriber::registry().with(Capture(captured.clone())).set_default();
+
+    let layer = HttpRequestLayer::new_simple(my_span).with_response_header_allowlist(&ALLOWLIST);
+    let mut service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        let headers = response.headers_mut();
+        headers.insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static("text/plain"));
+        headers.insert(&ALLOWED, http::HeaderValue::from_static("1"));
+        headers.insert(http::header::SERVER, http::HeaderValue::from_static("internal/1.0"));
+        headers.append("x-debug", http::HeaderValue::from_static("a"));
+        headers.append("x-debug", http::HeaderValue::from_static("b"));
+        Ok::<_, std::io::Error>(response)
+    });
+
+    let response = service.ready().await.unwrap().call(http::Request::new(())).await.unwrap();
+    let headers = response.headers();
+    assert_eq!(headers.len(), 3);
+    assert!(headers.contains_key(REQUEST_ID));
+    assert!(headers.contains_key(http::header::CONTENT_TYPE));
+    assert!(headers.contains_key(&ALLOWED));
+    assert_eq!(take(&captured), ["http.response.headers_stripped=3", "headers=server,x-debug"]);
+
+    //Warning is emitted only once per header name
+    service.ready().await.unwrap().call(http::Request::new(())).await.unwrap();
+    assert_eq!(take(&captured), ["http.response.headers_stripped=3"]);
+}
+
+#[tokio::test]
+async fn should_not_strip_response_headers_by_default() {
+    let captured = Captured::default();
+    let _guard = tracing_subscriber::registry().with(Capture(captured.clone())).set_default();
+
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        response.headers_mut().insert(http::header::SERVER, http::HeaderValue::from_static("internal/1.0"));
+        Ok::<_, std::io::Error>(response)
+    });
+
+    let response = service.oneshot(http::Request::new(())).await.unwrap();
+    assert!(response.headers().contains_key(http::header::SERVER));
+    assert!(take(&captured).is_empty());
+}
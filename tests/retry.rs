@@ -0,0 +1,57 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, IDEMPOTENCY_KEY};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const RETRY_ATTEMPT: http::HeaderName = http::HeaderName::from_static("x-retry-attempt");
+
+async fn request(layer: HttpRequestLayer, retry_attempt: Option<&'static str>, idempotency_key: bool) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    if let Some(retry_attempt) = retry_attempt {
+        req.headers_mut().insert(RETRY_ATTEMPT, http::HeaderValue::from_static(retry_attempt));
+    }
+    if idempotency_key {
+        req.headers_mut().insert(IDEMPOTENCY_KEY, http::HeaderValue::from_static("8e03978e-40d5-43e8-bc93-6894a57f9324"));
+    }
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_retry_attempt() {
+    request(HttpRequestLayer::new_simple(my_span).with_retry_header(RETRY_ATTEMPT), Some("2"), true).await;
+    assert!(logs_contain("http.request.resend_count=2 http.request.idempotency_key.present=true"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_skip_malformed_retry_attempt() {
+    request(HttpRequestLayer::new_simple(my_span).with_retry_header(RETRY_ATTEMPT), Some("-1"), false).await;
+    request(HttpRequestLayer::new_simple(my_span).with_retry_header(RETRY_ATTEMPT), Some("second"), false).await;
+    request(HttpRequestLayer::new_simple(my_span).with_retry_header(RETRY_ATTEMPT), Some("4294967296"), false).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("http.request.resend_count"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_idempotency_key_without_retry_attempt() {
+    request(HttpRequestLayer::new_simple(my_span).with_retry_header(RETRY_ATTEMPT), None, true).await;
+    assert!(logs_contain("http.request.idempotency_key.present=true"));
+    assert!(!logs_contain("http.request.resend_count"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_retry_by_default() {
+    request(HttpRequestLayer::new_simple(my_span), Some("2"), true).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("http.request.resend_count"));
+    assert!(!logs_contain("http.request.idempotency_key.present"));
+}
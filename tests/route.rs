@@ -0,0 +1,73 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext, MatchedRoute, SpanHandle};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+//Logs event once response fields are recorded
+#[derive(Copy, Clone)]
+struct ResponseLogContext;
+
+impl LayerContext for ResponseLogContext {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_route_from_response_extensions() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        let mut response = http::Response::new(());
+        response.extensions_mut().insert(MatchedRoute("/users/{id}".to_owned()));
+        Ok::<_, core::convert::Infallible>(response)
+    });
+
+    service.oneshot(http::Request::get("/users/12345").body(()).unwrap()).await.unwrap();
+    assert!(logs_contain(r#"url.path="/users/12345""#));
+    assert!(logs_contain(r#"http.route="/users/{id}""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_route_reported_by_handler() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|req: http::Request<()>| async move {
+        req.extensions().get::<SpanHandle>().unwrap().record_route("/users/{id}");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::get("/users/12345").body(()).unwrap()).await.unwrap();
+    assert!(logs_contain(r#"http.route="/users/{id}""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_route_without_router() {
+    let layer = HttpRequestLayer::new(my_span, ResponseLogContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::get("/users/12345").body(()).unwrap()).await.unwrap();
+    assert!(logs_contain("RESPONSE"));
+    assert!(!logs_contain("http.route"));
+}
+
+async fn get_user(path: axum::extract::MatchedPath) -> (axum::Extension<MatchedRoute>, &'static str) {
+    (axum::Extension(MatchedRoute(path.as_str().to_owned())), "user")
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_route_matched_by_axum() {
+    let router = axum::Router::new().route("/users/{id}", axum::routing::get(get_user))
+                                    .layer(HttpRequestLayer::new(my_span, ResponseLogContext));
+
+    let response = router.oneshot(http::Request::get("/users/12345").body(axum::body::Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert!(logs_contain(r#"http.route="/users/{id}""#));
+}
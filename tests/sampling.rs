@@ -0,0 +1,114 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext, RequestInfo, REQUEST_ID};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const REQUEST_ID_VALUE: &str = "request-id";
+
+//Samples requests with `x-sample` header
+#[derive(Copy, Clone)]
+struct HeaderSampler;
+
+impl LayerContext for HeaderSampler {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn should_sample<T>(&self, request: &http::Request<T>) -> bool {
+        request.headers().contains_key("x-sample")
+    }
+
+    fn extract_client_ip(&self, _: &tracing::Span, _: &http::request::Parts) -> Option<std::net::IpAddr> {
+        tracing::info!("EXTRACT_CLIENT_IP");
+        None
+    }
+}
+
+async fn request(layer: HttpRequestLayer<HeaderSampler>, sample: bool, request_id: Option<&'static str>) -> (bool, String, http::HeaderMap) {
+    request_with_headers(layer, sample, request_id.iter().map(|request_id| (REQUEST_ID, *request_id))).await
+}
+
+async fn request_with_headers(layer: HttpRequestLayer<HeaderSampler>, sample: bool, headers: impl IntoIterator<Item = (http::HeaderName, &'static str)>) -> (bool, String, http::HeaderMap) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        let info = request.extensions().get::<RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new((info.sampled, info.request_id.to_string())))
+    });
+
+    let mut request = http::Request::get("/api").body(()).unwrap();
+    if sample {
+        request.headers_mut().insert("x-sample", http::HeaderValue::from_static("1"));
+    }
+    for (name, value) in headers {
+        request.headers_mut().append(name, http::HeaderValue::from_static(value));
+    }
+    let (parts, (sampled, request_id)) = service.oneshot(request).await.unwrap().into_parts();
+    (sampled, request_id, parts.headers)
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_emit_span_for_sampled_request() {
+    let layer = HttpRequestLayer::new(my_span, HeaderSampler);
+    let (sampled, request_id, headers) = request(layer.clone(), true, Some(REQUEST_ID_VALUE)).await;
+    assert!(sampled);
+    assert_eq!(request_id, REQUEST_ID_VALUE);
+    assert_eq!(headers.get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert!(logs_contain("url.path=\"/api\""));
+    assert!(logs_contain("http.request_id=\"request-id\""));
+    assert!(logs_contain("EXTRACT_CLIENT_IP"));
+
+    let stats = layer.stats();
+    assert_eq!(stats.spans_created, 1);
+    assert_eq!(stats.spans_unsampled, 0);
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_skip_span_for_unsampled_request() {
+    let layer = HttpRequestLayer::new(my_span, HeaderSampler);
+    let (sampled, request_id, headers) = request(layer.clone(), false, Some(REQUEST_ID_VALUE)).await;
+    assert!(!sampled);
+    //Request id is still propagated
+    assert_eq!(request_id, REQUEST_ID_VALUE);
+    assert_eq!(headers.get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("url.path"));
+    assert!(!logs_contain("http.request_id"));
+
+    let (sampled, request_id, headers) = request(layer.clone(), false, None).await;
+    assert!(!sampled);
+    assert_eq!(headers.get(REQUEST_ID).unwrap().to_str().unwrap(), request_id);
+
+    let stats = layer.stats();
+    assert_eq!(stats.spans_created, 0);
+    assert_eq!(stats.spans_unsampled, 2);
+    assert_eq!(stats.ids_inherited, 1);
+    assert_eq!(stats.ids_generated, 1);
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_inspect_unsampled_request() {
+    let layer = HttpRequestLayer::new(my_span, HeaderSampler).with_correlation_id_header(http::HeaderName::from_static("x-correlation-id"));
+    let (sampled, request_id, headers) = request_with_headers(layer, false, [
+        (REQUEST_ID, REQUEST_ID_VALUE),
+        (REQUEST_ID, "duplicate-id"),
+        (http::HeaderName::from_static("x-correlation-id"), "correlation-id"),
+        (http::header::USER_AGENT, "curl/8.0"),
+    ]).await;
+    assert!(!sampled);
+    assert_eq!(request_id, REQUEST_ID_VALUE);
+    assert_eq!(headers.get(REQUEST_ID).unwrap(), REQUEST_ID_VALUE);
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("EXTRACT_CLIENT_IP"));
+    assert!(!logs_contain("Request id header is duplicated"));
+    assert!(!logs_contain("curl"));
+}
+
+#[tokio::test]
+async fn should_sample_by_default() {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|request: http::Request<()>| async move {
+        Ok::<_, core::convert::Infallible>(http::Response::new(request.extensions().get::<RequestInfo>().expect("to have request info").sampled))
+    });
+    assert!(service.oneshot(http::Request::new(())).await.unwrap().into_body());
+}
@@ -0,0 +1,87 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, LayerContext};
+use tower_http_tracing::semconv::EcsSemconv;
+
+use std::net::IpAddr;
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(otel_span("request", tracing::Level::INFO));
+make_request_spanner!(ecs_span("request", tracing::Level::INFO, semconv = ecs));
+
+#[derive(Copy, Clone)]
+struct Context;
+
+impl LayerContext for Context {
+    const INSPECT_HEADERS: &'static [&'static http::HeaderName] = &[];
+
+    fn extract_client_ip(&self, _: &tracing::Span, _: &http::request::Parts) -> Option<IpAddr> {
+        "127.0.0.1".parse().ok()
+    }
+
+    fn on_response_ok<T>(&self, _: &tracing::Span, _: &mut http::Response<T>) {
+        tracing::info!("RESPONSE");
+    }
+}
+
+async fn request(layer: HttpRequestLayer<Context>) {
+    request_with_method(layer, http::Method::GET).await
+}
+
+async fn request_with_method(layer: HttpRequestLayer<Context>, method: http::Method) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    *req.method_mut() = method;
+    req.headers_mut().insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static("request-ID"));
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_otel_fields_by_default() {
+    request(HttpRequestLayer::new(otel_span, Context)).await;
+
+    assert!(logs_contain(r#"http.request_id="request-ID" http.request_id.origin="inherited" client.address=127.0.0.1"#));
+    assert!(logs_contain(r#"network.protocol.name="http" network.protocol.version="1.1""#));
+    assert!(logs_contain("http.response.status_code=200"));
+    assert!(!logs_contain("client.ip"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_ecs_fields() {
+    request(HttpRequestLayer::new(ecs_span, Context).with_semantic_convention(EcsSemconv)).await;
+
+    assert!(logs_contain(r#"http.request.id="request-ID" http.request_id.origin="inherited" client.ip=127.0.0.1"#));
+    assert!(logs_contain(r#"network.protocol="http" http.version="1.1""#));
+    assert!(logs_contain("http.response.status_code=200"));
+    assert!(!logs_contain("client.address="));
+    assert!(!logs_contain("span.kind"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_standard_method() {
+    request_with_method(HttpRequestLayer::new(otel_span, Context), http::Method::PATCH).await;
+
+    assert!(logs_contain(r#"http.request.method="PATCH" url.path="/""#));
+    assert!(!logs_contain("http.request.method_original"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_non_standard_method_as_other() {
+    request_with_method(HttpRequestLayer::new(otel_span, Context), http::Method::from_bytes(b"PROPFIND").unwrap()).await;
+
+    assert!(logs_contain(r#"http.request.method="_OTHER" http.request.method_original="PROPFIND" url.path="/""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_non_standard_method_as_is_with_ecs() {
+    request_with_method(HttpRequestLayer::new(ecs_span, Context).with_semantic_convention(EcsSemconv), http::Method::from_bytes(b"PROPFIND").unwrap()).await;
+
+    assert!(logs_contain(r#"http.request.method="PROPFIND" url.path="/""#));
+    assert!(!logs_contain("_OTHER"));
+}
@@ -0,0 +1,71 @@
+use tower_http_tracing::{make_request_spanner, Endpoint, HttpRequestLayer};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn request(layer: HttpRequestLayer, uri: &'static str, host: Option<&'static str>) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::get(uri);
+    if let Some(host) = host {
+        request = request.header(http::header::HOST, host);
+    }
+    service.oneshot(request.body(()).unwrap()).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_server_address_with_explicit_port() {
+    request(HttpRequestLayer::new_simple(my_span), "/", Some("example.com:8443")).await;
+    assert!(logs_contain(r#"server.address="example.com" server.port=8443"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_ipv6_server_address_without_brackets() {
+    request(HttpRequestLayer::new_simple(my_span), "/", Some("[::1]:3000")).await;
+    assert!(logs_contain(r#"server.address="::1" server.port=3000"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_prefer_host_header_over_authority() {
+    request(HttpRequestLayer::new_simple(my_span), "https://example.org/", Some("example.com")).await;
+    assert!(logs_contain(r#"server.address="example.com" server.port=443"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_fall_back_to_authority_and_default_port() {
+    request(HttpRequestLayer::new_simple(my_span), "http://example.org/", None).await;
+    assert!(logs_contain(r#"server.address="example.org" server.port=80"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_server_address_without_host() {
+    request(HttpRequestLayer::new_simple(my_span), "/", None).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("server.address"));
+    assert!(!logs_contain("server.port"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_malformed_host() {
+    request(HttpRequestLayer::new_simple(my_span), "/", Some("example.com:http")).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("server.address"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_take_port_from_endpoint_when_scheme_is_unknown() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_extract_endpoint(|_| Some(Endpoint { name: "public", port: 8080 }));
+    request(layer, "/", Some("example.com")).await;
+    assert!(logs_contain(r#"server.address="example.com" server.endpoint="public" server.port=8080"#));
+}
@@ -0,0 +1,45 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn request(layer: HttpRequestLayer, cookie: Option<&'static str>) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    if let Some(cookie) = cookie {
+        req.headers_mut().insert(http::header::COOKIE, http::HeaderValue::from_static(cookie));
+    }
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_session_id() {
+    request(HttpRequestLayer::new_simple(my_span).with_session_cookie("sid"), Some("locale=en; sid=abc123; sid=other")).await;
+    assert!(logs_contain(r#"session.id="abc123""#));
+    assert!(!logs_contain("other"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_hashed_session_id() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_session_cookie("sid").with_session_id_hash(|session_id| format!("hash-of-{}", session_id.len()));
+    request(layer, Some("sid=abc123")).await;
+    assert!(logs_contain(r#"session.id="hash-of-6""#));
+    assert!(!logs_contain("abc123"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_absent_session_id() {
+    request(HttpRequestLayer::new_simple(my_span).with_session_cookie("sid"), Some("locale=en; sidx=abc123")).await;
+    request(HttpRequestLayer::new_simple(my_span).with_session_cookie("sid"), None).await;
+    request(HttpRequestLayer::new_simple(my_span), Some("sid=abc123")).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("session.id"));
+}
@@ -0,0 +1,61 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestInfo, REQUEST_ID};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+static SKIP_PATHS: [&str; 2] = ["/healthz", "/readyz"];
+
+async fn request(layer: HttpRequestLayer, uri: &'static str) -> (bool, http::HeaderMap) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        tracing::info!(path = request.uri().path(), "HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(request.extensions().get::<RequestInfo>().is_some()))
+    });
+
+    let request = http::Request::get(uri).body(()).unwrap();
+    let (parts, has_info) = service.oneshot(request).await.unwrap().into_parts();
+    (has_info, parts.headers)
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_bypass_skipped_paths() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_skip_paths(&SKIP_PATHS);
+
+    let (has_info, headers) = request(layer.clone(), "/healthz").await;
+    assert!(!has_info);
+    assert!(!headers.contains_key(REQUEST_ID));
+    assert!(logs_contain("HANDLER path=\"/healthz\""));
+    assert!(!logs_contain("url.path=\"/healthz\""));
+
+    //Query is not part of path
+    let (has_info, _) = request(layer.clone(), "/readyz?verbose=1").await;
+    assert!(!has_info);
+    assert!(!logs_contain("url.path=\"/readyz\""));
+
+    let (has_info, headers) = request(layer.clone(), "/api").await;
+    assert!(has_info);
+    assert!(headers.contains_key(REQUEST_ID));
+    assert!(logs_contain("url.path=\"/api\""));
+
+    //Only exact match is skipped
+    let (has_info, _) = request(layer.clone(), "/healthz/deep").await;
+    assert!(has_info);
+    assert!(logs_contain("url.path=\"/healthz/deep\""));
+
+    let stats = layer.stats();
+    assert_eq!(stats.requests_skipped, 2);
+    assert_eq!(stats.spans_created, 2);
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_trace_all_paths_by_default() {
+    let layer = HttpRequestLayer::new_simple(my_span);
+
+    let (has_info, headers) = request(layer.clone(), "/healthz").await;
+    assert!(has_info);
+    assert!(headers.contains_key(REQUEST_ID));
+    assert!(logs_contain("url.path=\"/healthz\""));
+    assert_eq!(layer.stats().requests_skipped, 0);
+}
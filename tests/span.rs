@@ -1,4 +1,4 @@
-use tower_http_tracing::{make_request_spanner, RequestSpan, Protocol, HttpRequestLayer, LayerContext};
+use tower_http_tracing::{make_request_spanner, RequestSpan, RequestIdOrigin, Protocol, HttpRequestLayer, LayerContext, SpanHandle};
 
 use std::net::IpAddr;
 use tower::{ServiceBuilder, ServiceExt};
@@ -34,7 +34,7 @@ fn should_generate_grpc_info() {
     tracing::info!("LOG");
     drop(_guard);
 
-    let expected_span = r#"should_generate_grpc_info:request{span.kind="server" http.request.method="GET" url.path="/grpc.heatlh.v1.Health/Check" url.scheme="grpc" http.request_id="request-ID" client.address=127.0.0.1 network.protocol.name="grpc""#;
+    let expected_span = r#"should_generate_grpc_info:request{span.kind="server" http.request.method="GET" url.path="/grpc.heatlh.v1.Health/Check" url.scheme="grpc" server.address="localhost" http.request_id="request-ID" http.request_id.origin="inherited" client.address=127.0.0.1 client.address.family="ipv4" network.protocol.name="grpc" network.protocol.version="1.1" rpc.service="grpc.heatlh.v1.Health" rpc.method="Check"}"#;
     assert!(logs_contain(expected_span));
 }
 
@@ -55,10 +55,31 @@ fn should_generate_grpc_info_with_extra() {
     tracing::info!("LOG");
     drop(_guard);
 
-    let expected_span = r#"span.kind="server" service_name="EXTRA" http.request.method="GET" url.path="/grpc.heatlh.v1.Health/Check" url.scheme="grpc" http.request_id="request-ID" client.address=127.0.0.1 network.protocol.name="grpc"#;
+    let expected_span = r#"span.kind="server" service_name="EXTRA" http.request.method="GET" url.path="/grpc.heatlh.v1.Health/Check" url.scheme="grpc" server.address="localhost" http.request_id="request-ID" http.request_id.origin="inherited" client.address=127.0.0.1 client.address.family="ipv4" network.protocol.name="grpc" network.protocol.version="1.1" rpc.service="grpc.heatlh.v1.Health" rpc.method="Check"}"#;
     assert!(logs_contain(expected_span));
 }
 
+#[test]
+#[tracing_test::traced_test]
+fn should_not_record_rpc_of_invalid_grpc_path() {
+    let mut req = http::Request::new(());
+    req.headers_mut().insert(http::header::CONTENT_TYPE, http::header::HeaderValue::from_static("application/grpc"));
+    *req.uri_mut() = http::Uri::from_static("grpc://localhost/grpc.heatlh.v1.Health");
+    let (parts, ()) = req.into_parts();
+
+    let span = my_span();
+    let span = RequestSpan::new(&TestContext, span, &parts);
+    assert_eq!(span.info.protocol, Protocol::Grpc);
+
+    let _guard = span.span.enter();
+    tracing::info!("LOG");
+    drop(_guard);
+
+    assert!(logs_contain(r#"network.protocol.name="grpc" network.protocol.version="1.1"}"#));
+    assert!(!logs_contain("rpc.service"));
+    assert!(!logs_contain("rpc.method"));
+}
+
 #[test]
 #[tracing_test::traced_test]
 fn should_generate_http_info() {
@@ -71,15 +92,53 @@ fn should_generate_http_info() {
     let span = my_span();
     let span = RequestSpan::new(&TestContext, span, &parts);
     assert_eq!(span.info.protocol, Protocol::Http);
+    assert_eq!(span.info.request_id_origin, RequestIdOrigin::Inherited);
 
     let _guard = span.span.enter();
     tracing::info!("LOG");
     drop(_guard);
 
-    let expected_span = r#"should_generate_http_info:request{span.kind="server" http.request.method="GET" url.path="/index.html" url.scheme="http" http.request_id="request-ID" client.address=127.0.0.1 network.protocol.name="http" network.protocol.version=1.1"#;
+    let expected_span = r#"should_generate_http_info:request{span.kind="server" http.request.method="GET" url.path="/index.html" url.scheme="http" server.address="localhost" server.port=80 http.request_id="request-ID" http.request_id.origin="inherited" client.address=127.0.0.1 client.address.family="ipv4" network.protocol.name="http" network.protocol.version="1.1""#;
     assert!(logs_contain(expected_span));
 }
 
+#[test]
+#[tracing_test::traced_test]
+fn should_record_http2_version_as_string() {
+    let mut req = http::Request::new(());
+    *req.version_mut() = http::Version::HTTP_2;
+    *req.uri_mut() = http::Uri::from_static("https://localhost/index.html");
+    let (parts, ()) = req.into_parts();
+
+    let span = my_span();
+    let span = RequestSpan::new(&TestContext, span, &parts);
+
+    let _guard = span.span.enter();
+    tracing::info!("LOG");
+    drop(_guard);
+
+    assert!(logs_contain(r#"network.protocol.name="http" network.protocol.version="2"}"#));
+}
+
+#[test]
+#[tracing_test::traced_test]
+fn should_generate_request_id_when_absent() {
+    let mut req = http::Request::new(());
+    *req.uri_mut() = http::Uri::from_static("http://localhost/index.html");
+    let (parts, ()) = req.into_parts();
+
+    let span = my_span();
+    let span = RequestSpan::new(&TestContext, span, &parts);
+    assert_eq!(span.info.request_id_origin, RequestIdOrigin::Generated);
+
+    let _guard = span.span.enter();
+    tracing::info!("LOG");
+    drop(_guard);
+
+    let expected_span = format!(r#"http.request_id="{}" http.request_id.origin="generated" client.address=127.0.0.1"#, span.info.request_id);
+    assert!(logs_contain(&expected_span));
+}
+
 #[tokio::test]
 async fn should_complete_successful_request_span() {
     const REQUEST_ID_VALUE: &str = "successful-id";
@@ -94,3 +153,126 @@ async fn should_complete_successful_request_span() {
     let request_id = res.headers().get(tower_http_tracing::REQUEST_ID).unwrap();
     assert_eq!(request_id.to_str().expect("request id must be valid string"), REQUEST_ID_VALUE);
 }
+
+async fn forwarded_for_first_request(forwarded_for: &'static str) {
+    let layer = HttpRequestLayer::new(my_span, TestContext).with_record_forwarded_for_first();
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("LOG");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(tower_http_tracing::X_FORWARDED_FOR, http::HeaderValue::from_static(forwarded_for));
+    service.oneshot(request).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_forwarded_for_single_entry() {
+    forwarded_for_first_request("203.0.113.7").await;
+    assert!(logs_contain("client.address=127.0.0.1"));
+    assert!(logs_contain(r#"http.request.header.x-forwarded-for.first="203.0.113.7""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_forwarded_for_first_of_multiple_entries() {
+    forwarded_for_first_request("not-an-ip,198.51.100.1, 10.0.0.1").await;
+    assert!(logs_contain(r#"http.request.header.x-forwarded-for.first="not-an-ip""#));
+    assert!(!logs_contain("198.51.100.1"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_forwarded_for_first_trimmed() {
+    forwarded_for_first_request("    2001:db8::1   ,   198.51.100.1  ").await;
+    assert!(logs_contain(r#"http.request.header.x-forwarded-for.first="2001:db8::1""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_cap_forwarded_for_first() {
+    forwarded_for_first_request("0123456789012345678901234567890123456789012345678901234567890123456789, 10.0.0.1").await;
+    assert!(logs_contain(r#"http.request.header.x-forwarded-for.first="0123456789012345678901234567890123456789012345678901234567890123"}"#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_propagate_request_span_into_background_task() {
+    const REQUEST_ID_VALUE: &str = "background-id";
+    let layer = HttpRequestLayer::new(my_span, TestContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let scope = request.extensions().get::<SpanHandle>().expect("to have span handle").scope();
+        let task = tokio::spawn(scope.instrument(async move {
+            tracing::info!("BACKGROUND");
+        }));
+        task.await.expect("to complete task");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static(REQUEST_ID_VALUE));
+    service.oneshot(request).await.unwrap();
+
+    assert!(logs_contain(r#"http.request_id="background-id""#));
+    assert!(logs_contain(r#"client.address=127.0.0.1 client.address.family="ipv4" network.protocol.name="http" network.protocol.version="1.1"}: span: BACKGROUND"#));
+}
+
+#[derive(Clone)]
+struct FakeStreamId(u64);
+
+fn extract_fake_stream_id(parts: &http::request::Parts) -> Option<u64> {
+    parts.extensions.get::<FakeStreamId>().map(|stream| stream.0)
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_stream_id() {
+    let layer = HttpRequestLayer::new(my_span, TestContext).with_extract_stream_info(extract_fake_stream_id);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("LOG");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut request = http::Request::new(());
+    request.extensions_mut().insert(FakeStreamId(5));
+    service.oneshot(request).await.unwrap();
+    assert!(logs_contain("network.stream.id=5"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_missing_stream_id() {
+    let layer = HttpRequestLayer::new(my_span, TestContext).with_extract_stream_info(extract_fake_stream_id);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("LOG");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+    assert!(logs_contain("LOG"));
+    assert!(!logs_contain("network.stream.id"));
+}
+
+tower_http_tracing::define_span_fields!(
+    UserId("user.id": str);
+    TenantId("tenant.id": u64);
+);
+make_request_spanner!(my_span_with_keys("request", tracing::Level::INFO, keys = [UserId, TenantId], service_name = "EXTRA"));
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_typed_span_fields() {
+    let layer = HttpRequestLayer::new(my_span_with_keys, TestContext);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let handle = request.extensions().get::<SpanHandle>().expect("to have span handle");
+        handle.record_key::<UserId>("42");
+        handle.record_key::<TenantId>(&7);
+        tracing::info!("LOG");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    service.oneshot(http::Request::new(())).await.unwrap();
+    assert!(logs_contain(r#"request{span.kind="server" service_name="EXTRA""#));
+    assert!(logs_contain(r#"user.id="42" tenant.id=7}: span: LOG"#));
+}
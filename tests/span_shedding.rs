@@ -0,0 +1,99 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, RequestInfo, RequestSummary};
+
+use core::{future::Future, pin::Pin, task};
+use std::cell::RefCell;
+use tower::{Service, ServiceBuilder};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+thread_local! {
+    static SHED: RefCell<Vec<bool>> = const { RefCell::new(Vec::new()) };
+}
+
+fn on_complete(_: &tracing::Span, summary: &RequestSummary) {
+    SHED.with_borrow_mut(|shed| shed.push(summary.shed));
+}
+
+type Gate = tokio::sync::oneshot::Receiver<()>;
+type ResponseFut = Pin<Box<dyn Future<Output = Result<http::Response<String>, std::io::Error>>>>;
+
+//Starts `count` requests, each waiting for its gate to be released
+fn start_requests<S: Service<http::Request<Gate>, Response = http::Response<String>, Error = std::io::Error>>(service: &mut S, count: usize) -> (Vec<ResponseFut>, Vec<tokio::sync::oneshot::Sender<()>>) where S::Future: 'static {
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    let mut futures = Vec::new();
+    let mut gates = Vec::new();
+    for _ in 0..count {
+        let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+        let request = http::Request::new(receiver);
+        assert!(service.poll_ready(&mut ctx).is_ready());
+        let mut fut: ResponseFut = Box::pin(service.call(request));
+        assert!(fut.as_mut().poll(&mut ctx).is_pending());
+        futures.push(fut);
+        gates.push(sender);
+    }
+    (futures, gates)
+}
+
+fn make_service(layer: HttpRequestLayer) -> impl Service<http::Request<Gate>, Response = http::Response<String>, Error = std::io::Error, Future: 'static> {
+    ServiceBuilder::new().layer(layer).service_fn(|req: http::Request<Gate>| async move {
+        let request_id = req.extensions().get::<RequestInfo>().expect("request info").request_id.to_string();
+        req.into_body().await.expect("to be released");
+        Ok::<_, std::io::Error>(http::Response::new(request_id))
+    })
+}
+
+#[test]
+fn should_shed_spans_above_limit() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_max_concurrent_spans(2).with_on_complete(on_complete);
+    let mut service = make_service(layer.clone());
+
+    let (mut futures, gates) = start_requests(&mut service, 5);
+    let stats = layer.stats();
+    assert_eq!(stats.spans_created, 2);
+    assert_eq!(stats.spans_shed, 3);
+    assert_eq!(stats.ids_generated, 5);
+
+    let mut ctx = task::Context::from_waker(task::Waker::noop());
+    for gate in gates {
+        gate.send(()).unwrap();
+    }
+    for fut in futures.iter_mut() {
+        match fut.as_mut().poll(&mut ctx) {
+            //Request id is assigned regardless of shedding
+            task::Poll::Ready(Ok(response)) => assert!(!response.body().is_empty()),
+            _ => panic!("request should complete"),
+        }
+    }
+    assert_eq!(SHED.with_borrow_mut(core::mem::take), [false, false, true, true, true]);
+
+    //Slots are released on completion
+    let (_futures, _gates) = start_requests(&mut service, 2);
+    let stats = layer.stats();
+    assert_eq!(stats.spans_created, 4);
+    assert_eq!(stats.spans_shed, 3);
+}
+
+#[test]
+fn should_release_span_slot_on_cancel() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_max_concurrent_spans(1);
+    let mut service = make_service(layer.clone());
+
+    let (futures, _gates) = start_requests(&mut service, 2);
+    assert_eq!(layer.stats().spans_created, 1);
+    assert_eq!(layer.stats().spans_shed, 1);
+    drop(futures);
+
+    let (_futures, _gates) = start_requests(&mut service, 1);
+    assert_eq!(layer.stats().spans_created, 2);
+    assert_eq!(layer.stats().spans_shed, 1);
+}
+
+#[test]
+fn should_not_shed_spans_by_default() {
+    let layer = HttpRequestLayer::new_simple(my_span);
+    let mut service = make_service(layer.clone());
+
+    let (_futures, _gates) = start_requests(&mut service, 8);
+    assert_eq!(layer.stats().spans_created, 8);
+    assert_eq!(layer.stats().spans_shed, 0);
+}
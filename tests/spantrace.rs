@@ -0,0 +1,39 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, SpanHandle};
+
+use std::sync::{Arc, Mutex};
+use tower::{ServiceBuilder, ServiceExt};
+use tracing_subscriber::layer::SubscriberExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[tokio::test]
+async fn should_capture_request_span_in_background_task() {
+    //fmt layer keeps formatted fields up to date with values recorded after span creation
+    let subscriber = tracing_subscriber::registry().with(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(std::io::sink))
+                                                   .with(tracing_error::ErrorLayer::default());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let rendered = Arc::new(Mutex::new(String::new()));
+    let output = rendered.clone();
+    let layer = HttpRequestLayer::new_simple(my_span);
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |req: http::Request<()>| {
+        let output = output.clone();
+        async move {
+            let scope = req.extensions().get::<SpanHandle>().unwrap().scope();
+            //Spawned task is not instrumented, hence request span is not entered there
+            let spantrace = tokio::spawn(async move {
+                scope.capture_spantrace()
+            }).await.unwrap();
+            *output.lock().unwrap() = spantrace.to_string();
+            Ok::<_, std::io::Error>(http::Response::new(()))
+        }
+    });
+
+    let mut req = http::Request::new(());
+    req.headers_mut().insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static("request-ID"));
+    service.oneshot(req).await.unwrap();
+
+    let rendered = rendered.lock().unwrap();
+    assert!(rendered.contains("spantrace::request"), "{rendered}");
+    assert!(rendered.contains(r#"http.request_id="request-ID""#), "{rendered}");
+}
@@ -0,0 +1,74 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use std::sync::{Arc, Mutex};
+
+use tower::{Layer, ServiceExt};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+type Timeline = Arc<Mutex<Vec<String>>>;
+
+//Records creation of spans and service events with their fields in chronological order
+struct TimelineLayer(Timeline);
+
+struct FieldVisitor<'a>(&'a mut String);
+
+impl tracing::field::Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for TimelineLayer {
+    fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, _: &tracing::span::Id, _: tracing_subscriber::layer::Context<'_, S>) {
+        self.0.lock().unwrap().push(format!("span {}", attrs.metadata().name()));
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        //Integrations may report their own errors
+        if event.metadata().target() != tower_http_tracing::targets::SERVICE {
+            return;
+        }
+        let mut line = format!("event {}", event.metadata().target());
+        event.record(&mut FieldVisitor(&mut line));
+        self.0.lock().unwrap().push(line);
+    }
+}
+
+async fn run_requests(layer: HttpRequestLayer) -> Vec<String> {
+    let timeline = Timeline::default();
+    let _guard = tracing_subscriber::registry().with(TimelineLayer(timeline.clone())).set_default();
+
+    let service = layer.layer(tower::service_fn(|_: http::Request<()>| async move {
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    }));
+    for _ in 0..3 {
+        service.clone().oneshot(http::Request::new(())).await.unwrap();
+    }
+
+    timeline.lock().unwrap().clone()
+}
+
+#[tokio::test]
+async fn should_emit_startup_event_once() {
+    let layer = HttpRequestLayer::new_simple(my_span).with_startup_event(&[("service.version", "1.2.3"), ("config.hash", "abcdef")]);
+    let timeline = run_requests(layer).await;
+
+    assert_eq!(timeline, [
+        concat!(
+            "event tower_http_tracing::service message=Service started service.metadata=service.version=1.2.3 config.hash=abcdef",
+            " library.name=\"tower-http-tracing\" library.version=\"", env!("CARGO_PKG_VERSION"), "\""
+        ),
+        "span request",
+        "span request",
+        "span request",
+    ]);
+}
+
+#[tokio::test]
+async fn should_not_emit_startup_event_by_default() {
+    let timeline = run_requests(HttpRequestLayer::new_simple(my_span)).await;
+    assert_eq!(timeline, ["span request", "span request", "span request"]);
+}
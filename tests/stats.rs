@@ -0,0 +1,78 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, MiddlewareStatsSnapshot, RequestIdOrigin, RequestIdPolicy, RequestIdValidation, REQUEST_ID};
+use tower_http_tracing::propagation::PropagationFormat;
+
+use tower::{Service, ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+#[tokio::test]
+async fn should_count_middleware_stats() {
+    let layer = HttpRequestLayer::new_simple(my_span);
+    assert_eq!(layer.stats(), MiddlewareStatsSnapshot::default());
+
+    let mut service = ServiceBuilder::new().layer(layer.clone()).service_fn(|_: http::Request<()>| async move {
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    service.ready().await.unwrap().call(http::Request::new(())).await.unwrap();
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_static("inherited-id"));
+    service.ready().await.unwrap().call(request).await.unwrap();
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_bytes(&[b'a'; tower_http_tracing::REQUEST_ID_MAX_LEN + 1]).unwrap());
+    service.ready().await.unwrap().call(request).await.unwrap();
+
+    let mut request = http::Request::new(());
+    request.headers_mut().insert(REQUEST_ID, http::HeaderValue::from_bytes(&[0xFF, 0xFE]).unwrap());
+    service.ready().await.unwrap().call(request).await.unwrap();
+
+    let stats = layer.stats();
+    assert_eq!(stats.spans_created, 4);
+    //No subscriber is installed
+    assert_eq!(stats.spans_disabled, 4);
+    assert_eq!(stats.ids_generated, 1);
+    assert_eq!(stats.ids_inherited, 3);
+    assert_eq!(stats.truncations, 1);
+    assert_eq!(stats.header_insert_failures, 0);
+}
+
+//Sends request with given headers, returning origin of its id
+async fn request_id_origin(layer: HttpRequestLayer, headers: &[(&'static str, &'static str)]) -> RequestIdOrigin {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|request: http::Request<()>| async move {
+        let info = request.extensions().get::<tower_http_tracing::RequestInfo>().expect("to have request info");
+        Ok::<_, core::convert::Infallible>(http::Response::new(info.request_id_origin))
+    });
+
+    let mut request = http::Request::new(());
+    for (name, value) in headers {
+        request.headers_mut().insert(*name, http::HeaderValue::from_static(value));
+    }
+    service.oneshot(request).await.unwrap().into_body()
+}
+
+#[tokio::test]
+async fn should_count_request_ids_by_their_origin() {
+    const TRACEPARENT: (&str, &str) = ("traceparent", "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01");
+
+    let layer = HttpRequestLayer::new_simple(my_span).with_propagation_extract(&[PropagationFormat::W3c])
+                                                     .with_request_id_policy(RequestIdPolicy::DeriveFromTrace);
+    assert_eq!(request_id_origin(layer.clone(), &[TRACEPARENT]).await, RequestIdOrigin::DerivedFromTrace);
+    assert_eq!(request_id_origin(layer.clone(), &[]).await, RequestIdOrigin::Generated);
+    assert_eq!(layer.stats().ids_inherited, 1);
+    assert_eq!(layer.stats().ids_generated, 1);
+
+    let layer = HttpRequestLayer::new_simple(my_span).with_request_id_policy(RequestIdPolicy::AlwaysGenerate);
+    let long_id: &'static str = "a".repeat(tower_http_tracing::REQUEST_ID_MAX_LEN + 1).leak();
+    assert_eq!(request_id_origin(layer.clone(), &[("x-request-id", long_id)]).await, RequestIdOrigin::Generated);
+    assert_eq!(layer.stats().ids_inherited, 0);
+    assert_eq!(layer.stats().ids_generated, 1);
+    assert_eq!(layer.stats().truncations, 1);
+
+    let layer = HttpRequestLayer::new_simple(my_span).with_request_id_validation(RequestIdValidation::Reject)
+                                                     .with_correlation_id_header(http::HeaderName::from_static("x-correlation-id"));
+    assert_eq!(request_id_origin(layer.clone(), &[("x-request-id", "bad id"), ("x-correlation-id", "correlation-id")]).await, RequestIdOrigin::Generated);
+    assert_eq!(layer.stats().ids_inherited, 0);
+    assert_eq!(layer.stats().ids_generated, 1);
+}
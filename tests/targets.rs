@@ -0,0 +1,65 @@
+use tower_http_tracing::{make_request_spanner, targets, HttpRequestLayer};
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use tower::{ServiceBuilder, ServiceExt};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::util::SubscriberInitExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+//Captures output of fmt subscriber
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+async fn request_with_filter(filter: &str) -> String {
+    let output = Output::default();
+    let writer = output.clone();
+    let subscriber = tracing_subscriber::fmt().with_env_filter(EnvFilter::new(filter))
+                                              .with_span_events(FmtSpan::CLOSE)
+                                              .with_ansi(false)
+                                              .with_writer(move || writer.clone())
+                                              .finish();
+    let _guard = subscriber.set_default();
+
+    let layer = HttpRequestLayer::new_simple(my_span).with_access_log(true);
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, io::Error>(http::Response::new(()))
+    });
+    service.oneshot(http::Request::builder().uri("/access").body(()).unwrap()).await.unwrap();
+
+    let output = output.0.lock().unwrap();
+    String::from_utf8(output.clone()).unwrap()
+}
+
+#[tokio::test]
+async fn should_emit_access_log_on_its_target() {
+    let output = request_with_filter("info").await;
+    assert!(output.contains("HANDLER"), "{output}");
+    assert!(output.contains(&format!("{}: GET /access http 200", targets::ACCESS)), "{output}");
+    assert!(output.contains("close"), "{output}");
+}
+
+#[tokio::test]
+async fn should_silence_access_log_while_keeping_spans() {
+    let output = request_with_filter(&format!("info,{}=off", targets::ACCESS)).await;
+    assert!(output.contains("HANDLER"), "{output}");
+    assert!(output.contains(r#"request{span.kind="server" http.request.method="GET""#), "{output}");
+    assert!(output.contains("close"), "{output}");
+    assert!(!output.contains(targets::ACCESS), "{output}");
+    assert!(!output.contains("GET /access"), "{output}");
+}
@@ -0,0 +1,94 @@
+use tower_http_tracing::time::{format_grpc_timeout, parse_grpc_timeout, parse_http_date, parse_retry_after};
+
+use std::time::{Duration, SystemTime};
+
+//Sun, 06 Nov 1994 08:49:37 GMT
+const DATE: u64 = 784111777;
+
+fn date() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(DATE)
+}
+
+#[test]
+fn should_parse_grpc_timeout_units() {
+    assert_eq!(parse_grpc_timeout(b"2H"), Some(Duration::from_secs(7200)));
+    assert_eq!(parse_grpc_timeout(b"3M"), Some(Duration::from_secs(180)));
+    assert_eq!(parse_grpc_timeout(b"5S"), Some(Duration::from_secs(5)));
+    assert_eq!(parse_grpc_timeout(b"250m"), Some(Duration::from_millis(250)));
+    assert_eq!(parse_grpc_timeout(b"100u"), Some(Duration::from_micros(100)));
+    assert_eq!(parse_grpc_timeout(b"42n"), Some(Duration::from_nanos(42)));
+    assert_eq!(parse_grpc_timeout(b"0S"), Some(Duration::ZERO));
+}
+
+#[test]
+fn should_reject_invalid_grpc_timeout() {
+    assert_eq!(parse_grpc_timeout(b""), None);
+    assert_eq!(parse_grpc_timeout(b"S"), None);
+    assert_eq!(parse_grpc_timeout(b"10"), None);
+    assert_eq!(parse_grpc_timeout(b"10s"), None);
+    assert_eq!(parse_grpc_timeout(b"1.5S"), None);
+    assert_eq!(parse_grpc_timeout(b"-1S"), None);
+    assert_eq!(parse_grpc_timeout(b" 1S"), None);
+}
+
+#[test]
+fn should_cap_grpc_timeout_digits() {
+    assert_eq!(parse_grpc_timeout(b"99999999S"), Some(Duration::from_secs(99_999_999)));
+    assert_eq!(parse_grpc_timeout(b"100000000S"), None);
+}
+
+#[test]
+fn should_format_grpc_timeout() {
+    assert_eq!(format_grpc_timeout(Duration::ZERO), "0n");
+    assert_eq!(format_grpc_timeout(Duration::from_nanos(42)), "42n");
+    assert_eq!(format_grpc_timeout(Duration::from_millis(250)), "250000u");
+    assert_eq!(format_grpc_timeout(Duration::from_secs(1)), "1000000u");
+    assert_eq!(format_grpc_timeout(Duration::from_secs(100)), "100000m");
+    assert_eq!(format_grpc_timeout(Duration::from_secs(200_000)), "200000S");
+    assert_eq!(format_grpc_timeout(Duration::from_secs(100_000_000)), "1666667M");
+    assert_eq!(format_grpc_timeout(Duration::from_secs(10_000_000_000)), "2777778H");
+    assert_eq!(format_grpc_timeout(Duration::MAX), "99999999H");
+}
+
+#[test]
+fn should_round_trip_grpc_timeout() {
+    for timeout in [Duration::from_nanos(1), Duration::from_millis(1500), Duration::from_secs(30), Duration::from_secs(86400)] {
+        let value = format_grpc_timeout(timeout);
+        assert_eq!(parse_grpc_timeout(value.as_bytes()), Some(timeout));
+    }
+}
+
+#[test]
+fn should_parse_http_date_formats() {
+    assert_eq!(parse_http_date(b"Sun, 06 Nov 1994 08:49:37 GMT"), Some(date()));
+    assert_eq!(parse_http_date(b"Sunday, 06-Nov-94 08:49:37 GMT"), Some(date()));
+    assert_eq!(parse_http_date(b"Sun Nov  6 08:49:37 1994"), Some(date()));
+}
+
+#[test]
+fn should_reject_invalid_http_date() {
+    assert_eq!(parse_http_date(b""), None);
+    assert_eq!(parse_http_date(b"Sun, 06 Nov 1994 08:49:37 UTC"), None);
+    assert_eq!(parse_http_date(b"Sun, 06 Foo 1994 08:49:37 GMT"), None);
+    assert_eq!(parse_http_date(b"Sun, 06 Nov 1994 24:49:37 GMT"), None);
+    assert_eq!(parse_http_date(b"Sunday, 06-Nov-1994 08:49:37 GMT"), None);
+    assert_eq!(parse_http_date(b"Sun Nov  6 08:49:37 1994 GMT"), None);
+}
+
+#[test]
+fn should_parse_retry_after_seconds() {
+    assert_eq!(parse_retry_after(&http::HeaderValue::from_static("120"), date()), Some(Duration::from_secs(120)));
+    assert_eq!(parse_retry_after(&http::HeaderValue::from_static(" 0 "), date()), Some(Duration::ZERO));
+    assert_eq!(parse_retry_after(&http::HeaderValue::from_static("-1"), date()), None);
+    assert_eq!(parse_retry_after(&http::HeaderValue::from_static("soon"), date()), None);
+}
+
+#[test]
+fn should_parse_retry_after_date() {
+    let now = date() - Duration::from_secs(30);
+    assert_eq!(parse_retry_after(&http::HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"), now), Some(Duration::from_secs(30)));
+    assert_eq!(parse_retry_after(&http::HeaderValue::from_static("Sunday, 06-Nov-94 08:49:37 GMT"), now), Some(Duration::from_secs(30)));
+    assert_eq!(parse_retry_after(&http::HeaderValue::from_static("Sun Nov  6 08:49:37 1994"), now), Some(Duration::from_secs(30)));
+    //Past date
+    assert_eq!(parse_retry_after(&http::HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"), date() + Duration::from_secs(1)), Some(Duration::ZERO));
+}
@@ -0,0 +1,56 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, TlsInfo};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn request(host: Option<&'static str>, tls: Option<TlsInfo>) {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    if let Some(host) = host {
+        req.headers_mut().insert(http::header::HOST, http::HeaderValue::from_static(host));
+    }
+    if let Some(tls) = tls {
+        req.extensions_mut().insert(tls);
+    }
+    service.oneshot(req).await.unwrap();
+}
+
+fn tls(cipher: Option<&'static str>, sni: Option<&str>) -> TlsInfo {
+    TlsInfo {
+        protocol_version: "1.3",
+        cipher,
+        sni: sni.map(ToOwned::to_owned),
+    }
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_tls_info() {
+    request(Some("example.com"), Some(tls(Some("TLS13_AES_128_GCM_SHA256"), Some("sni.example.com")))).await;
+    assert!(logs_contain(r#"server.address="example.com" http.request_id"#));
+    assert!(logs_contain(r#"tls.protocol.version="1.3" tls.cipher="TLS13_AES_128_GCM_SHA256" network.protocol.name"#));
+    assert!(!logs_contain("sni.example.com"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_sni_as_server_address_without_host() {
+    request(None, Some(tls(None, Some("sni.example.com")))).await;
+    assert!(logs_contain(r#"server.address="sni.example.com" http.request_id"#));
+    assert!(logs_contain(r#"tls.protocol.version="1.3" network.protocol.name"#));
+    assert!(!logs_contain("tls.cipher"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_tls_without_extension() {
+    request(None, None).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("tls."));
+    assert!(!logs_contain("server.address"));
+}
@@ -0,0 +1,32 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn request(layer: HttpRequestLayer) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    req.headers_mut().insert(tower_http_tracing::REQUEST_ID, http::HeaderValue::from_static("0af7651916cd43dd"));
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_request_id_as_trace_id_without_trace_context() {
+    request(HttpRequestLayer::new_simple(my_span).with_record_trace_ids()).await;
+    assert!(logs_contain(r#"trace_id="0af7651916cd43dd""#));
+    assert!(!logs_contain("span_id"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_record_trace_ids_by_default() {
+    request(HttpRequestLayer::new_simple(my_span)).await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("trace_id="));
+}
@@ -0,0 +1,101 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer, SpanHandle};
+use tower_http_tracing::opentelemetry::trace::{TraceContextExt, TracerProvider};
+use tower_http_tracing::opentelemetry::tracing_opentelemetry::{self, OpenTelemetrySpanExt};
+use tower_http_tracing::propagation::{self, TraceContext, TRACEPARENT, TRACERESPONSE};
+
+use std::sync::{Arc, Mutex};
+
+use tower::{ServiceBuilder, ServiceExt};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+const PARENT: TraceContext = TraceContext {
+    trace_id: 0x0af7651916cd43dd8448eb211c80319c,
+    span_id: 0xb7ad6b7169203331,
+    sampled: true,
+};
+
+async fn request(layer: HttpRequestLayer) -> (http::Response<()>, u64) {
+    let provider = tower_http_tracing::opentelemetry::sdk::trace::SdkTracerProvider::builder().build();
+    let _guard = tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test"))).set_default();
+
+    let server_span_id = Arc::new(Mutex::new(0));
+    let output = server_span_id.clone();
+    let service = ServiceBuilder::new().layer(layer).service_fn(move |req: http::Request<()>| {
+        let span = req.extensions().get::<SpanHandle>().unwrap().span().clone();
+        *output.lock().unwrap() = u64::from_be_bytes(span.context().span().span_context().span_id().to_bytes());
+        async move {
+            Ok::<_, std::io::Error>(http::Response::new(()))
+        }
+    });
+
+    let mut req = http::Request::new(());
+    propagation::PropagationFormat::W3c.inject(req.headers_mut(), &PARENT);
+    let response = service.oneshot(req).await.unwrap();
+    let server_span_id = *server_span_id.lock().unwrap();
+    (response, server_span_id)
+}
+
+#[tokio::test]
+async fn should_inject_server_span_context() {
+    let (response, server_span_id) = request(HttpRequestLayer::new_simple(my_span).with_trace_response_header()).await;
+
+    assert_ne!(server_span_id, 0);
+    assert_ne!(server_span_id, PARENT.span_id);
+    let expected = format!("00-0af7651916cd43dd8448eb211c80319c-{server_span_id:016x}-01");
+    assert_eq!(response.headers().get(TRACERESPONSE).unwrap(), expected.as_str());
+    assert_eq!(propagation::extract_trace_response(response.headers()), Some(TraceContext { span_id: server_span_id, ..PARENT }));
+}
+
+#[tokio::test]
+async fn should_not_inject_trace_response_by_default() {
+    let (response, _) = request(HttpRequestLayer::new_simple(my_span)).await;
+    assert!(response.headers().contains_key(TRACEPARENT));
+    assert!(!response.headers().contains_key(TRACERESPONSE));
+}
+
+//Captures values of `trace_id` and `span_id`
+#[derive(Clone, Default)]
+struct TraceIdsCapture(Arc<Mutex<Vec<String>>>);
+
+struct TraceIdsVisitor<'a>(&'a mut Vec<String>);
+
+impl tracing::field::Visit for TraceIdsVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+        if field.name() == "trace_id" || field.name() == "span_id" {
+            self.0.push(format!("{}={value:?}", field.name()));
+        }
+    }
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for TraceIdsCapture {
+    fn on_record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>, _: tracing_subscriber::layer::Context<'_, S>) {
+        values.record(&mut TraceIdsVisitor(&mut self.0.lock().unwrap()));
+    }
+}
+
+#[tokio::test]
+async fn should_record_server_span_trace_ids() {
+    let provider = tower_http_tracing::opentelemetry::sdk::trace::SdkTracerProvider::builder().build();
+    let capture = TraceIdsCapture::default();
+    let _guard = tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")))
+                                               .with(capture.clone())
+                                               .set_default();
+
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span).with_record_trace_ids()).service_fn(|req: http::Request<()>| async move {
+        let span = req.extensions().get::<SpanHandle>().unwrap().span().clone();
+        let span_id = u64::from_be_bytes(span.context().span().span_context().span_id().to_bytes());
+        Ok::<_, std::io::Error>(http::Response::new(span_id))
+    });
+
+    let mut req = http::Request::new(());
+    propagation::PropagationFormat::W3c.inject(req.headers_mut(), &PARENT);
+    let server_span_id = service.oneshot(req).await.unwrap().into_body();
+
+    assert_eq!(*capture.0.lock().unwrap(), [
+        "trace_id=0af7651916cd43dd8448eb211c80319c".to_owned(),
+        format!("span_id={server_span_id:016x}"),
+    ]);
+}
@@ -0,0 +1,46 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn request(layer: HttpRequestLayer, uri: String) {
+    let service = ServiceBuilder::new().layer(layer).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, std::io::Error>(http::Response::new(()))
+    });
+
+    let mut req = http::Request::new(());
+    *req.uri_mut() = uri.parse().unwrap();
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_truncate_uri_above_limit() {
+    let uri = format!("/{}?query={}", "p".repeat(40), "q".repeat(60_000));
+    request(HttpRequestLayer::new_simple(my_span).with_max_uri_len(16), uri).await;
+
+    assert!(logs_contain(r#"url.path="/ppppppppppppppp" url.length=60048 url.truncated=true"#));
+    assert!(!logs_contain("url.query"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_truncate_uri_above_default_limit() {
+    let uri = format!("/path?query={}", "q".repeat(tower_http_tracing::DEFAULT_MAX_URI_LEN));
+    request(HttpRequestLayer::new_simple(my_span), uri).await;
+
+    assert!(logs_contain(r#"url.path="/path" url.length=8204 url.truncated=true"#));
+    assert!(!logs_contain("url.query"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_truncate_uri_within_limit() {
+    request(HttpRequestLayer::new_simple(my_span).with_max_uri_len(16), "/path?query=1".to_owned()).await;
+
+    assert!(logs_contain(r#"url.path="/path" url.query="query=1""#));
+    assert!(!logs_contain("url.truncated"));
+    assert!(!logs_contain("url.length"));
+}
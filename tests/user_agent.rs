@@ -0,0 +1,55 @@
+use tower_http_tracing::{make_request_spanner, HttpRequestLayer};
+
+use tower::{ServiceBuilder, ServiceExt};
+
+make_request_spanner!(my_span("request", tracing::Level::INFO));
+
+async fn request(user_agent: &str) {
+    let service = ServiceBuilder::new().layer(HttpRequestLayer::new_simple(my_span)).service_fn(|_: http::Request<()>| async move {
+        tracing::info!("HANDLER");
+        Ok::<_, core::convert::Infallible>(http::Response::new(()))
+    });
+
+    let req = http::Request::builder().header(http::header::USER_AGENT, user_agent).body(()).unwrap();
+    service.oneshot(req).await.unwrap();
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_curl_user_agent() {
+    request("curl/8.4.0").await;
+    assert!(logs_contain(r#"user_agent.original="curl/8.4.0" user_agent.name="curl" user_agent.version="8.4.0""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_leading_product_of_browser_user_agent() {
+    request("Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0").await;
+    assert!(logs_contain(r#"user_agent.name="Mozilla" user_agent.version="5.0""#));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_record_user_agent_without_version() {
+    request("  my-client (internal)").await;
+    assert!(logs_contain(r#"user_agent.name="my-client" network.protocol.name"#));
+    assert!(!logs_contain("user_agent.version"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_parse_empty_user_agent() {
+    request("").await;
+    assert!(logs_contain("HANDLER"));
+    assert!(!logs_contain("user_agent.name"));
+    assert!(!logs_contain("user_agent.version"));
+}
+
+#[tokio::test]
+#[tracing_test::traced_test]
+async fn should_not_parse_too_long_user_agent() {
+    let user_agent = format!("curl/8.4.0 {}", "x".repeat(512));
+    request(&user_agent).await;
+    assert!(logs_contain("user_agent.original"));
+    assert!(!logs_contain("user_agent.name"));
+}